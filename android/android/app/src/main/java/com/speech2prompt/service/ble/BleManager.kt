@@ -308,7 +308,13 @@ class BleManager @Inject constructor(
         
         when (decryptedMessage.messageType) {
             MessageType.ACK -> handleAck(decryptedMessage)
-            MessageType.PAIR_ACK -> handlePairAck(decryptedMessage)
+            MessageType.PAIR_ACK -> {
+                handlePairAck(decryptedMessage)
+                // Let the desktop know its response arrived (queued until paired)
+                scope.launch {
+                    sendMessage(Message.ack(decryptedMessage.timestamp))
+                }
+            }
             MessageType.HEARTBEAT -> {
                 // Respond with ACK
                 scope.launch {
@@ -16,6 +16,8 @@
 
 use uuid::Uuid;
 
+pub use speech2prompt_protocol::packet::config;
+
 /// Speech2Prompt GATT service UUID.
 pub const SERVICE_UUID: Uuid = Uuid::from_u128(0xa1b2c3d4_e5f6_7890_abcd_ef1234567890);
 
@@ -35,12 +37,6 @@ pub const STATUS_UUID: Uuid = Uuid::from_u128(0xa1b2c3d4_e5f6_7890_abcd_ef123456
 /// Properties: Read
 pub const MTU_INFO_UUID: Uuid = Uuid::from_u128(0xa1b2c3d4_e5f6_7890_abcd_ef1234567894);
 
-/// BLE packet flags.
-pub mod flags {
-    pub const FIRST: u8 = 0x08; // First packet of message
-    pub const LAST: u8 = 0x04; // Last packet of message
-}
-
 /// BLE status codes (for Status characteristic).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
@@ -48,6 +44,7 @@ pub enum StatusCode {
     Idle = 0x00,            // Not paired
     AwaitingPairing = 0x01, // Awaiting pairing
     Paired = 0x02,          // Paired and ready
+    Busy = 0x03,            // Paired, typing text or running a command
 }
 
 impl StatusCode {
@@ -56,29 +53,6 @@ impl StatusCode {
     }
 }
 
-/// BLE configuration constants.
-pub mod config {
-    /// Default MTU (minimum for all BLE devices).
-    pub const DEFAULT_MTU: usize = 23;
-
-    /// ATT protocol overhead (3 bytes).
-    pub const ATT_OVERHEAD: usize = 3;
-
-    /// Packet header size (4 bytes for first packet, 2 for continuation).
-    pub const HEADER_SIZE_FIRST: usize = 4;
-    pub const HEADER_SIZE_CONTINUATION: usize = 2;
-
-    /// Calculate effective payload size for a given MTU.
-    pub fn effective_payload_size(mtu: usize, is_first: bool) -> usize {
-        mtu - ATT_OVERHEAD
-            - if is_first {
-                HEADER_SIZE_FIRST
-            } else {
-                HEADER_SIZE_CONTINUATION
-            }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -95,15 +69,4 @@ mod tests {
             "a1b2c3d4-e5f6-7890-abcd-ef1234567891"
         );
     }
-
-    #[test]
-    fn test_effective_payload() {
-        // With default MTU (23 bytes)
-        assert_eq!(config::effective_payload_size(23, true), 16); // 23 - 3 - 4
-        assert_eq!(config::effective_payload_size(23, false), 18); // 23 - 3 - 2
-
-        // With target MTU (512 bytes)
-        assert_eq!(config::effective_payload_size(512, true), 505); // 512 - 3 - 4
-        assert_eq!(config::effective_payload_size(512, false), 507); // 512 - 3 - 2
-    }
 }
@@ -16,21 +16,84 @@
 
 use anyhow::{anyhow, Result};
 use bluer::gatt::local::{
-    characteristic_control, Application, ApplicationHandle, Characteristic, CharacteristicNotify,
-    CharacteristicNotifyMethod, CharacteristicRead, CharacteristicReadRequest,
-    CharacteristicWrite, CharacteristicWriteMethod, CharacteristicWriteRequest, Service,
+    characteristic_control, Application, ApplicationHandle, Characteristic, CharacteristicNotifier,
+    CharacteristicNotify, CharacteristicNotifyMethod, CharacteristicRead,
+    CharacteristicReadRequest, CharacteristicWrite, CharacteristicWriteMethod,
+    CharacteristicWriteRequest, Service,
 };
 use bluer::adv::{Advertisement, AdvertisementHandle};
 use bluer::{Adapter, Address};
 use std::sync::Arc;
-use tokio::sync::{mpsc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch, Mutex, RwLock};
+use tokio::task::JoinHandle;
 use tracing::{debug, error, info, warn};
 
 use super::ble_constants::*;
-use super::protocol::{Message, MessageType, PairAckPayload, PairRequestPayload, WordPayload};
-use super::reassembler::{chunk_message, MessageReassembler};
-use crate::crypto::CryptoContext;
-use crate::crypto::ecdh::EcdhKeypair;
+use crate::logging::redacted;
+use crate::pairing;
+use crate::qr::QrPayload;
+use speech2prompt_protocol::crypto::ecdh::EcdhKeypair;
+use speech2prompt_protocol::{
+    chunk_message, AckTimeout, CryptoContext, HeartbeatPayload, Message, MessageReassembler,
+    MessageType, PairAckPayload, PairRequestPayload, PendingAcks, RekeyPolicy, SessionRekey,
+    WordPayload,
+};
+
+/// How long the phone gets to acknowledge a message we sent.
+const ACK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long shutdown waits for notification loops to send what is queued.
+const NOTIFY_LOOP_EXIT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Wait before the second try to power on the adapter, doubled after each.
+const POWER_ON_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest wait between tries to power on the adapter.
+const POWER_ON_MAX_DELAY: Duration = Duration::from_secs(8);
+
+/// Wait after failed power-on try `attempt` (0-based).
+fn power_on_delay(attempt: u32) -> Duration {
+    POWER_ON_BASE_DELAY
+        .saturating_mul(1 << attempt.min(16))
+        .min(POWER_ON_MAX_DELAY)
+}
+
+/// Get the default adapter and power it on, trying up to `attempts` times.
+async fn power_on_adapter(session: &bluer::Session, attempts: u32) -> Result<Adapter> {
+    let mut attempt = 0;
+    loop {
+        let result = async {
+            let adapter = session.default_adapter().await?;
+            if !adapter.is_powered().await? {
+                info!("Powering on Bluetooth adapter...");
+                adapter.set_powered(true).await?;
+            }
+            Ok::<_, bluer::Error>(adapter)
+        }
+        .await;
+
+        match result {
+            Ok(adapter) => return Ok(adapter),
+            Err(e) if attempt + 1 < attempts => {
+                let delay = power_on_delay(attempt);
+                warn!(
+                    "Bluetooth adapter not ready ({}), retrying in {:?}",
+                    e, delay
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(anyhow!(
+                    "Bluetooth adapter not ready after {} tries: {}",
+                    attempts,
+                    e
+                ))
+            }
+        }
+    }
+}
 
 /// Events emitted by the GATT server.
 #[derive(Debug, Clone)]
@@ -42,27 +105,37 @@ pub enum ConnectionEvent {
         word: String,
         seq: Option<u64>,  // Optional for backward compatibility
         session: String,
+        /// Last word of an utterance: flush held-back words now.
+        eou: bool,
     },
     /// Command received from the Android app.
     CommandReceived(String),
     /// Connection established.
-    Connected { device_name: String },
+    Connected {
+        device_id: String,
+        device_name: String,
+    },
     /// Connection closed.
-    #[allow(dead_code)]
     Disconnected,
     /// Pairing requested.
     PairRequested { 
         device_id: String,
         device_name: Option<String>,
+        /// The phone's X25519 public key (base64).
+        public_key: String,
+        /// The phone scanned the pairing QR code.
+        scanned: bool,
     },
+    /// Battery level of the phone in percent, from its heartbeats.
+    Battery(u8),
     /// Error occurred.
     #[allow(dead_code)]
     Error(String),
 }
 
 /// State of the connection.
-#[derive(Debug, Clone, PartialEq)]
-enum ConnectionState {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
     /// Waiting for pairing.
     AwaitingPair,
     /// Paired and authenticated.
@@ -79,6 +152,8 @@ struct PendingPairing {
 
 /// Shared state for the GATT server.
 struct ServerState {
+    /// Central the current session belongs to.
+    peer: Option<Address>,
     reassembler: MessageReassembler,
     crypto: Option<Arc<CryptoContext>>,
     device_id: Option<String>,
@@ -86,11 +161,25 @@ struct ServerState {
     negotiated_mtu: usize,
     status_code: StatusCode,
     pending_pairing: Option<PendingPairing>,
+    /// Messages sent to the phone that it hasn't acknowledged yet.
+    pending_acks: PendingAcks<Message>,
+    /// One-time keypair shown as a QR code. Not part of the session, the
+    /// phone connects after scanning it.
+    qr_keypair: Option<EcdhKeypair>,
+    /// Session key rotation, once paired.
+    rekey: Option<SessionRekey>,
+    /// When to rotate the session key.
+    rekey_policy: RekeyPolicy,
+    /// Close a session without dictation for this long.
+    idle_timeout: Option<Duration>,
+    /// Last TEXT, WORD or COMMAND, or when pairing completed.
+    last_activity: Option<Instant>,
 }
 
 impl ServerState {
     fn new() -> Self {
         Self {
+            peer: None,
             reassembler: MessageReassembler::new(),
             crypto: None,
             device_id: None,
@@ -98,10 +187,309 @@ impl ServerState {
             negotiated_mtu: config::DEFAULT_MTU,
             status_code: StatusCode::Idle,
             pending_pairing: None,
+            pending_acks: PendingAcks::new(ACK_TIMEOUT),
+            qr_keypair: None,
+            rekey: None,
+            rekey_policy: RekeyPolicy::default(),
+            idle_timeout: None,
+            last_activity: None,
+        }
+    }
+
+    /// Note a write from `peer`, starting a clean session if it's a new central.
+    ///
+    /// Returns whether the session was reset.
+    fn begin_session(&mut self, peer: Address) -> bool {
+        if self.peer == Some(peer) {
+            return false;
+        }
+        if self.peer.is_some() {
+            info!("New central {} connected, resetting session state", peer);
+        }
+        self.reset_session();
+        self.peer = Some(peer);
+        true
+    }
+
+    /// Drop all state of the previous session.
+    ///
+    /// Keeps partial packets, keys and pending pairings from leaking into the
+    /// next session.
+    fn reset_session(&mut self) {
+        self.reassembler.reset();
+        self.crypto = None;
+        self.device_id = None;
+        self.state = ConnectionState::AwaitingPair;
+        self.negotiated_mtu = config::DEFAULT_MTU;
+        self.status_code = StatusCode::Idle;
+        self.pending_pairing = None;
+        self.pending_acks.clear();
+        self.rekey = None;
+        self.last_activity = None;
+    }
+
+    /// Whether the session saw no dictation for the idle timeout.
+    fn is_idle(&self, now: Instant) -> bool {
+        if self.state != ConnectionState::Authenticated {
+            return false;
+        }
+        match (self.idle_timeout, self.last_activity) {
+            (Some(timeout), Some(last)) => now.duration_since(last) >= timeout,
+            _ => false,
+        }
+    }
+
+    /// Desktop keypair for a pairing request, and whether the phone scanned it.
+    ///
+    /// A phone that sends the key of the QR code on screen gets that keypair,
+    /// once. Anything else gets a fresh keypair and needs confirmation.
+    fn pairing_keypair(&mut self, scanned_key: Option<&str>) -> (EcdhKeypair, bool) {
+        if let Some(key) = scanned_key {
+            if let Some(keypair) = self.qr_keypair.take_if(|k| k.public_key_base64() == key) {
+                return (keypair, true);
+            }
+            warn!("Scanned pairing key is unknown or expired, asking for confirmation");
+        }
+        (EcdhKeypair::generate(), false)
+    }
+
+    /// Take the pending pairing request, if it is still the one from
+    /// `device_id` with `public_key`.
+    ///
+    /// Another PAIR_REQ or a new central can replace the request while the
+    /// user decides, and the answer must not apply to the replacement.
+    fn take_pending_pairing(
+        &mut self,
+        device_id: &str,
+        public_key: &str,
+    ) -> Result<PendingPairing> {
+        if self.pending_pairing.is_none() {
+            return Err(anyhow!("No pending pairing request"));
+        }
+        self.pending_pairing
+            .take_if(|p| p.android_device_id == device_id && p.android_public_key == public_key)
+            .ok_or_else(|| anyhow!("Pairing request from {} was replaced by another", device_id))
+    }
+
+    /// Derive the session key for the pending pairing request the user
+    /// approved, from `device_id` with `public_key`.
+    ///
+    /// Returns the `PAIR_ACK` to send and the name of the phone.
+    fn complete_pairing(
+        &mut self,
+        linux_device_id: &str,
+        device_id: &str,
+        public_key: &str,
+    ) -> Result<(Message, String)> {
+        let pending = self.take_pending_pairing(device_id, public_key)?;
+
+        // Get desktop public key before consuming keypair
+        let desktop_public_key = pending.desktop_keypair.public_key_base64();
+
+        // Compute ECDH shared secret
+        let shared_secret = pending
+            .desktop_keypair
+            .compute_shared_secret_base64(&pending.android_public_key)?;
+
+        // Derive crypto context from ECDH shared secret
+        let crypto =
+            CryptoContext::from_ecdh(&shared_secret, &pending.android_device_id, linux_device_id);
+
+        // Create PAIR_ACK with desktop's public key
+        let payload = PairAckPayload::success_with_key(linux_device_id, desktop_public_key);
+        let response = Message::new(MessageType::PairAck, payload.to_json()?);
+
+        // Update state
+        self.crypto = Some(Arc::new(crypto));
+        self.rekey = Some(SessionRekey::new(
+            &pending.android_device_id,
+            linux_device_id,
+            Instant::now(),
+        ));
+        self.state = ConnectionState::Authenticated;
+        self.status_code = StatusCode::Paired;
+        self.last_activity = Some(Instant::now());
+
+        info!("Pairing completed with device: {}", pending.android_device_id);
+
+        // A repeated PAIR_ACK would restart the phone's key exchange, so only
+        // watch for a lost one
+        self.pending_acks
+            .track(response.timestamp, response.clone(), 0, Instant::now());
+
+        let device_name = pending
+            .android_device_name
+            .unwrap_or(pending.android_device_id);
+        Ok((response, device_name))
+    }
+
+    /// Note a message under the session key, and start rotating the key if
+    /// it is due.
+    ///
+    /// `counted` says whether the message counts towards the message limit.
+    /// Returns the `REKEY` request to send.
+    fn rekey_if_due(&mut self, counted: bool, now: Instant) -> Option<Message> {
+        let rekey = self.rekey.as_mut()?;
+        if counted {
+            rekey.count_message();
+        }
+        if !rekey.is_due(&self.rekey_policy, now) {
+            return None;
+        }
+        match rekey.start(now) {
+            Ok(request) => {
+                info!("🔑 Rotating session key");
+                Some(request)
+            }
+            Err(e) => {
+                error!("Failed to start re-key: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Raise the MTU to what a write from the phone reports.
+    ///
+    /// The MTU only grows during a session. Returns whether it changed.
+    fn update_mtu(&mut self, mtu: usize) -> bool {
+        if mtu <= self.negotiated_mtu {
+            return false;
+        }
+        info!("MTU updated: {} -> {} bytes", self.negotiated_mtu, mtu);
+        self.negotiated_mtu = mtu;
+        true
+    }
+
+    /// Switch the status of a paired session between Busy and Paired.
+    ///
+    /// Returns the new status if it changed.
+    fn set_busy(&mut self, busy: bool) -> Option<StatusCode> {
+        if self.state != ConnectionState::Authenticated {
+            return None;
+        }
+        let status = if busy { StatusCode::Busy } else { StatusCode::Paired };
+        if self.status_code == status {
+            return None;
+        }
+        self.status_code = status;
+        Some(status)
+    }
+
+    /// Feed a written packet to the reassembler.
+    ///
+    /// Returns the message once it is complete. A corrupted message is
+    /// dropped and reported on `event_tx`, otherwise it would vanish silently.
+    async fn receive_packet(
+        &mut self,
+        data: &[u8],
+        event_tx: &mpsc::Sender<ConnectionEvent>,
+    ) -> Option<Vec<u8>> {
+        match self.reassembler.try_process_packet(data) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("Dropped corrupted message: {}", e);
+                let _ = event_tx
+                    .send(ConnectionEvent::Error(format!("Message from phone lost: {}", e)))
+                    .await;
+                None
+            }
         }
     }
 }
 
+/// BlueZ registrations that make the server visible to phones.
+///
+/// Dropping a handle unregisters it.
+struct Registrations<A, V> {
+    app: Option<A>,
+    adv: Option<V>,
+}
+
+impl<A, V> Registrations<A, V> {
+    fn new() -> Self {
+        Self {
+            app: None,
+            adv: None,
+        }
+    }
+
+    /// Whether the GATT application and the advertisement are registered.
+    fn is_serving(&self) -> bool {
+        self.app.is_some() && self.adv.is_some()
+    }
+
+    /// Stop advertising and unregister the GATT application.
+    fn clear(&mut self) {
+        if self.adv.take().is_some() {
+            info!("BLE advertising stopped");
+        }
+        if self.app.take().is_some() {
+            info!("GATT application unregistered");
+        }
+    }
+}
+
+/// Where a notification loop sends its data: the phone, in production.
+trait NotificationSink {
+    async fn notify(&mut self, data: Vec<u8>) -> Result<()>;
+}
+
+impl NotificationSink for CharacteristicNotifier {
+    async fn notify(&mut self, data: Vec<u8>) -> Result<()> {
+        Ok(CharacteristicNotifier::notify(self, data).await?)
+    }
+}
+
+/// Send the data queued on `rx` to `sink` until the server closes the
+/// channel.
+///
+/// Fails if sending does, e.g. because the phone unsubscribed.
+async fn forward_notifications(
+    rx: &Mutex<mpsc::Receiver<Vec<u8>>>,
+    sink: &mut impl NotificationSink,
+) -> Result<()> {
+    loop {
+        let data = {
+            let mut rx = rx.lock().await;
+            rx.recv().await
+        };
+        let Some(data) = data else {
+            return Ok(());
+        };
+        debug!("Sending notification: {} bytes", data.len());
+        sink.notify(data).await?;
+    }
+}
+
+/// Counts the running notification loops, so shutdown can wait for them.
+#[derive(Clone)]
+struct NotifyLoops(Arc<watch::Sender<usize>>);
+
+/// Marks a notification loop as running until dropped.
+struct NotifyLoopGuard(Arc<watch::Sender<usize>>);
+
+impl NotifyLoops {
+    fn new() -> Self {
+        Self(Arc::new(watch::channel(0).0))
+    }
+
+    fn enter(&self) -> NotifyLoopGuard {
+        self.0.send_modify(|running| *running += 1);
+        NotifyLoopGuard(self.0.clone())
+    }
+
+    /// Wait until no notification loop runs.
+    async fn wait_exited(&self) {
+        let _ = self.0.subscribe().wait_for(|running| *running == 0).await;
+    }
+}
+
+impl Drop for NotifyLoopGuard {
+    fn drop(&mut self) {
+        self.0.send_modify(|running| *running -= 1);
+    }
+}
+
 /// GATT server for Speech2Prompt.
 pub struct GattServer {
     adapter: Adapter,
@@ -111,30 +499,31 @@ pub struct GattServer {
     state: Arc<RwLock<ServerState>>,
     response_tx: Arc<Mutex<Option<mpsc::Sender<Vec<u8>>>>>,
     status_tx: Arc<Mutex<Option<mpsc::Sender<Vec<u8>>>>>,
-    _adv_handle: Option<AdvertisementHandle>,
-    _app_handle: Option<ApplicationHandle>,
+    notify_loops: NotifyLoops,
+    registrations: Registrations<ApplicationHandle, AdvertisementHandle>,
+    ack_sweeper: Option<JoinHandle<()>>,
 }
 
 impl GattServer {
     /// Create a new GATT server.
-    pub async fn new(event_tx: mpsc::Sender<ConnectionEvent>) -> Result<Self> {
+    ///
+    /// Tries up to `power_on_attempts` times to find and power on the
+    /// adapter, see [`power_on_delay`].
+    pub async fn new(
+        event_tx: mpsc::Sender<ConnectionEvent>,
+        power_on_attempts: u32,
+    ) -> Result<Self> {
         info!("Initializing BLE GATT server...");
 
         // Create BlueZ session
         let session = bluer::Session::new().await?;
         info!("BlueZ session created");
 
-        // Get the default adapter
-        let adapter = session.default_adapter().await?;
+        // Get the default adapter and ensure it is powered on
+        let adapter = power_on_adapter(&session, power_on_attempts).await?;
         let adapter_name = adapter.name();
         info!("Using Bluetooth adapter: {}", adapter_name);
 
-        // Ensure adapter is powered on
-        if !adapter.is_powered().await? {
-            info!("Powering on Bluetooth adapter...");
-            adapter.set_powered(true).await?;
-        }
-
         // Get adapter address as device ID
         let address = adapter.address().await?;
         let linux_device_id = format!("linux-{}", address.to_string().replace(':', ""));
@@ -148,11 +537,17 @@ impl GattServer {
             state: Arc::new(RwLock::new(ServerState::new())),
             response_tx: Arc::new(Mutex::new(None)),
             status_tx: Arc::new(Mutex::new(None)),
-            _adv_handle: None,
-            _app_handle: None,
+            notify_loops: NotifyLoops::new(),
+            registrations: Registrations::new(),
+            ack_sweeper: None,
         })
     }
 
+    /// Get the name of the adapter, e.g. "hci0".
+    pub fn adapter_name(&self) -> &str {
+        self.adapter.name()
+    }
+
     /// Get the Linux device ID.
     #[allow(dead_code)]
     pub fn device_id(&self) -> &str {
@@ -165,6 +560,18 @@ impl GattServer {
         Ok(self.adapter.address().await?)
     }
 
+    /// Get the ATT MTU negotiated with the connected phone.
+    pub async fn negotiated_mtu(&self) -> usize {
+        self.state.read().await.negotiated_mtu
+    }
+
+    /// Get whether a phone is paired, without waiting for its
+    /// [`ConnectionEvent`]s.
+    #[allow(dead_code)]
+    pub async fn connection_state(&self) -> ConnectionState {
+        self.state.read().await.state
+    }
+
     /// Set the device name.
     pub async fn set_name(&mut self, name: &str) -> Result<()> {
         self.device_name = name.to_string();
@@ -175,16 +582,96 @@ impl GattServer {
 
     /// Start the GATT server and advertising.
     pub async fn start(&mut self) -> Result<()> {
+        if self.registrations.is_serving() {
+            warn!("GATT server already started");
+            return Ok(());
+        }
+
         // Register GATT service
         self.register_gatt_service().await?;
 
         // Start advertising
         self.start_advertising().await?;
 
+        self.ack_sweeper = Some(self.spawn_ack_sweeper());
+
         info!("GATT server started successfully");
         Ok(())
     }
 
+    /// Periodically resend or give up on messages the phone didn't ACK, and
+    /// close the session once it is idle.
+    fn spawn_ack_sweeper(&self) -> JoinHandle<()> {
+        let state = self.state.clone();
+        let response_tx = self.response_tx.clone();
+        let status_tx = self.status_tx.clone();
+        let event_tx = self.event_tx.clone();
+        let adapter = self.adapter.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(ACK_TIMEOUT / 4);
+            loop {
+                interval.tick().await;
+                let mut state_guard = state.write().await;
+                for timeout in state_guard.pending_acks.expire(Instant::now()) {
+                    match timeout {
+                        AckTimeout::Resend(message) => {
+                            debug!("No ACK for {:?}, resending", message.message_type);
+                            Self::send_response_internal(
+                                message,
+                                &state_guard,
+                                response_tx.clone(),
+                            )
+                            .await;
+                        }
+                        AckTimeout::GaveUp { timestamp, item } => {
+                            warn!(
+                                "Phone never acknowledged {:?} sent at {}",
+                                item.message_type, timestamp
+                            );
+                        }
+                    }
+                }
+
+                if state_guard.is_idle(Instant::now()) {
+                    info!(
+                        "No dictation for {:?}, closing the session",
+                        state_guard.idle_timeout.unwrap_or_default()
+                    );
+                    let peer = state_guard.peer.take();
+                    state_guard.reset_session();
+                    drop(state_guard);
+                    Self::close_idle_session(peer, &adapter, &status_tx, &event_tx).await;
+                }
+            }
+        })
+    }
+
+    /// Tell the phone the session is over and disconnect it.
+    ///
+    /// Advertising stays registered, so BlueZ advertises again once the
+    /// link is down and the phone has to pair again to continue.
+    async fn close_idle_session(
+        peer: Option<Address>,
+        adapter: &Adapter,
+        status_tx: &Mutex<Option<mpsc::Sender<Vec<u8>>>>,
+        event_tx: &mpsc::Sender<ConnectionEvent>,
+    ) {
+        if let Some(ref tx) = *status_tx.lock().await {
+            let _ = tx.send(StatusCode::Idle.as_bytes()).await;
+        }
+        if let Some(peer) = peer {
+            let result = match adapter.device(peer) {
+                Ok(device) => device.disconnect().await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                warn!("Failed to disconnect idle phone {}: {}", peer, e);
+            }
+        }
+        let _ = event_tx.send(ConnectionEvent::Disconnected).await;
+    }
+
     /// Register the GATT service with BlueZ.
     async fn register_gatt_service(&mut self) -> Result<()> {
         let state = self.state.clone();
@@ -241,40 +728,16 @@ impl GattServer {
             uuid: RESPONSE_TX_UUID,
             notify: Some(CharacteristicNotify {
                 notify: true,
-                method: CharacteristicNotifyMethod::Fun(Box::new(move |mut notifier| {
-                    let resp_notify_rx = resp_notify_rx.clone();
-                    
-                    Box::pin(async move {
-                        debug!("Response TX notification loop started");
-                        loop {
-                            let data = {
-                                let mut rx = resp_notify_rx.lock().await;
-                                rx.recv().await
-                            };
-                            
-                            match data {
-                                Some(data) => {
-                                    debug!("Sending notification: {} bytes", data.len());
-                                    if let Err(e) = notifier.notify(data).await {
-                                        error!("Failed to send notification: {}", e);
-                                        break;
-                                    }
-                                    debug!("Notification sent successfully");
-                                }
-                                None => {
-                                    info!("Response TX channel closed, exiting notification loop");
-                                    break;
-                                }
-                            }
-                        }
-                        info!("Response TX notification loop exited");
-                    })
-                })),
+                method: Self::notify_method(
+                    "Response TX",
+                    resp_notify_rx,
+                    self.notify_loops.clone(),
+                ),
+                ..Default::default()
+            }),
+            control_handle: resp_tx_control_handle,
             ..Default::default()
-        }),
-        control_handle: resp_tx_control_handle,
-        ..Default::default()
-    };
+        };
 
         // Build Status characteristic (read + notify)
         let (_status_control, status_control_handle) = characteristic_control();
@@ -300,34 +763,17 @@ impl GattServer {
                 }),
                 notify: Some(CharacteristicNotify {
                     notify: true,
-                    method: CharacteristicNotifyMethod::Fun(Box::new(move |mut notifier| {
-                        let status_notify_rx = status_notify_rx.clone();
-                        
-                        Box::pin(async move {
-                            loop {
-                                let data = {
-                                    let mut rx = status_notify_rx.lock().await;
-                                    rx.recv().await
-                                };
-                                
-                                match data {
-                                    Some(data) => {
-                                        if let Err(e) = notifier.notify(data).await {
-                                            error!("Failed to send status notification: {}", e);
-                                            break;
-                                        }
-                                    }
-                                    None => break,
-                                }
-                            }
-                        })
-                    })),
-            ..Default::default()
-        }),
-        control_handle: status_control_handle,
-        ..Default::default()
-    }
-};
+                    method: Self::notify_method(
+                        "Status",
+                        status_notify_rx,
+                        self.notify_loops.clone(),
+                    ),
+                    ..Default::default()
+                }),
+                control_handle: status_control_handle,
+                ..Default::default()
+            }
+        };
 
         // Build MTU Info characteristic (read only)
         let mtu_char = {
@@ -371,13 +817,35 @@ impl GattServer {
         };
 
         // Register with BlueZ
-        self._app_handle = Some(self.adapter.serve_gatt_application(app).await?);
+        self.registrations.app = Some(self.adapter.serve_gatt_application(app).await?);
 
         info!("GATT service registered");
 
         Ok(())
     }
 
+    /// Notify method forwarding the data queued on `rx` to each subscribed
+    /// phone, see [`forward_notifications`].
+    fn notify_method(
+        name: &'static str,
+        rx: Arc<Mutex<mpsc::Receiver<Vec<u8>>>>,
+        loops: NotifyLoops,
+    ) -> CharacteristicNotifyMethod {
+        CharacteristicNotifyMethod::Fun(Box::new(move |mut notifier| {
+            let rx = rx.clone();
+            let guard = loops.enter();
+
+            Box::pin(async move {
+                debug!("{} notification loop started", name);
+                match forward_notifications(&rx, &mut notifier).await {
+                    Ok(()) => info!("{} channel closed, notification loop exited", name),
+                    Err(e) => error!("Failed to send {} notification: {}", name, e),
+                }
+                drop(guard);
+            })
+        }))
+    }
+
     /// Handle writes to Command RX characteristic.
     async fn handle_command_write(
         data: Vec<u8>,
@@ -388,17 +856,16 @@ impl GattServer {
         response_tx: Arc<Mutex<Option<mpsc::Sender<Vec<u8>>>>>,
     ) -> Result<(), bluer::gatt::local::ReqError> {
         let mut state_guard = state.write().await;
+
+        // A write from a different central starts a new session
+        state_guard.begin_session(req.device_address);
         
         // Update MTU if this write indicates a larger negotiated MTU
         // The MTU in the write request is the effective ATT MTU negotiated with the client
-        let write_mtu = req.mtu as usize;
-        if write_mtu > state_guard.negotiated_mtu {
-            info!("MTU updated: {} -> {} bytes", state_guard.negotiated_mtu, write_mtu);
-            state_guard.negotiated_mtu = write_mtu;
-        }
+        state_guard.update_mtu(req.mtu as usize);
 
         // Process packet through reassembler
-        if let Some(complete_message) = state_guard.reassembler.process_packet(&data) {
+        if let Some(complete_message) = state_guard.receive_packet(&data, &event_tx).await {
             // Parse JSON message
             let json = match String::from_utf8(complete_message) {
                 Ok(s) => s,
@@ -408,7 +875,7 @@ impl GattServer {
                 }
             };
 
-            debug!("Received complete message: {}", json.trim());
+            debug!("Received complete message: {}", redacted(json.trim()));
 
             let mut message = match Message::from_json(&json) {
                 Ok(m) => m,
@@ -424,7 +891,7 @@ impl GattServer {
                 // Only verify messages after authentication for types that require it
                 let should_verify = matches!(
                     message.message_type,
-                    MessageType::Text | MessageType::Word | MessageType::Command
+                    MessageType::Text | MessageType::Word | MessageType::Command | MessageType::Rekey
                 );
                 
                 if should_verify {
@@ -441,13 +908,18 @@ impl GattServer {
                     info!("📱 PAIR_REQ message received!");
                     
                     // Handle pairing request
-                    let payload = match PairRequestPayload::from_json(&message.payload) {
+                    let mut payload = match PairRequestPayload::from_json(&message.payload) {
                         Ok(p) => p,
                         Err(e) => {
                             error!("Failed to parse PAIR_REQ: {}", e);
                             return Ok(());
                         }
                     };
+                    // The name ends up in logs, dialogs and the tray
+                    payload.device_name = payload
+                        .device_name
+                        .as_deref()
+                        .and_then(pairing::sanitize_device_name);
 
                     info!("🔐 Pairing request from device: {} ({})", 
                           payload.device_name.as_deref().unwrap_or("Unknown"),
@@ -460,10 +932,14 @@ impl GattServer {
                         return Ok(());
                     }
 
-                    // Generate desktop ECDH keypair
-                    info!("🔐 Generating desktop ECDH keypair...");
-                    let desktop_keypair = EcdhKeypair::generate();
-                    info!("✅ Desktop ECDH keypair generated");
+                    // Desktop ECDH keypair: the scanned QR code's, or a fresh one
+                    let (desktop_keypair, scanned) =
+                        state_guard.pairing_keypair(payload.scanned_key.as_deref());
+                    if scanned {
+                        info!("✅ Phone scanned the pairing QR code");
+                    } else {
+                        info!("✅ Desktop ECDH keypair generated");
+                    }
 
                     // Store pending pairing data
                     state_guard.device_id = Some(payload.device_id.clone());
@@ -471,7 +947,7 @@ impl GattServer {
                     state_guard.pending_pairing = Some(PendingPairing {
                         android_device_id: payload.device_id.clone(),
                         android_device_name: payload.device_name.clone(),
-                        android_public_key: payload.public_key,
+                        android_public_key: payload.public_key.clone(),
                         desktop_keypair,
                     });
 
@@ -481,6 +957,8 @@ impl GattServer {
                         .send(ConnectionEvent::PairRequested {
                             device_id: payload.device_id,
                             device_name: payload.device_name,
+                            public_key: payload.public_key,
+                            scanned,
                         })
                         .await;
                     info!("✅ PairRequested event sent");
@@ -496,7 +974,7 @@ impl GattServer {
                         return Ok(());
                     }
 
-                    debug!("Text received: {}", message.payload);
+                    debug!("Text received: {}", redacted(&message.payload));
                     let _ = event_tx
                         .send(ConnectionEvent::TextReceived(message.payload.clone()))
                         .await;
@@ -520,12 +998,13 @@ impl GattServer {
                     match WordPayload::from_json(&message.payload) {
                         Ok(word_payload) => {
                             debug!("Word received: '{}' seq={:?} session={}", 
-                                   word_payload.word, word_payload.seq, word_payload.session);
+                                   redacted(&word_payload.word), word_payload.seq, word_payload.session);
                             let _ = event_tx
                                 .send(ConnectionEvent::WordReceived {
                                     word: word_payload.word,
                                     seq: word_payload.seq,
                                     session: word_payload.session,
+                                    eou: word_payload.eou,
                                 })
                                 .await;
                         }
@@ -549,7 +1028,7 @@ impl GattServer {
                         return Ok(());
                     }
 
-                    debug!("Command received: {}", message.payload);
+                    debug!("Command received: {}", redacted(&message.payload));
                     let _ = event_tx
                         .send(ConnectionEvent::CommandReceived(message.payload.clone()))
                         .await;
@@ -564,6 +1043,18 @@ impl GattServer {
                     .await;
                 }
                 MessageType::Heartbeat => {
+                    if state_guard.state == ConnectionState::Authenticated {
+                        match HeartbeatPayload::from_json(&message.payload) {
+                            Ok(HeartbeatPayload {
+                                battery: Some(level),
+                            }) => {
+                                let _ = event_tx.send(ConnectionEvent::Battery(level)).await;
+                            }
+                            Ok(_) => {}
+                            Err(e) => debug!("Ignoring HEARTBEAT payload: {}", e),
+                        }
+                    }
+
                     // Respond with ACK
                     let ack = Message::ack(message.timestamp);
                     Self::send_response_internal(
@@ -573,10 +1064,64 @@ impl GattServer {
                     )
                     .await;
                 }
+                MessageType::Ack => {
+                    if state_guard.pending_acks.acknowledge(&message).is_some() {
+                        debug!("Phone acknowledged message {}", message.payload);
+                    }
+                }
+                MessageType::Rekey => {
+                    if state_guard.state != ConnectionState::Authenticated {
+                        warn!("Received REKEY before authentication");
+                        return Ok(());
+                    }
+                    let Some(rekey) = state_guard.rekey.as_mut() else {
+                        return Ok(());
+                    };
+
+                    if rekey.is_pending() {
+                        // Answer to our request: switch now
+                        match rekey.finish(&message, Instant::now()) {
+                            Ok(crypto) => {
+                                state_guard.crypto = Some(Arc::new(crypto));
+                                info!("🔑 Session key rotated");
+                            }
+                            Err(e) => error!("Re-key failed: {}", e),
+                        }
+                    } else {
+                        // Request from the phone: answer under the old key, then switch
+                        match rekey.respond(&message, Instant::now()) {
+                            Ok((answer, crypto)) => {
+                                Self::send_response_internal(
+                                    answer,
+                                    &state_guard,
+                                    response_tx.clone(),
+                                )
+                                .await;
+                                state_guard.crypto = Some(Arc::new(crypto));
+                                info!("🔑 Session key rotated at the phone's request");
+                            }
+                            Err(e) => error!("Re-key failed: {}", e),
+                        }
+                    }
+                }
                 _ => {
                     debug!("Ignoring message type: {:?}", message.message_type);
                 }
             }
+
+            // Rotate the session key when it is due
+            if state_guard.state == ConnectionState::Authenticated {
+                let counted = matches!(
+                    message.message_type,
+                    MessageType::Text | MessageType::Word | MessageType::Command
+                );
+                if counted {
+                    state_guard.last_activity = Some(Instant::now());
+                }
+                if let Some(request) = state_guard.rekey_if_due(counted, Instant::now()) {
+                    Self::send_response_internal(request, &state_guard, response_tx.clone()).await;
+                }
+            }
         }
 
         Ok(())
@@ -620,37 +1165,65 @@ impl GattServer {
         }
     }
 
-    /// Complete pairing after user approval (ECDH key exchange).
-    pub async fn complete_pairing(&self) -> Result<()> {
-        let mut state = self.state.write().await;
-
-        let pending = state.pending_pairing.take()
-            .ok_or_else(|| anyhow!("No pending pairing request"))?;
-
-        // Get desktop public key before consuming keypair
-        let desktop_public_key = pending.desktop_keypair.public_key_base64();
+    /// Set when to rotate the session key of a connection.
+    ///
+    /// Not configurable yet, as the Android app doesn't answer `REKEY`.
+    #[allow(dead_code)]
+    pub async fn set_rekey_policy(&self, policy: RekeyPolicy) {
+        self.state.write().await.rekey_policy = policy;
+    }
 
-        // Compute ECDH shared secret
-        let shared_secret = pending.desktop_keypair
-            .compute_shared_secret_base64(&pending.android_public_key)?;
+    /// Close sessions without dictation for `timeout`. `None` keeps them open.
+    pub async fn set_idle_timeout(&self, timeout: Option<Duration>) {
+        self.state.write().await.idle_timeout = timeout;
+    }
 
-        // Derive crypto context from ECDH shared secret
-        let crypto = CryptoContext::from_ecdh(
-            &shared_secret,
-            &pending.android_device_id,
-            &self.linux_device_id,
-        );
+    /// Tell the phone whether text or a command is being injected, so it
+    /// can show progress. Only reported while paired.
+    pub async fn set_busy(&self, busy: bool) {
+        let Some(status) = self.state.write().await.set_busy(busy) else {
+            return;
+        };
+        debug!("Status: {:?}", status);
+        if let Some(ref tx) = *self.status_tx.lock().await {
+            let _ = tx.send(status.as_bytes()).await;
+        }
+    }
 
-        // Create PAIR_ACK with desktop's public key
-        let payload = PairAckPayload::success_with_key(&self.linux_device_id, desktop_public_key);
-        let response = Message::new(MessageType::PairAck, payload.to_json()?);
+    /// Start QR code pairing.
+    ///
+    /// Generates a one-time keypair for the phone to scan, replacing any
+    /// previous QR code.
+    pub async fn start_qr_pairing(&self) -> QrPayload {
+        let keypair = EcdhKeypair::generate();
+        let payload = QrPayload {
+            device_id: self.linux_device_id.clone(),
+            public_key: keypair.public_key_bytes(),
+        };
+        self.state.write().await.qr_keypair = Some(keypair);
+        payload
+    }
 
-        // Update state
-        state.crypto = Some(Arc::new(crypto));
-        state.state = ConnectionState::Authenticated;
-        state.status_code = StatusCode::Paired;
+    /// Invalidate the QR code of `payload`, unless it was used or replaced.
+    pub async fn cancel_qr_pairing(&self, payload: &QrPayload) {
+        let mut state = self.state.write().await;
+        if state
+            .qr_keypair
+            .take_if(|k| k.public_key_bytes() == payload.public_key)
+            .is_some()
+        {
+            info!("Pairing QR code expired");
+        }
+    }
 
-        info!("Pairing completed with device: {}", pending.android_device_id);
+    /// Complete pairing after user approval (ECDH key exchange).
+    ///
+    /// `device_id` and `public_key` are those of the approved request. Fails
+    /// if another request replaced it in the meantime.
+    pub async fn complete_pairing(&self, device_id: &str, public_key: &str) -> Result<()> {
+        let mut state = self.state.write().await;
+        let (response, device_name) =
+            state.complete_pairing(&self.linux_device_id, device_id, public_key)?;
 
         // Send PAIR_ACK
         let json = response.to_json()?;
@@ -672,7 +1245,8 @@ impl GattServer {
         // Emit connected event
         let _ = self.event_tx
             .send(ConnectionEvent::Connected {
-                device_name: pending.android_device_name.unwrap_or(pending.android_device_id),
+                device_id: device_id.to_string(),
+                device_name,
             })
             .await;
 
@@ -680,12 +1254,18 @@ impl GattServer {
     }
 
     /// Reject pairing request.
-    pub async fn reject_pairing(&self, reason: &str) -> Result<()> {
-        let state = self.state.read().await;
-        
-        let device_id = state.device_id.as_deref()
-            .unwrap_or("unknown");
-        
+    ///
+    /// Like [`complete_pairing`](Self::complete_pairing), only rejects the
+    /// request from `device_id` with `public_key`.
+    pub async fn reject_pairing(
+        &self,
+        device_id: &str,
+        public_key: &str,
+        reason: &str,
+    ) -> Result<()> {
+        let mut state = self.state.write().await;
+        state.take_pending_pairing(device_id, public_key)?;
+
         // Create PAIR_ACK with error status
         let payload = PairAckPayload::error(&self.linux_device_id, reason);
         let response = Message::new(MessageType::PairAck, payload.to_json()?);
@@ -705,8 +1285,13 @@ impl GattServer {
         Ok(())
     }
 
-    /// Start BLE advertising.
-    async fn start_advertising(&mut self) -> Result<()> {
+    /// Start BLE advertising, so phones can find the desktop.
+    pub async fn start_advertising(&mut self) -> Result<()> {
+        if self.registrations.adv.is_some() {
+            debug!("BLE advertising already started");
+            return Ok(());
+        }
+
         let adv = Advertisement {
             service_uuids: vec![SERVICE_UUID].into_iter().collect(),
             discoverable: Some(true),
@@ -715,12 +1300,79 @@ impl GattServer {
         };
 
         let handle = self.adapter.advertise(adv).await?;
-        self._adv_handle = Some(handle);
+        self.registrations.adv = Some(handle);
 
         info!("BLE advertising started");
         Ok(())
     }
 
+    /// Stop BLE advertising.
+    ///
+    /// A connected phone stays connected, but no other phone can find or
+    /// connect to the desktop until advertising starts again.
+    pub fn stop_advertising(&mut self) {
+        if self.registrations.adv.take().is_some() {
+            info!("BLE advertising stopped");
+        }
+    }
+
+    /// Whether the desktop is advertising.
+    pub fn is_advertising(&self) -> bool {
+        self.registrations.adv.is_some()
+    }
+
+    /// Stop serving before the app exits.
+    ///
+    /// Tells a connected phone the desktop is no longer paired and waits for
+    /// the notification loops to send it, then stops advertising and
+    /// unregisters the GATT application.
+    pub async fn shutdown(&mut self) {
+        if let Some(sweeper) = self.ack_sweeper.take() {
+            sweeper.abort();
+        }
+        {
+            let mut state = self.state.write().await;
+            state.status_code = StatusCode::Idle;
+            state.crypto = None;
+        }
+
+        let status_tx_guard = self.status_tx.lock().await;
+        if let Some(ref tx) = *status_tx_guard {
+            let _ = tx.send(StatusCode::Idle.as_bytes()).await;
+        }
+        drop(status_tx_guard);
+
+        // Closing the channels lets the notification loops send what is
+        // queued and exit, before unregistering fails their notifications
+        self.response_tx.lock().await.take();
+        self.status_tx.lock().await.take();
+        if tokio::time::timeout(NOTIFY_LOOP_EXIT_TIMEOUT, self.notify_loops.wait_exited())
+            .await
+            .is_err()
+        {
+            warn!("Notification loops still running, unregistering anyway");
+        }
+
+        self.registrations.clear();
+    }
+
+    /// Register the GATT application and advertisement again.
+    ///
+    /// Recovers from an adapter reset or a confused BlueZ without restarting
+    /// the app. The current session ends, so the phone has to reconnect.
+    pub async fn restart(&mut self) -> Result<()> {
+        info!("Restarting GATT server...");
+        self.shutdown().await;
+        {
+            let mut state = self.state.write().await;
+            state.peer = None;
+            state.reset_session();
+        }
+        let _ = self.event_tx.send(ConnectionEvent::Disconnected).await;
+
+        self.start().await
+    }
+
     /// Send a notification (for testing or manual responses).
     #[allow(dead_code)]
     pub async fn send_notification(&self, data: Vec<u8>) -> Result<()> {
@@ -731,3 +1383,324 @@ impl GattServer {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PHONE: Address = Address::new([0x11, 0x22, 0x33, 0x44, 0x55, 0x66]);
+    const OTHER_PHONE: Address = Address::new([0x66, 0x55, 0x44, 0x33, 0x22, 0x11]);
+
+    /// Leave state behind as an authenticated session with a partial message would.
+    fn dirty_session(state: &mut ServerState) {
+        let crypto = CryptoContext::from_ecdh(&[7u8; 32], "android-1", "linux-1");
+        state.crypto = Some(Arc::new(crypto));
+        state.rekey = Some(SessionRekey::new("android-1", "linux-1", Instant::now()));
+        state.device_id = Some("android-1".to_string());
+        state.state = ConnectionState::Authenticated;
+        state.status_code = StatusCode::Paired;
+        state.negotiated_mtu = 185;
+        let pair_ack = Message::new(MessageType::PairAck, "{}");
+        state
+            .pending_acks
+            .track(pair_ack.timestamp, pair_ack, 0, Instant::now());
+
+        let packets = chunk_message(&[b'x'; 100], config::DEFAULT_MTU);
+        state.reassembler.process_packet(&packets[0]);
+        assert!(state.reassembler.is_in_progress());
+    }
+
+    #[test]
+    fn test_power_on_delay_doubles_up_to_max() {
+        assert_eq!(power_on_delay(0), Duration::from_millis(500));
+        assert_eq!(power_on_delay(1), Duration::from_secs(1));
+        assert_eq!(power_on_delay(3), Duration::from_secs(4));
+        assert_eq!(power_on_delay(4), POWER_ON_MAX_DELAY);
+        assert_eq!(power_on_delay(u32::MAX), POWER_ON_MAX_DELAY);
+    }
+
+    #[test]
+    fn test_same_central_keeps_session() {
+        let mut state = ServerState::new();
+        assert!(state.begin_session(PHONE));
+        dirty_session(&mut state);
+
+        assert!(!state.begin_session(PHONE));
+        assert!(state.crypto.is_some());
+        assert!(state.reassembler.is_in_progress());
+    }
+
+    #[test]
+    fn test_new_central_starts_clean() {
+        let mut state = ServerState::new();
+        state.begin_session(PHONE);
+        dirty_session(&mut state);
+
+        assert!(state.begin_session(OTHER_PHONE));
+        assert!(state.crypto.is_none());
+        assert!(state.device_id.is_none());
+        assert!(state.pending_pairing.is_none());
+        assert!(state.pending_acks.is_empty());
+        assert!(state.rekey.is_none());
+        assert!(!state.reassembler.is_in_progress());
+        assert_eq!(state.state, ConnectionState::AwaitingPair);
+        assert_eq!(state.status_code, StatusCode::Idle);
+        assert_eq!(state.negotiated_mtu, config::DEFAULT_MTU);
+    }
+
+    #[test]
+    fn test_mtu_only_grows() {
+        let mut state = ServerState::new();
+        assert_eq!(state.negotiated_mtu, config::DEFAULT_MTU);
+
+        assert!(state.update_mtu(185));
+        assert_eq!(state.negotiated_mtu, 185);
+
+        assert!(!state.update_mtu(config::DEFAULT_MTU));
+        assert!(!state.update_mtu(185));
+        assert_eq!(state.negotiated_mtu, 185);
+    }
+
+    /// Sink recording what it was sent, or failing like an unsubscribed phone.
+    #[derive(Default)]
+    struct RecordingSink {
+        sent: Vec<Vec<u8>>,
+        fail: bool,
+    }
+
+    impl NotificationSink for RecordingSink {
+        async fn notify(&mut self, data: Vec<u8>) -> Result<()> {
+            if self.fail {
+                return Err(anyhow!("Notification session stopped"));
+            }
+            self.sent.push(data);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_closed_channel_ends_notification_loop() {
+        let (tx, rx) = mpsc::channel(4);
+        let rx = Mutex::new(rx);
+        tx.send(StatusCode::Idle.as_bytes()).await.unwrap();
+        drop(tx);
+
+        // What is queued still goes out, then the loop ends without an error
+        let mut sink = RecordingSink::default();
+        assert!(forward_notifications(&rx, &mut sink).await.is_ok());
+        assert_eq!(sink.sent, vec![StatusCode::Idle.as_bytes()]);
+
+        let (tx, rx) = mpsc::channel(4);
+        let rx = Mutex::new(rx);
+        tx.send(StatusCode::Idle.as_bytes()).await.unwrap();
+        let mut sink = RecordingSink {
+            fail: true,
+            ..Default::default()
+        };
+        assert!(forward_notifications(&rx, &mut sink).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_notification_loops() {
+        let loops = NotifyLoops::new();
+        loops.wait_exited().await;
+
+        let guard = loops.enter();
+        let wait = tokio::time::timeout(Duration::from_millis(50), loops.wait_exited());
+        assert!(wait.await.is_err());
+
+        drop(guard);
+        let wait = tokio::time::timeout(Duration::from_millis(50), loops.wait_exited());
+        assert!(wait.await.is_ok());
+    }
+
+    #[test]
+    fn test_busy_while_paired() {
+        let mut state = ServerState::new();
+        // Nothing to report before pairing
+        assert_eq!(state.set_busy(true), None);
+        assert_eq!(state.status_code, StatusCode::Idle);
+
+        state.state = ConnectionState::Authenticated;
+        state.status_code = StatusCode::Paired;
+        assert_eq!(state.set_busy(true), Some(StatusCode::Busy));
+        assert_eq!(state.set_busy(true), None);
+        assert_eq!(state.set_busy(false), Some(StatusCode::Paired));
+        assert_eq!(state.status_code, StatusCode::Paired);
+    }
+
+    fn pending(device_id: &str, public_key: &str) -> PendingPairing {
+        PendingPairing {
+            android_device_id: device_id.to_string(),
+            android_device_name: Some("Pixel 8".to_string()),
+            android_public_key: public_key.to_string(),
+            desktop_keypair: EcdhKeypair::generate(),
+        }
+    }
+
+    #[test]
+    fn test_complete_pairing_authenticates() {
+        let mut state = ServerState::new();
+        let phone_key = EcdhKeypair::generate().public_key_base64();
+        assert_eq!(state.state, ConnectionState::AwaitingPair);
+        assert!(state
+            .complete_pairing("linux-1", "android-1", &phone_key)
+            .is_err());
+
+        state.pending_pairing = Some(pending("android-1", &phone_key));
+
+        let (response, device_name) = state
+            .complete_pairing("linux-1", "android-1", &phone_key)
+            .unwrap();
+        assert_eq!(response.message_type, MessageType::PairAck);
+        assert_eq!(device_name, "Pixel 8");
+        assert_eq!(state.state, ConnectionState::Authenticated);
+        assert_eq!(state.status_code, StatusCode::Paired);
+        assert!(state.crypto.is_some());
+        assert!(state.pending_pairing.is_none());
+    }
+
+    #[test]
+    fn test_approval_does_not_apply_to_a_newer_request() {
+        let mut state = ServerState::new();
+        let first_key = EcdhKeypair::generate().public_key_base64();
+        let second_key = EcdhKeypair::generate().public_key_base64();
+
+        // A second PAIR_REQ arrives while the first one is being approved
+        state.pending_pairing = Some(pending("android-1", &first_key));
+        state.pending_pairing = Some(pending("android-2", &second_key));
+
+        assert!(state
+            .complete_pairing("linux-1", "android-1", &first_key)
+            .is_err());
+        assert!(state.take_pending_pairing("android-1", &first_key).is_err());
+        // The same phone with another key is another request too
+        assert!(state
+            .complete_pairing("linux-1", "android-2", &first_key)
+            .is_err());
+        assert_eq!(state.state, ConnectionState::AwaitingPair);
+        assert!(state.crypto.is_none());
+
+        // The newer request is still waiting for its own answer
+        state
+            .complete_pairing("linux-1", "android-2", &second_key)
+            .unwrap();
+        assert_eq!(state.state, ConnectionState::Authenticated);
+    }
+
+    #[test]
+    fn test_scanned_key_uses_qr_keypair_once() {
+        let mut state = ServerState::new();
+        let qr_keypair = EcdhKeypair::generate();
+        let qr_key = qr_keypair.public_key_base64();
+        state.qr_keypair = Some(qr_keypair);
+
+        // Not scanned, or a different key: fresh keypair
+        let (keypair, scanned) = state.pairing_keypair(None);
+        assert!(!scanned);
+        assert_ne!(keypair.public_key_base64(), qr_key);
+        assert!(!state.pairing_keypair(Some("other-key")).1);
+
+        let (keypair, scanned) = state.pairing_keypair(Some(qr_key.as_str()));
+        assert!(scanned);
+        assert_eq!(keypair.public_key_base64(), qr_key);
+        assert!(state.qr_keypair.is_none());
+
+        // Used up
+        assert!(!state.pairing_keypair(Some(qr_key.as_str())).1);
+    }
+
+    #[test]
+    fn test_registrations_cleared_and_rebuilt() {
+        let app = Arc::new(());
+        let adv = Arc::new(());
+        let mut registrations = Registrations::new();
+        assert!(!registrations.is_serving());
+
+        registrations.app = Some(app.clone());
+        registrations.adv = Some(adv.clone());
+        assert!(registrations.is_serving());
+
+        // Clearing drops the handles, which unregisters them
+        registrations.clear();
+        assert!(!registrations.is_serving());
+        assert_eq!(Arc::strong_count(&app), 1);
+        assert_eq!(Arc::strong_count(&adv), 1);
+
+        registrations.app = Some(app.clone());
+        registrations.adv = Some(adv.clone());
+        assert!(registrations.is_serving());
+        assert_eq!(Arc::strong_count(&app), 2);
+    }
+
+    #[test]
+    fn test_idle_session() {
+        let now = Instant::now();
+        let mut state = ServerState::new();
+        dirty_session(&mut state);
+        state.last_activity = Some(now);
+
+        // No timeout configured
+        assert!(!state.is_idle(now + Duration::from_secs(86400)));
+
+        state.idle_timeout = Some(Duration::from_secs(600));
+        assert!(!state.is_idle(now + Duration::from_secs(599)));
+        assert!(state.is_idle(now + Duration::from_secs(600)));
+
+        // Not before pairing, and not again once the session is reset
+        state.reset_session();
+        assert!(state.crypto.is_none());
+        assert!(!state.is_idle(now + Duration::from_secs(600)));
+        state.state = ConnectionState::Authenticated;
+        assert!(!state.is_idle(now + Duration::from_secs(600)));
+    }
+
+    #[test]
+    fn test_rekey_starts_when_due() {
+        let now = Instant::now();
+        let mut state = ServerState::new();
+        state.rekey_policy = RekeyPolicy {
+            interval: None,
+            max_messages: Some(2),
+        };
+        // Not paired yet
+        assert!(state.rekey_if_due(true, now).is_none());
+
+        state.rekey = Some(SessionRekey::new("android-1", "linux-1", now));
+        assert!(state.rekey_if_due(true, now).is_none());
+        // Heartbeats don't count
+        assert!(state.rekey_if_due(false, now).is_none());
+        let request = state.rekey_if_due(true, now).unwrap();
+        assert_eq!(request.message_type, MessageType::Rekey);
+
+        // No second request while waiting for the answer
+        assert!(state.rekey_if_due(true, now).is_none());
+    }
+
+    #[test]
+    fn test_qr_keypair_survives_session_reset() {
+        let mut state = ServerState::new();
+        state.qr_keypair = Some(EcdhKeypair::generate());
+        state.begin_session(PHONE);
+        state.begin_session(OTHER_PHONE);
+        assert!(state.qr_keypair.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_sequence_error_emits_error_event() {
+        let (event_tx, mut event_rx) = mpsc::channel(4);
+        let mut state = ServerState::new();
+        let packets = chunk_message(&[b'x'; 100], config::DEFAULT_MTU);
+
+        assert!(state.receive_packet(&packets[0], &event_tx).await.is_none());
+        assert!(event_rx.try_recv().is_err());
+
+        // packets[1] got lost
+        assert!(state.receive_packet(&packets[2], &event_tx).await.is_none());
+        match event_rx.try_recv() {
+            Ok(ConnectionEvent::Error(message)) => assert!(message.contains("Sequence error")),
+            other => panic!("expected an error event, got {:?}", other),
+        }
+        assert!(!state.reassembler.is_in_progress());
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recovery from the Bluetooth adapter being unplugged.
+//!
+//! BlueZ reports adapters coming and going. When the adapter the server
+//! runs on disappears, the app reports an error and waits; when an adapter
+//! shows up again, the GATT server is rebuilt on it.
+
+use anyhow::Result;
+use bluer::SessionEvent;
+use futures::StreamExt;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+/// Adapter change reported by BlueZ, by adapter name (e.g. "hci0").
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdapterEvent {
+    Added(String),
+    Removed(String),
+}
+
+/// What to do after an adapter change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    /// The server's adapter is gone: report it.
+    ReportLost,
+    /// An adapter is back: build a new server on it.
+    Rebuild,
+}
+
+/// Tracks whether the adapter the server runs on is present.
+#[derive(Debug)]
+pub struct AdapterWatch {
+    adapter: String,
+    present: bool,
+}
+
+impl AdapterWatch {
+    /// Watch `adapter`, which the server currently runs on.
+    pub fn new(adapter: impl Into<String>) -> Self {
+        Self {
+            adapter: adapter.into(),
+            present: true,
+        }
+    }
+
+    /// Whether the server has an adapter to run on.
+    pub fn is_present(&self) -> bool {
+        self.present
+    }
+
+    /// Note an adapter change and say what to do about it.
+    ///
+    /// Other adapters coming and going are ignored while the server has
+    /// one; once it lost its adapter, any adapter that shows up is used.
+    pub fn handle(&mut self, event: AdapterEvent) -> Option<Recovery> {
+        match event {
+            AdapterEvent::Removed(name) if self.present && name == self.adapter => {
+                warn!("Bluetooth adapter {} removed", name);
+                self.present = false;
+                Some(Recovery::ReportLost)
+            }
+            AdapterEvent::Added(name) if !self.present => {
+                info!("Bluetooth adapter {} added, restarting the server", name);
+                self.adapter = name;
+                self.present = true;
+                Some(Recovery::Rebuild)
+            }
+            event => {
+                debug!("Ignoring adapter change {:?}", event);
+                None
+            }
+        }
+    }
+
+    /// Note that rebuilding the server failed, so the next adapter that
+    /// shows up is tried again.
+    pub fn rebuild_failed(&mut self) {
+        self.present = false;
+    }
+}
+
+/// Receive adapter changes from BlueZ.
+pub async fn watch_adapters() -> Result<mpsc::Receiver<AdapterEvent>> {
+    let session = bluer::Session::new().await?;
+    let events = session.events().await?;
+    let (tx, rx) = mpsc::channel(8);
+
+    tokio::spawn(async move {
+        // The session must outlive the stream
+        let _session = session;
+        futures::pin_mut!(events);
+        while let Some(event) = events.next().await {
+            let event = match event {
+                SessionEvent::AdapterAdded(name) => AdapterEvent::Added(name),
+                SessionEvent::AdapterRemoved(name) => AdapterEvent::Removed(name),
+            };
+            if tx.send(event).await.is_err() {
+                break;
+            }
+        }
+    });
+    Ok(rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn added(name: &str) -> AdapterEvent {
+        AdapterEvent::Added(name.to_string())
+    }
+
+    fn removed(name: &str) -> AdapterEvent {
+        AdapterEvent::Removed(name.to_string())
+    }
+
+    #[test]
+    fn test_unplug_and_replug() {
+        let mut watch = AdapterWatch::new("hci0");
+        // Already present at startup
+        assert_eq!(watch.handle(added("hci0")), None);
+
+        assert_eq!(watch.handle(removed("hci0")), Some(Recovery::ReportLost));
+        assert!(!watch.is_present());
+        // Reported once
+        assert_eq!(watch.handle(removed("hci0")), None);
+
+        assert_eq!(watch.handle(added("hci0")), Some(Recovery::Rebuild));
+        assert!(watch.is_present());
+        assert_eq!(watch.handle(added("hci0")), None);
+    }
+
+    #[test]
+    fn test_other_adapters() {
+        let mut watch = AdapterWatch::new("hci0");
+        // A second dongle doesn't matter while the server has one
+        assert_eq!(watch.handle(added("hci1")), None);
+        assert_eq!(watch.handle(removed("hci1")), None);
+
+        // Replugged dongles may come back under a new name
+        assert_eq!(watch.handle(removed("hci0")), Some(Recovery::ReportLost));
+        assert_eq!(watch.handle(added("hci1")), Some(Recovery::Rebuild));
+        assert_eq!(watch.handle(removed("hci1")), Some(Recovery::ReportLost));
+    }
+
+    #[test]
+    fn test_failed_rebuild_retries_on_next_adapter() {
+        let mut watch = AdapterWatch::new("hci0");
+        watch.handle(removed("hci0"));
+        assert_eq!(watch.handle(added("hci0")), Some(Recovery::Rebuild));
+
+        watch.rebuild_failed();
+        assert_eq!(watch.handle(added("hci0")), Some(Recovery::Rebuild));
+    }
+}
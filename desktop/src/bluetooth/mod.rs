@@ -19,13 +19,13 @@
 // BLE modules
 mod ble_constants;
 mod gatt_server;
-mod reassembler;
-
-// Protocol (shared)
-mod protocol;
+mod hotplug;
 
 // Export BLE components (only what's used externally)
 pub use gatt_server::{ConnectionEvent, GattServer};
+#[allow(unused_imports)]
+pub use gatt_server::ConnectionState;
+pub use hotplug::{watch_adapters, AdapterEvent, AdapterWatch, Recovery};
 
 // Export protocol (only what's used externally)
-pub use protocol::CommandCode;
+pub use speech2prompt_protocol::CommandCode;
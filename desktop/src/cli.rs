@@ -0,0 +1,132 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Command-line arguments.
+
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::config::ConfigOverrides;
+
+/// Linux desktop companion for the Speech2Prompt voice-to-keyboard bridge.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Bluetooth device name (defaults to the hostname).
+    #[arg(long)]
+    pub device_name: Option<String>,
+
+    /// Input backend.
+    #[arg(long, value_parser = ["auto", "x11", "wayland", "atspi"])]
+    pub backend: Option<String>,
+
+    /// Data directory for storage and settings.
+    #[arg(long)]
+    pub data_dir: Option<PathBuf>,
+
+    /// Log level for the application.
+    #[arg(long, value_parser = ["error", "warn", "info", "debug", "trace"])]
+    pub log_level: Option<String>,
+
+    /// Run without tray and dialogs.
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Export paired devices to a passphrase-encrypted backup, then exit.
+    #[arg(long, value_name = "FILE", conflicts_with = "import_devices")]
+    pub export_devices: Option<PathBuf>,
+
+    /// Restore paired devices from a backup, then exit.
+    #[arg(long, value_name = "FILE")]
+    pub import_devices: Option<PathBuf>,
+}
+
+impl Cli {
+    /// Config settings given on the command line.
+    pub fn overrides(&self) -> ConfigOverrides {
+        ConfigOverrides {
+            device_name: self.device_name.clone(),
+            prefer_backend: self.backend.clone(),
+            data_dir: self.data_dir.clone(),
+            headless: self.headless,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_arguments() {
+        let cli = Cli::try_parse_from(["speech2prompt-desktop"]).unwrap();
+        let overrides = cli.overrides();
+
+        assert!(overrides.device_name.is_none());
+        assert!(overrides.prefer_backend.is_none());
+        assert!(overrides.data_dir.is_none());
+        assert!(!overrides.headless);
+        assert!(cli.log_level.is_none());
+        assert!(cli.export_devices.is_none());
+        assert!(cli.import_devices.is_none());
+    }
+
+    #[test]
+    fn test_all_arguments() {
+        let cli = Cli::try_parse_from([
+            "speech2prompt-desktop",
+            "--device-name",
+            "workstation",
+            "--backend",
+            "wayland",
+            "--data-dir",
+            "/tmp/s2p",
+            "--log-level",
+            "debug",
+            "--headless",
+        ])
+        .unwrap();
+        let overrides = cli.overrides();
+
+        assert_eq!(overrides.device_name.as_deref(), Some("workstation"));
+        assert_eq!(overrides.prefer_backend.as_deref(), Some("wayland"));
+        assert_eq!(overrides.data_dir, Some(PathBuf::from("/tmp/s2p")));
+        assert!(overrides.headless);
+        assert_eq!(cli.log_level.as_deref(), Some("debug"));
+    }
+
+    #[test]
+    fn test_device_backup() {
+        let cli =
+            Cli::try_parse_from(["speech2prompt-desktop", "--export-devices", "/tmp/devices"])
+                .unwrap();
+        assert_eq!(cli.export_devices, Some(PathBuf::from("/tmp/devices")));
+
+        // One at a time
+        assert!(Cli::try_parse_from([
+            "speech2prompt-desktop",
+            "--export-devices",
+            "/tmp/a",
+            "--import-devices",
+            "/tmp/b",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_invalid_values_are_rejected() {
+        assert!(Cli::try_parse_from(["speech2prompt-desktop", "--backend", "foo"]).is_err());
+        assert!(Cli::try_parse_from(["speech2prompt-desktop", "--log-level", "loud"]).is_err());
+    }
+}
@@ -0,0 +1,75 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Current date and time as text.
+//!
+//! "insert date" and "insert time" type the local date or time in a
+//! `chrono` strftime format from the config, e.g. `%d.%m.%Y`.
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, TimeZone};
+use std::fmt::Display;
+
+/// Default format of "insert date", e.g. 2026-10-16.
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Default format of "insert time", e.g. 14:05.
+pub const DEFAULT_TIME_FORMAT: &str = "%H:%M";
+
+/// Check that `format` is a strftime format `chrono` can render.
+pub fn is_valid_format(format: &str) -> bool {
+    !format.trim().is_empty() && !StrftimeItems::new(format).any(|item| item == Item::Error)
+}
+
+/// Text to type for `time` in `format`, with the usual trailing space.
+///
+/// `format` must be valid, see [`is_valid_format`].
+pub fn datetime_text<Tz>(time: &DateTime<Tz>, format: &str) -> String
+where
+    Tz: TimeZone,
+    Tz::Offset: Display,
+{
+    format!("{} ", time.format(format))
+}
+
+/// Text to type for the current local time in `format`.
+pub fn now_text(format: &str) -> String {
+    datetime_text(&Local::now(), format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_datetime_text() {
+        let time = Utc.with_ymd_and_hms(2026, 10, 16, 14, 5, 9).unwrap();
+        assert_eq!(datetime_text(&time, DEFAULT_DATE_FORMAT), "2026-10-16 ");
+        assert_eq!(datetime_text(&time, DEFAULT_TIME_FORMAT), "14:05 ");
+        assert_eq!(datetime_text(&time, "%d.%m.%Y"), "16.10.2026 ");
+        assert_eq!(datetime_text(&time, "%A, %B %-d"), "Friday, October 16 ");
+        assert_eq!(datetime_text(&time, "%I:%M:%S %p"), "02:05:09 PM ");
+    }
+
+    #[test]
+    fn test_is_valid_format() {
+        assert!(is_valid_format(DEFAULT_DATE_FORMAT));
+        assert!(is_valid_format(DEFAULT_TIME_FORMAT));
+        assert!(is_valid_format("week %V"));
+        assert!(!is_valid_format("%Q"));
+        assert!(!is_valid_format("%"));
+        assert!(!is_valid_format(" "));
+    }
+}
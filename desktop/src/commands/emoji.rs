@@ -0,0 +1,93 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Emoji by name.
+//!
+//! Saying "emoji <name>" types the emoji from [`EMOJI`]. The table is kept
+//! small on purpose: one spoken word per emoji, picked so speech
+//! recognizers transcribe it reliably.
+
+/// Word that starts an emoji phrase.
+pub const EMOJI_TRIGGER: &str = "emoji";
+
+/// Emoji names and their codepoints.
+pub const EMOJI: &[(&str, char)] = &[
+    ("smile", '\u{1F642}'),
+    ("grin", '\u{1F600}'),
+    ("laugh", '\u{1F602}'),
+    ("wink", '\u{1F609}'),
+    ("sad", '\u{1F641}'),
+    ("love", '\u{1F60D}'),
+    ("thinking", '\u{1F914}'),
+    ("eyes", '\u{1F440}'),
+    ("thumbsup", '\u{1F44D}'),
+    ("clap", '\u{1F44F}'),
+    ("wave", '\u{1F44B}'),
+    ("party", '\u{1F389}'),
+    ("fire", '\u{1F525}'),
+    ("rocket", '\u{1F680}'),
+    ("check", '\u{2705}'),
+];
+
+/// Look up an emoji by name, ignoring case and trailing punctuation.
+pub fn lookup(name: &str) -> Option<char> {
+    let name = name
+        .trim()
+        .trim_end_matches(['.', ',', '!', '?', ':', ';'])
+        .to_lowercase();
+    EMOJI
+        .iter()
+        .find(|(emoji_name, _)| *emoji_name == name)
+        .map(|(_, emoji)| *emoji)
+}
+
+/// Text to type for "emoji <name>", with the usual trailing space.
+///
+/// Unknown names type the phrase itself, so nothing said is lost.
+pub fn emoji_text(name: &str) -> String {
+    match lookup(name) {
+        Some(emoji) => format!("{} ", emoji),
+        None => format!("{} {} ", EMOJI_TRIGGER, name.trim()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_codepoints() {
+        assert_eq!(lookup("smile"), Some('\u{1F642}'));
+        assert_eq!(lookup("thumbsup"), Some('\u{1F44D}'));
+        assert_eq!(lookup("Rocket."), Some('\u{1F680}'));
+        assert_eq!(lookup("check"), Some('\u{2705}'));
+        assert_eq!(lookup("unicorn"), None);
+    }
+
+    #[test]
+    fn test_emoji_text() {
+        assert_eq!(emoji_text("smile"), "\u{1F642} ");
+        assert_eq!(emoji_text("Fire!"), "\u{1F525} ");
+        // A miss types the literal phrase
+        assert_eq!(emoji_text("unicorn"), "emoji unicorn ");
+    }
+
+    #[test]
+    fn test_names_are_single_lowercase_words() {
+        for (name, _) in EMOJI {
+            assert_eq!(*name, name.to_lowercase());
+            assert!(!name.contains(' '));
+        }
+    }
+}
@@ -1,5 +1,16 @@
 // Copyright 2024 Speech2Prompt Contributors
-// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
 
 //! Command matcher for voice phrase recognition.
 //!
@@ -9,14 +20,11 @@
 
 use std::sync::Arc;
 use tracing::debug;
+use unicode_normalization::UnicodeNormalization;
 
-use super::VoiceCommand;
+use super::{VoiceCommand, EMOJI_CODE_PREFIX, EMOJI_TRIGGER, LAUNCH_CODE_PREFIX};
 use crate::storage::VoiceCommandStore;
 
-/// Default 2-word command phrases
-pub const DEFAULT_TWO_WORD_PHRASES: &[(&str, &str)] =
-    &[("select all", "SELECT_ALL"), ("new line", "ENTER")];
-
 /// Result of matching input text that may contain commands.
 #[derive(Debug, Clone)]
 pub enum MatchResult {
@@ -42,12 +50,63 @@ pub enum TextSegment {
 /// Supports finding commands anywhere in the input text.
 pub struct CombinedMatcher {
     store: Arc<VoiceCommandStore>,
+    /// Phrases launching applications, normalized, with the phrase as
+    /// configured.
+    app_phrases: Vec<(String, String)>,
 }
 
 impl CombinedMatcher {
     /// Create a new combined matcher.
     pub fn new(store: Arc<VoiceCommandStore>) -> Self {
-        Self { store }
+        Self {
+            store,
+            app_phrases: Vec::new(),
+        }
+    }
+
+    /// Also match `phrases` from `[apps.launch]`, as [`VoiceCommand::Launch`].
+    ///
+    /// Phrases of the voice command store take precedence.
+    pub fn with_app_phrases<'a>(mut self, phrases: impl IntoIterator<Item = &'a String>) -> Self {
+        let app_phrases = phrases
+            .into_iter()
+            .map(|phrase| (self.normalize_phrase(phrase), phrase.clone()))
+            .collect();
+        self.app_phrases = app_phrases;
+        self
+    }
+
+    /// Match a phrase against the application phrases.
+    /// Returns the launch command code if matched.
+    fn match_app_phrase(&self, phrase: &str) -> Option<String> {
+        let normalized = self.normalize_phrase(phrase);
+        self.app_phrases
+            .iter()
+            .find(|(app_phrase, _)| *app_phrase == normalized)
+            .map(|(_, configured)| format!("{}{}", LAUNCH_CODE_PREFIX, configured))
+    }
+
+    /// Resolve a command code to a command.
+    ///
+    /// Built-in codes are parsed directly; user-defined codes are looked up
+    /// in the store.
+    pub fn resolve_command(&self, command_code: &str) -> Option<VoiceCommand> {
+        VoiceCommand::parse(command_code)
+            .or_else(|| {
+                self.store
+                    .get_key_combo(command_code)
+                    .map(VoiceCommand::KeyCombo)
+            })
+            .or_else(|| {
+                self.store
+                    .get_script(command_code)
+                    .map(|_| VoiceCommand::Script(command_code.to_uppercase()))
+            })
+            .or_else(|| {
+                self.store
+                    .get_shell(command_code)
+                    .map(|_| VoiceCommand::Shell(command_code.to_uppercase()))
+            })
     }
 
     /// Try to match as a spoken phrase first, then as a command code.
@@ -59,7 +118,11 @@ impl CombinedMatcher {
                 "Matched spoken phrase '{}' to command '{}'",
                 input, command_code
             );
-            return VoiceCommand::parse(&command_code);
+            return self.resolve_command(&command_code);
+        }
+
+        if let Some(command_code) = self.match_app_phrase(input) {
+            return self.resolve_command(&command_code);
         }
 
         // Fall back to direct command code parsing (for protocol messages)
@@ -110,7 +173,7 @@ impl CombinedMatcher {
 
             // Check if this word matches a command
             if let Some(command_code) = self.store.match_phrase(word) {
-                if let Some(cmd) = VoiceCommand::parse(&command_code) {
+                if let Some(cmd) = self.resolve_command(&command_code) {
                     // Found a command! First, flush any accumulated text (including leading whitespace)
                     if word_start > last_end {
                         text_buffer.push_str(&input[last_end..word_start]);
@@ -152,12 +215,43 @@ impl CombinedMatcher {
         }
     }
 
+    /// Describe what saying `input` would do, e.g. `Types "hi", runs ENTER`.
+    ///
+    /// Used to test phrases without typing anything.
+    pub fn explain(&self, input: &str) -> String {
+        match self.match_with_context(input) {
+            MatchResult::ExactCommand(cmd) => format!("Runs {}", describe_command(&cmd)),
+            MatchResult::NoMatch if input.trim().is_empty() => "Does nothing".to_string(),
+            MatchResult::NoMatch => format!("Types \"{}\" (no command)", input.trim()),
+            MatchResult::MidTextCommand(segments) => {
+                let steps: Vec<String> = segments
+                    .iter()
+                    .filter_map(|segment| match segment {
+                        TextSegment::Text(text) if text.trim().is_empty() => None,
+                        TextSegment::Text(text) => Some(format!("types \"{}\"", text.trim())),
+                        TextSegment::Command(cmd) => {
+                            Some(format!("runs {}", describe_command(cmd)))
+                        }
+                    })
+                    .collect();
+                let description = steps.join(", ");
+                let mut chars = description.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect(),
+                    None => description,
+                }
+            }
+        }
+    }
+
     /// Match a single word against command phrases.
     /// Returns the command code (e.g., "ENTER") if matched.
     pub fn match_single_word(&self, word: &str) -> Option<String> {
-        let normalized = Self::normalize_for_matching(word);
+        let normalized = self.normalize_for_matching(word);
         // Check custom phrases first, then defaults
-        self.store.match_phrase(&normalized)
+        self.store
+            .match_phrase(&normalized)
+            .or_else(|| self.match_app_phrase(&normalized))
     }
 
     /// Match two words as a potential 2-word command.
@@ -165,20 +259,22 @@ impl CombinedMatcher {
     pub fn match_two_words(&self, word1: &str, word2: &str) -> Option<String> {
         let phrase = format!(
             "{} {}",
-            Self::normalize_for_matching(word1),
-            Self::normalize_for_matching(word2)
+            self.normalize_for_matching(word1),
+            self.normalize_for_matching(word2)
         );
 
-        // Check custom 2-word phrases first
+        // Check custom 2-word phrases first, then defaults
         if let Some(cmd) = self.store.match_phrase(&phrase) {
             return Some(cmd);
         }
 
-        // Check default 2-word phrases
-        for (default_phrase, command) in DEFAULT_TWO_WORD_PHRASES {
-            if phrase == *default_phrase {
-                return Some(command.to_string());
-            }
+        if let Some(cmd) = self.match_app_phrase(&phrase) {
+            return Some(cmd);
+        }
+
+        // "emoji <name>", unknown names are typed as said
+        if self.normalize_for_matching(word1) == EMOJI_TRIGGER {
+            return Some(format!("{}{}", EMOJI_CODE_PREFIX, word2.trim()));
         }
 
         None
@@ -187,34 +283,60 @@ impl CombinedMatcher {
     /// Check if a word could be the first word of a 2-word command.
     /// Used for look-ahead buffering.
     pub fn could_start_two_word_command(&self, word: &str) -> bool {
-        let normalized = Self::normalize_for_matching(word);
-
-        // Check default 2-word phrases
-        for (phrase, _) in DEFAULT_TWO_WORD_PHRASES {
-            if phrase.starts_with(&normalized) && phrase.contains(' ') {
-                let first_word = phrase.split_whitespace().next().unwrap_or("");
-                if first_word == normalized {
-                    return true;
-                }
-            }
+        let normalized = self.normalize_for_matching(word);
+        if normalized == EMOJI_TRIGGER {
+            return true;
         }
 
-        // Check custom phrases that have 2 words
-        // (Note: may need to add method to store to get all phrases)
+        let starts_app_phrase = self.app_phrases.iter().any(|(phrase, _)| {
+            let mut words = phrase.split(' ');
+            words.next() == Some(normalized.as_str()) && words.count() == 1
+        });
+        if starts_app_phrase {
+            return true;
+        }
+
+        // Check custom and default phrases that have 2 words
         self.store.could_start_two_word_command(&normalized)
     }
 
-    /// Normalize a word for matching: trim, lowercase, strip punctuation
-    fn normalize_for_matching(word: &str) -> String {
-        word.trim()
+    /// Normalize a word for matching: trim, strip punctuation, NFC, lowercase
+    /// by the store's case folding
+    fn normalize_for_matching(&self, word: &str) -> String {
+        let word: String = word
+            .trim()
             .trim_end_matches(['.', ',', '!', '?', ':', ';'])
-            .to_lowercase()
+            .nfc()
+            .collect();
+        self.store.case_folding().lowercase(&word)
+    }
+
+    /// Normalize each word of a phrase, separated by single spaces.
+    fn normalize_phrase(&self, phrase: &str) -> String {
+        phrase
+            .split_whitespace()
+            .map(|word| self.normalize_for_matching(word))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Name of `cmd` for [`CombinedMatcher::explain`], with its argument if any.
+fn describe_command(cmd: &VoiceCommand) -> String {
+    match cmd {
+        VoiceCommand::Backspace(count) => format!("BACKSPACE x{}", count),
+        VoiceCommand::Emoji(name) => format!("EMOJI {}", name),
+        VoiceCommand::KeyCombo(combo) => combo.to_string(),
+        VoiceCommand::Script(code) | VoiceCommand::Shell(code) => code.clone(),
+        VoiceCommand::Launch(phrase) => format!("LAUNCH {}", phrase),
+        cmd => cmd.as_str().to_string(),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::MediaAction;
     use tempfile::TempDir;
 
     fn create_test_store_with_custom_phrase(
@@ -240,6 +362,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_nfc_and_nfd_forms_match() {
+        let nfc = "\u{161}mach";
+        let nfd = "s\u{30C}mach";
+        assert_ne!(nfc, nfd);
+
+        for stored in [nfc, nfd] {
+            let (_temp, store) = create_test_store_with_custom_phrase(stored, "ENTER");
+            let matcher = CombinedMatcher::new(store);
+
+            for spoken in [nfc, nfd] {
+                assert!(
+                    matches!(
+                        matcher.match_with_context(spoken),
+                        MatchResult::ExactCommand(VoiceCommand::Enter)
+                    ),
+                    "{:?} stored, {:?} spoken",
+                    stored,
+                    spoken
+                );
+                assert_eq!(matcher.match_single_word(spoken), Some("ENTER".to_string()));
+            }
+            // Within text as well
+            assert!(matches!(
+                matcher.match_with_context(&format!("hello {} world", nfd)),
+                MatchResult::MidTextCommand(_)
+            ));
+        }
+    }
+
     #[test]
     fn test_single_word_with_trailing_space_is_still_command() {
         // Commands ALWAYS execute, even with trailing space from Android word-by-word sending
@@ -387,6 +539,161 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_default_media_phrases() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = Arc::new(VoiceCommandStore::new(temp_dir.path()).unwrap());
+        let matcher = CombinedMatcher::new(store);
+
+        assert_eq!(matcher.match_single_word("play"), Some("PLAY".to_string()));
+        assert_eq!(
+            matcher.match_single_word("Pause"),
+            Some("PAUSE".to_string())
+        );
+
+        assert!(matcher.could_start_two_word_command("next"));
+        assert!(matcher.could_start_two_word_command("previous"));
+        assert_eq!(
+            matcher.match_two_words("next", "track"),
+            Some("NEXT_TRACK".to_string())
+        );
+        assert_eq!(
+            matcher.match_two_words("previous", "track"),
+            Some("PREVIOUS_TRACK".to_string())
+        );
+
+        match matcher.match_with_context("next track") {
+            MatchResult::ExactCommand(cmd) => {
+                assert_eq!(cmd, VoiceCommand::Media(MediaAction::NextTrack))
+            }
+            other => panic!("Expected ExactCommand for 'next track', got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_caps_lock_phrase() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = Arc::new(VoiceCommandStore::new(temp_dir.path()).unwrap());
+        let matcher = CombinedMatcher::new(store);
+
+        assert!(matcher.could_start_two_word_command("caps"));
+        assert_eq!(
+            matcher.match_two_words("Caps", "lock."),
+            Some("CAPS_LOCK".to_string())
+        );
+    }
+
+    #[test]
+    fn test_click_phrases() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = Arc::new(VoiceCommandStore::new(temp_dir.path()).unwrap());
+        let matcher = CombinedMatcher::new(store);
+
+        assert!(matches!(
+            matcher.match_with_context("click"),
+            MatchResult::ExactCommand(VoiceCommand::Click)
+        ));
+        assert!(matcher.could_start_two_word_command("right"));
+        assert_eq!(
+            matcher.match_two_words("Right", "click"),
+            Some("RIGHT_CLICK".to_string())
+        );
+        assert_eq!(
+            matcher.match_two_words("double", "click."),
+            Some("DOUBLE_CLICK".to_string())
+        );
+    }
+
+    #[test]
+    fn test_app_phrases() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = Arc::new(VoiceCommandStore::new(temp_dir.path()).unwrap());
+        let phrases = ["Open Terminal".to_string(), "browser".to_string()];
+        let matcher = CombinedMatcher::new(store).with_app_phrases(&phrases);
+        let launch = |phrase: &str| VoiceCommand::Launch(phrase.to_string());
+
+        // Whole text, ignoring case and punctuation
+        match matcher.match_with_context("open terminal.") {
+            MatchResult::ExactCommand(cmd) => assert_eq!(cmd, launch("Open Terminal")),
+            other => panic!("Expected ExactCommand, got {:?}", other),
+        }
+
+        // Word by word
+        assert!(matcher.could_start_two_word_command("open"));
+        assert!(!matcher.could_start_two_word_command("terminal"));
+        let code = matcher.match_two_words("open", "Terminal").unwrap();
+        assert_eq!(
+            matcher.resolve_command(&code),
+            Some(launch("Open Terminal"))
+        );
+        let code = matcher.match_single_word("Browser!").unwrap();
+        assert_eq!(matcher.resolve_command(&code), Some(launch("browser")));
+
+        // Anything else is text
+        assert!(matches!(
+            matcher.match_with_context("open the door"),
+            MatchResult::NoMatch
+        ));
+        assert_eq!(matcher.match_two_words("open", "door"), None);
+    }
+
+    #[test]
+    fn test_emoji_phrase() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = Arc::new(VoiceCommandStore::new(temp_dir.path()).unwrap());
+        let matcher = CombinedMatcher::new(store);
+
+        assert!(matcher.could_start_two_word_command("Emoji"));
+        assert_eq!(
+            matcher.match_two_words("emoji", "smile"),
+            Some("EMOJI:smile".to_string())
+        );
+        // Unknown names still match, they are typed as said
+        assert_eq!(
+            matcher.resolve_command(&matcher.match_two_words("emoji", "unicorn").unwrap()),
+            Some(VoiceCommand::Emoji("unicorn".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_new_paragraph_phrase() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = Arc::new(VoiceCommandStore::new(temp_dir.path()).unwrap());
+        let matcher = CombinedMatcher::new(store);
+
+        // "new" is held back for both "new line" and "new paragraph"
+        assert!(matcher.could_start_two_word_command("new"));
+        assert_eq!(
+            matcher.match_two_words("new", "paragraph"),
+            Some("NEW_PARAGRAPH".to_string())
+        );
+        assert_eq!(
+            matcher.match_two_words("new", "line"),
+            Some("ENTER".to_string())
+        );
+    }
+
+    #[test]
+    fn test_default_selection_phrases() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = Arc::new(VoiceCommandStore::new(temp_dir.path()).unwrap());
+        let matcher = CombinedMatcher::new(store);
+
+        assert_eq!(
+            matcher.match_two_words("select", "word"),
+            Some("SELECT_WORD".to_string())
+        );
+        assert_eq!(
+            matcher.match_two_words("Select", "line."),
+            Some("SELECT_LINE".to_string())
+        );
+
+        match matcher.match_with_context("select line") {
+            MatchResult::ExactCommand(cmd) => assert_eq!(cmd, VoiceCommand::SelectLine),
+            other => panic!("Expected ExactCommand for 'select line', got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_match_single_word_custom_phrase() {
         let (_temp, store) = create_test_store_with_custom_phrase("šmach", "ENTER");
@@ -444,6 +751,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_store_and_matcher_share_default_phrases() {
+        use std::collections::BTreeMap;
+
+        let temp_dir = TempDir::new().unwrap();
+        let overrides = BTreeMap::from([("select_all".to_string(), "Mark Everything".to_string())]);
+        let store = Arc::new(
+            VoiceCommandStore::new(temp_dir.path())
+                .unwrap()
+                .with_default_phrases(&overrides),
+        );
+        let matcher = CombinedMatcher::new(store.clone());
+
+        for (cmd, phrase) in store.default_phrases() {
+            assert_eq!(
+                store.match_phrase(phrase).as_deref(),
+                Some(cmd),
+                "{}",
+                phrase
+            );
+            let words: Vec<&str> = phrase.split(' ').collect();
+            let matched = match words[..] {
+                [word] => matcher.match_single_word(word),
+                [first, second] => {
+                    assert!(matcher.could_start_two_word_command(first), "{}", phrase);
+                    matcher.match_two_words(first, second)
+                }
+                _ => unreachable!(),
+            };
+            assert_eq!(matched.as_deref(), Some(cmd), "{}", phrase);
+        }
+
+        // The built-in phrase is replaced for both
+        assert_eq!(store.get_phrase("SELECT_ALL"), "mark everything");
+        assert_eq!(store.match_phrase("select all"), None);
+        assert_eq!(matcher.match_two_words("select", "all"), None);
+        assert!(matcher.could_start_two_word_command("select")); // "select word"
+    }
+
     #[test]
     fn test_match_two_words_no_match() {
         let temp_dir = TempDir::new().unwrap();
@@ -473,6 +819,32 @@ mod tests {
         assert!(!matcher.could_start_two_word_command("all"));
     }
 
+    #[test]
+    fn test_user_defined_command_resolves_to_key_combo() {
+        use crate::input::{Key, KeyCombo, Modifier};
+
+        let temp_dir = TempDir::new().unwrap();
+        let store = Arc::new(VoiceCommandStore::new(temp_dir.path()).unwrap());
+        store
+            .add_custom_command("save file", "ctrl+s", "save it")
+            .unwrap();
+        let matcher = CombinedMatcher::new(store);
+
+        let expected = VoiceCommand::KeyCombo(KeyCombo {
+            modifiers: vec![Modifier::Ctrl],
+            key: Key::S,
+        });
+        assert_eq!(matcher.resolve_command("SAVE_FILE"), Some(expected.clone()));
+        assert_eq!(
+            matcher.match_two_words("save", "it"),
+            Some("SAVE_FILE".to_string())
+        );
+        match matcher.match_with_context("save it") {
+            MatchResult::ExactCommand(cmd) => assert_eq!(cmd, expected),
+            _ => panic!("Expected ExactCommand for user-defined phrase"),
+        }
+    }
+
     #[test]
     fn test_punctuation_stripping() {
         let (_temp, store) = create_test_store_with_custom_phrase("hello", "ENTER");
@@ -533,4 +905,29 @@ mod tests {
             Some("ENTER".to_string())
         );
     }
+
+    #[test]
+    fn test_explain() {
+        let (_temp, store) = create_test_store_with_custom_phrase("šmach", "ENTER");
+        let matcher = CombinedMatcher::new(store);
+
+        // ExactCommand
+        assert_eq!(matcher.explain("šmach"), "Runs ENTER");
+        assert_eq!(matcher.explain("copy "), "Runs COPY");
+        // MidTextCommand
+        assert_eq!(
+            matcher.explain("hello šmach world"),
+            "Types \"hello\", runs ENTER, types \"world\""
+        );
+        assert_eq!(
+            matcher.explain("šmach again"),
+            "Runs ENTER, types \"again\""
+        );
+        // NoMatch
+        assert_eq!(
+            matcher.explain(" hello world "),
+            "Types \"hello world\" (no command)"
+        );
+        assert_eq!(matcher.explain("  "), "Does nothing");
+    }
 }
@@ -0,0 +1,94 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Media player control.
+//!
+//! Media commands talk to the active MPRIS player through `playerctl`
+//! instead of pressing media keys, which many setups don't forward.
+
+use anyhow::{Context, Result};
+use std::process::Stdio;
+use tracing::{debug, warn};
+
+/// Media player action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaAction {
+    Play,
+    Pause,
+    NextTrack,
+    PreviousTrack,
+}
+
+impl MediaAction {
+    /// Matching `playerctl` subcommand.
+    pub fn playerctl_command(&self) -> &'static str {
+        match self {
+            Self::Play => "play",
+            Self::Pause => "pause",
+            Self::NextTrack => "next",
+            Self::PreviousTrack => "previous",
+        }
+    }
+}
+
+/// Sends media actions to a player.
+pub trait MediaController: Send + Sync {
+    /// Send an action. Returns once it is handed off, not when the player
+    /// has reacted.
+    fn send(&self, action: MediaAction) -> Result<()>;
+}
+
+/// Controls the active MPRIS player with `playerctl`.
+pub struct Playerctl;
+
+impl MediaController for Playerctl {
+    fn send(&self, action: MediaAction) -> Result<()> {
+        let command = action.playerctl_command();
+        debug!("Running playerctl {}", command);
+
+        let mut child = tokio::process::Command::new("playerctl")
+            .arg(command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to run playerctl (is it installed?)")?;
+
+        // Don't hold up typing while the player answers
+        tokio::spawn(async move {
+            match child.wait().await {
+                Ok(status) if status.success() => {}
+                Ok(status) => warn!(
+                    "playerctl {} failed ({}), is a player running?",
+                    command, status
+                ),
+                Err(e) => warn!("playerctl {} failed: {}", command, e),
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_playerctl_commands() {
+        assert_eq!(MediaAction::Play.playerctl_command(), "play");
+        assert_eq!(MediaAction::Pause.playerctl_command(), "pause");
+        assert_eq!(MediaAction::NextTrack.playerctl_command(), "next");
+        assert_eq!(MediaAction::PreviousTrack.playerctl_command(), "previous");
+    }
+}
@@ -16,44 +16,143 @@
 //!
 //! Maps received commands to keyboard actions.
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use tracing::debug;
 
-use crate::input::{InputInjector, Key, Modifier};
+use crate::input::{InputInjector, Key, KeyCombo, Modifier, MouseButton};
+
+mod datetime;
+pub use datetime::{is_valid_format, now_text, DEFAULT_DATE_FORMAT, DEFAULT_TIME_FORMAT};
+
+mod emoji;
+pub use emoji::{emoji_text, EMOJI_TRIGGER};
 
 mod matcher;
 pub use matcher::{CombinedMatcher, MatchResult, TextSegment};
 
+mod media;
+pub use media::{MediaAction, MediaController, Playerctl};
+
 mod word_buffer;
 pub use word_buffer::{ProcessedItem, WordBuffer};
 
+/// Command code prefix for [`VoiceCommand::Emoji`], followed by the name.
+pub const EMOJI_CODE_PREFIX: &str = "EMOJI:";
+
+/// Command code prefix for [`VoiceCommand::Launch`], followed by the phrase.
+pub const LAUNCH_CODE_PREFIX: &str = "LAUNCH:";
+
+/// Mouse wheel notches turned by the scroll commands.
+pub const SCROLL_NOTCHES: i32 = 3;
+
 /// Voice command types.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum VoiceCommand {
     /// Press Enter key.
     Enter,
+    /// Press Enter twice, leaving a blank line.
+    NewParagraph,
     /// Select all (Ctrl+A).
     SelectAll,
+    /// Clear the focused field (Ctrl+A, then Delete).
+    ClearField,
+    /// Select the word left of the cursor (Ctrl+Shift+Left).
+    SelectWord,
+    /// Select the current line (Home, then Shift+End).
+    SelectLine,
     /// Copy (Ctrl+C).
     Copy,
     /// Paste (Ctrl+V).
     Paste,
     /// Cut (Ctrl+X).
     Cut,
+    /// Indent (Tab).
+    Indent,
+    /// Dedent (Shift+Tab).
+    Dedent,
+    /// Toggle Caps Lock.
+    CapsLock,
+    /// Duplicate the current line below it (Home, Shift+End, Ctrl+C, End,
+    /// Enter, Ctrl+V). Overwrites the clipboard.
+    DuplicateLine,
+    /// Left-click where the mouse pointer is.
+    Click,
+    /// Right-click where the mouse pointer is.
+    RightClick,
+    /// Double-click where the mouse pointer is.
+    DoubleClick,
+    /// Scroll up by [`SCROLL_NOTCHES`].
+    ScrollUp,
+    /// Scroll down by [`SCROLL_NOTCHES`].
+    ScrollDown,
+    /// Type the current date.
+    /// Run by the event processor, not by [`execute`].
+    InsertDate,
+    /// Type the current time.
+    /// Run by the event processor, not by [`execute`].
+    InsertTime,
     /// Cancel/discard (no action).
     Cancel,
+    /// Erase the text typed since the last command.
+    /// Run by the event processor as [`Backspace`](Self::Backspace).
+    CancelTyping,
+    /// Press Backspace this many times.
+    Backspace(usize),
+    /// Type the emoji with this name, see [`emoji_text`].
+    /// Run by the event processor, not by [`execute`].
+    Emoji(String),
+    /// Control the media player.
+    /// Run by the event processor, not by [`execute`].
+    Media(MediaAction),
+    /// User-defined key combination.
+    KeyCombo(KeyCombo),
+    /// User-defined command backed by a script, by command code.
+    /// Run by the event processor, not by [`execute`].
+    Script(String),
+    /// User-defined command running an allowlisted shell command, by
+    /// command code. Run by the event processor, not by [`execute`].
+    Shell(String),
+    /// Launch the application configured for this phrase under
+    /// `[apps.launch]`. Run by the event processor, not by [`execute`].
+    Launch(String),
 }
 
 impl VoiceCommand {
     /// Parse from string code.
     pub fn parse(s: &str) -> Option<Self> {
+        if let Some(name) = s.trim().strip_prefix(EMOJI_CODE_PREFIX) {
+            return Some(Self::Emoji(name.to_string()));
+        }
+        if let Some(phrase) = s.trim().strip_prefix(LAUNCH_CODE_PREFIX) {
+            return Some(Self::Launch(phrase.to_string()));
+        }
         match s.trim().to_uppercase().as_str() {
             "ENTER" => Some(Self::Enter),
+            "NEW_PARAGRAPH" => Some(Self::NewParagraph),
             "SELECT_ALL" => Some(Self::SelectAll),
+            "CLEAR_FIELD" => Some(Self::ClearField),
+            "SELECT_WORD" => Some(Self::SelectWord),
+            "SELECT_LINE" => Some(Self::SelectLine),
             "COPY" => Some(Self::Copy),
             "PASTE" => Some(Self::Paste),
             "CUT" => Some(Self::Cut),
+            "INDENT" => Some(Self::Indent),
+            "DEDENT" => Some(Self::Dedent),
+            "CAPS_LOCK" => Some(Self::CapsLock),
+            "DUPLICATE_LINE" => Some(Self::DuplicateLine),
+            "CLICK" => Some(Self::Click),
+            "RIGHT_CLICK" => Some(Self::RightClick),
+            "DOUBLE_CLICK" => Some(Self::DoubleClick),
+            "SCROLL_UP" => Some(Self::ScrollUp),
+            "SCROLL_DOWN" => Some(Self::ScrollDown),
+            "INSERT_DATE" => Some(Self::InsertDate),
+            "INSERT_TIME" => Some(Self::InsertTime),
             "CANCEL" => Some(Self::Cancel),
+            "CANCEL_TYPING" => Some(Self::CancelTyping),
+            "PLAY" => Some(Self::Media(MediaAction::Play)),
+            "PAUSE" => Some(Self::Media(MediaAction::Pause)),
+            "NEXT_TRACK" => Some(Self::Media(MediaAction::NextTrack)),
+            "PREVIOUS_TRACK" => Some(Self::Media(MediaAction::PreviousTrack)),
             _ => None,
         }
     }
@@ -63,11 +162,37 @@ impl VoiceCommand {
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::Enter => "ENTER",
+            Self::NewParagraph => "NEW_PARAGRAPH",
             Self::SelectAll => "SELECT_ALL",
+            Self::ClearField => "CLEAR_FIELD",
+            Self::SelectWord => "SELECT_WORD",
+            Self::SelectLine => "SELECT_LINE",
             Self::Copy => "COPY",
             Self::Paste => "PASTE",
             Self::Cut => "CUT",
+            Self::Indent => "INDENT",
+            Self::Dedent => "DEDENT",
+            Self::CapsLock => "CAPS_LOCK",
+            Self::DuplicateLine => "DUPLICATE_LINE",
+            Self::Click => "CLICK",
+            Self::RightClick => "RIGHT_CLICK",
+            Self::DoubleClick => "DOUBLE_CLICK",
+            Self::ScrollUp => "SCROLL_UP",
+            Self::ScrollDown => "SCROLL_DOWN",
+            Self::InsertDate => "INSERT_DATE",
+            Self::InsertTime => "INSERT_TIME",
             Self::Cancel => "CANCEL",
+            Self::CancelTyping => "CANCEL_TYPING",
+            Self::Backspace(_) => "BACKSPACE",
+            Self::Emoji(_) => "EMOJI",
+            Self::Media(MediaAction::Play) => "PLAY",
+            Self::Media(MediaAction::Pause) => "PAUSE",
+            Self::Media(MediaAction::NextTrack) => "NEXT_TRACK",
+            Self::Media(MediaAction::PreviousTrack) => "PREVIOUS_TRACK",
+            Self::KeyCombo(_) => "KEY_COMBO",
+            Self::Script(_) => "SCRIPT",
+            Self::Shell(_) => "SHELL",
+            Self::Launch(_) => "LAUNCH",
         }
     }
 }
@@ -78,20 +203,155 @@ pub fn execute(command: &VoiceCommand, injector: &dyn InputInjector) -> Result<(
 
     match command {
         VoiceCommand::Enter => injector.press_key(Key::Enter),
+        VoiceCommand::NewParagraph => {
+            injector.press_key(Key::Enter)?;
+            injector.press_key(Key::Enter)
+        }
         VoiceCommand::SelectAll => injector.key_combo(&[Modifier::Ctrl], Key::A),
+        VoiceCommand::ClearField => {
+            execute(&VoiceCommand::SelectAll, injector)?;
+            injector.press_key(Key::Delete)
+        }
+        VoiceCommand::SelectWord => {
+            injector.key_combo(&[Modifier::Ctrl, Modifier::Shift], Key::Left)
+        }
+        VoiceCommand::SelectLine => {
+            injector.press_key(Key::Home)?;
+            injector.key_combo(&[Modifier::Shift], Key::End)
+        }
         VoiceCommand::Copy => injector.key_combo(&[Modifier::Ctrl], Key::C),
         VoiceCommand::Paste => injector.key_combo(&[Modifier::Ctrl], Key::V),
         VoiceCommand::Cut => injector.key_combo(&[Modifier::Ctrl], Key::X),
+        VoiceCommand::Indent => injector.press_key(Key::Tab),
+        VoiceCommand::Dedent => injector.key_combo(&[Modifier::Shift], Key::Tab),
+        VoiceCommand::CapsLock => injector.press_key(Key::CapsLock),
+        VoiceCommand::DuplicateLine => {
+            injector.press_key(Key::Home)?;
+            injector.key_combo(&[Modifier::Shift], Key::End)?;
+            injector.key_combo(&[Modifier::Ctrl], Key::C)?;
+            injector.press_key(Key::End)?;
+            injector.press_key(Key::Enter)?;
+            injector.key_combo(&[Modifier::Ctrl], Key::V)
+        }
+        VoiceCommand::Click => injector.click(MouseButton::Left),
+        VoiceCommand::RightClick => injector.click(MouseButton::Right),
+        VoiceCommand::DoubleClick => injector.double_click(MouseButton::Left),
+        VoiceCommand::ScrollUp => injector.scroll(-SCROLL_NOTCHES),
+        VoiceCommand::ScrollDown => injector.scroll(SCROLL_NOTCHES),
         VoiceCommand::Cancel => {
             debug!("Cancel command - no action taken");
             Ok(())
         }
+        VoiceCommand::CancelTyping => bail!("Cancel typing can't be executed directly"),
+        VoiceCommand::Backspace(count) => {
+            for _ in 0..*count {
+                injector.press_key(Key::Backspace)?;
+            }
+            Ok(())
+        }
+        VoiceCommand::KeyCombo(combo) => {
+            if combo.modifiers.is_empty() {
+                injector.press_key(combo.key)
+            } else {
+                injector.key_combo(&combo.modifiers, combo.key)
+            }
+        }
+        VoiceCommand::Emoji(name) => bail!("Emoji '{}' can't be executed directly", name),
+        VoiceCommand::InsertDate | VoiceCommand::InsertTime => {
+            bail!("{:?} can't be executed directly", command)
+        }
+        VoiceCommand::Media(action) => {
+            bail!("Media command {:?} can't be executed directly", action)
+        }
+        VoiceCommand::Script(code) => bail!("Script command '{}' can't be executed directly", code),
+        VoiceCommand::Shell(code) => bail!("Shell command '{}' can't be executed directly", code),
+        VoiceCommand::Launch(phrase) => bail!("Launching '{}' can't be executed directly", phrase),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use parking_lot::Mutex;
+
+    /// Injector that records the keys it is asked to press.
+    #[derive(Default)]
+    struct KeyRecorder(Mutex<Vec<(Vec<Modifier>, Key)>>);
+
+    /// Injector that records the mouse actions it is asked to make.
+    #[derive(Default)]
+    struct MouseRecorder(Mutex<Vec<String>>);
+
+    impl InputInjector for MouseRecorder {
+        fn backend_name(&self) -> &'static str {
+            "MouseRecorder"
+        }
+
+        fn type_text(&self, _text: &str) -> Result<()> {
+            Ok(())
+        }
+
+        fn press_key(&self, _key: Key) -> Result<()> {
+            Ok(())
+        }
+
+        fn key_combo(&self, _modifiers: &[Modifier], _key: Key) -> Result<()> {
+            Ok(())
+        }
+
+        fn click(&self, button: MouseButton) -> Result<()> {
+            self.0.lock().push(format!("click {:?}", button));
+            Ok(())
+        }
+
+        fn double_click(&self, button: MouseButton) -> Result<()> {
+            self.0.lock().push(format!("double click {:?}", button));
+            Ok(())
+        }
+
+        fn scroll(&self, delta: i32) -> Result<()> {
+            self.0.lock().push(format!("scroll {}", delta));
+            Ok(())
+        }
+    }
+
+    fn mouse_for(command: VoiceCommand) -> Vec<String> {
+        let injector = MouseRecorder::default();
+        execute(&command, &injector).unwrap();
+        injector.0.into_inner()
+    }
+
+    impl KeyRecorder {
+        fn keys(&self) -> Vec<(Vec<Modifier>, Key)> {
+            self.0.lock().clone()
+        }
+    }
+
+    impl InputInjector for KeyRecorder {
+        fn backend_name(&self) -> &'static str {
+            "KeyRecorder"
+        }
+
+        fn type_text(&self, _text: &str) -> Result<()> {
+            Ok(())
+        }
+
+        fn press_key(&self, key: Key) -> Result<()> {
+            self.0.lock().push((Vec::new(), key));
+            Ok(())
+        }
+
+        fn key_combo(&self, modifiers: &[Modifier], key: Key) -> Result<()> {
+            self.0.lock().push((modifiers.to_vec(), key));
+            Ok(())
+        }
+    }
+
+    fn keys_for(command: VoiceCommand) -> Vec<(Vec<Modifier>, Key)> {
+        let injector = KeyRecorder::default();
+        execute(&command, &injector).unwrap();
+        injector.keys()
+    }
 
     #[test]
     fn test_voice_command_parse() {
@@ -107,4 +367,153 @@ mod tests {
         assert_eq!(VoiceCommand::parse("CANCEL"), Some(VoiceCommand::Cancel));
         assert_eq!(VoiceCommand::parse("INVALID"), None);
     }
+
+    #[test]
+    fn test_select_word_and_line() {
+        assert_eq!(
+            VoiceCommand::parse("SELECT_WORD"),
+            Some(VoiceCommand::SelectWord)
+        );
+        assert_eq!(
+            VoiceCommand::parse("select_line"),
+            Some(VoiceCommand::SelectLine)
+        );
+
+        assert_eq!(
+            keys_for(VoiceCommand::SelectWord),
+            vec![(vec![Modifier::Ctrl, Modifier::Shift], Key::Left)]
+        );
+        assert_eq!(
+            keys_for(VoiceCommand::SelectLine),
+            vec![(vec![], Key::Home), (vec![Modifier::Shift], Key::End)]
+        );
+    }
+
+    #[test]
+    fn test_new_paragraph() {
+        assert_eq!(
+            VoiceCommand::parse("NEW_PARAGRAPH"),
+            Some(VoiceCommand::NewParagraph)
+        );
+        assert_eq!(
+            keys_for(VoiceCommand::NewParagraph),
+            vec![(vec![], Key::Enter), (vec![], Key::Enter)]
+        );
+    }
+
+    #[test]
+    fn test_parse_emoji() {
+        assert_eq!(
+            VoiceCommand::parse("EMOJI:smile"),
+            Some(VoiceCommand::Emoji("smile".to_string()))
+        );
+        assert_eq!(VoiceCommand::Emoji("smile".to_string()).as_str(), "EMOJI");
+        assert_eq!(VoiceCommand::parse("EMOJI"), None);
+    }
+
+    #[test]
+    fn test_caps_lock() {
+        assert_eq!(VoiceCommand::parse("CAPS_LOCK"), Some(VoiceCommand::CapsLock));
+        assert_eq!(VoiceCommand::CapsLock.as_str(), "CAPS_LOCK");
+        assert_eq!(
+            keys_for(VoiceCommand::CapsLock),
+            vec![(vec![], Key::CapsLock)]
+        );
+    }
+
+    #[test]
+    fn test_backspace_count() {
+        assert_eq!(keys_for(VoiceCommand::Backspace(0)), vec![]);
+        assert_eq!(
+            keys_for(VoiceCommand::Backspace(3)),
+            vec![(vec![], Key::Backspace); 3]
+        );
+        assert!(execute(&VoiceCommand::CancelTyping, &KeyRecorder::default()).is_err());
+    }
+
+    #[test]
+    fn test_indent_and_dedent() {
+        assert_eq!(VoiceCommand::parse("INDENT"), Some(VoiceCommand::Indent));
+        assert_eq!(VoiceCommand::parse("dedent"), Some(VoiceCommand::Dedent));
+
+        assert_eq!(keys_for(VoiceCommand::Indent), vec![(vec![], Key::Tab)]);
+        assert_eq!(
+            keys_for(VoiceCommand::Dedent),
+            vec![(vec![Modifier::Shift], Key::Tab)]
+        );
+    }
+
+    #[test]
+    fn test_click_commands() {
+        for (code, command, expected) in [
+            ("CLICK", VoiceCommand::Click, "click Left"),
+            ("RIGHT_CLICK", VoiceCommand::RightClick, "click Right"),
+            ("DOUBLE_CLICK", VoiceCommand::DoubleClick, "double click Left"),
+        ] {
+            assert_eq!(VoiceCommand::parse(code), Some(command.clone()));
+            assert_eq!(command.as_str(), code);
+            assert_eq!(mouse_for(command), vec![expected.to_string()]);
+        }
+
+        // Backends without mouse support fail instead of doing nothing
+        assert!(execute(&VoiceCommand::Click, &KeyRecorder::default()).is_err());
+    }
+
+    #[test]
+    fn test_clear_field() {
+        assert_eq!(
+            VoiceCommand::parse("CLEAR_FIELD"),
+            Some(VoiceCommand::ClearField)
+        );
+        assert_eq!(VoiceCommand::ClearField.as_str(), "CLEAR_FIELD");
+        assert_eq!(
+            keys_for(VoiceCommand::ClearField),
+            vec![(vec![Modifier::Ctrl], Key::A), (vec![], Key::Delete)]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_line() {
+        assert_eq!(
+            VoiceCommand::parse("DUPLICATE_LINE"),
+            Some(VoiceCommand::DuplicateLine)
+        );
+        assert_eq!(VoiceCommand::DuplicateLine.as_str(), "DUPLICATE_LINE");
+        assert_eq!(
+            keys_for(VoiceCommand::DuplicateLine),
+            vec![
+                (vec![], Key::Home),
+                (vec![Modifier::Shift], Key::End),
+                (vec![Modifier::Ctrl], Key::C),
+                (vec![], Key::End),
+                (vec![], Key::Enter),
+                (vec![Modifier::Ctrl], Key::V),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scroll_commands() {
+        assert_eq!(VoiceCommand::parse("SCROLL_UP"), Some(VoiceCommand::ScrollUp));
+        assert_eq!(VoiceCommand::ScrollDown.as_str(), "SCROLL_DOWN");
+
+        // Up is negative, down positive
+        assert_eq!(mouse_for(VoiceCommand::ScrollUp), vec!["scroll -3"]);
+        assert_eq!(mouse_for(VoiceCommand::ScrollDown), vec!["scroll 3"]);
+        assert!(execute(&VoiceCommand::ScrollUp, &KeyRecorder::default()).is_err());
+    }
+
+    #[test]
+    fn test_media_command_parse() {
+        for (code, action) in [
+            ("PLAY", MediaAction::Play),
+            ("PAUSE", MediaAction::Pause),
+            ("NEXT_TRACK", MediaAction::NextTrack),
+            ("PREVIOUS_TRACK", MediaAction::PreviousTrack),
+        ] {
+            let command = VoiceCommand::parse(code).unwrap();
+            assert_eq!(command, VoiceCommand::Media(action));
+            assert_eq!(command.as_str(), code);
+        }
+    }
 }
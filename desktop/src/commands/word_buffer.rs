@@ -50,6 +50,10 @@ pub struct WordBuffer {
     current_session: Option<String>,
     /// Pending word for look-ahead (waiting for potential 2nd word of command).
     pending: Option<PendingWord>,
+    /// How long a pending word waits for a second word.
+    look_ahead_timeout: Duration,
+    /// Whether words that could start a 2-word command are held back.
+    look_ahead: bool,
 }
 
 impl Default for WordBuffer {
@@ -64,9 +68,31 @@ impl WordBuffer {
         Self {
             current_session: None,
             pending: None,
+            look_ahead_timeout: LOOK_AHEAD_TIMEOUT,
+            look_ahead: true,
         }
     }
 
+    /// Set how long a word that could start a 2-word command is held back.
+    pub fn with_look_ahead_timeout(mut self, timeout: Duration) -> Self {
+        self.look_ahead_timeout = timeout;
+        self
+    }
+
+    /// Hold back words that could start a 2-word command (the default).
+    ///
+    /// Without look-ahead every word is processed as it arrives, so 2-word
+    /// commands only match when they arrive as one word.
+    pub fn with_look_ahead(mut self, enabled: bool) -> Self {
+        self.look_ahead = enabled;
+        self
+    }
+
+    /// How long a word that could start a 2-word command is held back.
+    pub fn look_ahead_timeout(&self) -> Duration {
+        self.look_ahead_timeout
+    }
+
     /// Reset the buffer state completely.
     ///
     /// Call this when a new connection is established to ensure stale
@@ -130,14 +156,18 @@ impl WordBuffer {
                 return;
             }
 
-            // No 2-word match - emit pending as text
-            results.push(ProcessedItem::Text(format!("{} ", pending.word)));
+            // No 2-word match - emit pending as a command or text
+            if let Some(cmd) = command_matcher(&pending.word) {
+                results.push(ProcessedItem::Command(cmd));
+            } else {
+                results.push(ProcessedItem::Text(format!("{} ", pending.word)));
+            }
 
             // Now process the new word
         }
 
         // Check if this word could start a 2-word command
-        if could_start_two_word(&word) {
+        if self.look_ahead && could_start_two_word(&word) {
             // Buffer for look-ahead
             self.pending = Some(PendingWord {
                 word,
@@ -162,20 +192,31 @@ impl WordBuffer {
     pub fn flush_pending(
         &mut self,
         command_matcher: &dyn Fn(&str) -> Option<String>,
+    ) -> Vec<ProcessedItem> {
+        match self.pending {
+            Some(ref pending) if pending.received_at.elapsed() >= self.look_ahead_timeout => {
+                self.flush_all(command_matcher)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Flush the pending look-ahead word without waiting for the timeout.
+    ///
+    /// Call this on shutdown so the last word isn't lost.
+    pub fn flush_all(
+        &mut self,
+        command_matcher: &dyn Fn(&str) -> Option<String>,
     ) -> Vec<ProcessedItem> {
         let mut results = Vec::new();
 
-        if let Some(ref pending) = self.pending {
-            if pending.received_at.elapsed() >= LOOK_AHEAD_TIMEOUT {
-                let pending = self.pending.take().unwrap();
-
-                // Check if it's a single-word command
-                if let Some(cmd) = command_matcher(&pending.word) {
-                    results.push(ProcessedItem::Command(cmd));
-                } else {
-                    // Emit as text
-                    results.push(ProcessedItem::Text(format!("{} ", pending.word)));
-                }
+        if let Some(pending) = self.pending.take() {
+            // Check if it's a single-word command
+            if let Some(cmd) = command_matcher(&pending.word) {
+                results.push(ProcessedItem::Command(cmd));
+            } else {
+                // Emit as text
+                results.push(ProcessedItem::Text(format!("{} ", pending.word)));
             }
         }
 
@@ -252,6 +293,30 @@ mod tests {
         word.to_lowercase() == "select"
     }
 
+    #[test]
+    fn test_look_ahead_disabled() {
+        let mut buffer = WordBuffer::new().with_look_ahead(false);
+
+        let results = buffer.process_word(
+            "select".to_string(),
+            "s1",
+            &no_command,
+            &select_all_matcher,
+            &could_be_select,
+        );
+        assert_eq!(results, vec![ProcessedItem::Text("select ".to_string())]);
+        assert!(!buffer.has_pending());
+
+        let results = buffer.process_word(
+            "all".to_string(),
+            "s1",
+            &no_command,
+            &select_all_matcher,
+            &could_be_select,
+        );
+        assert_eq!(results, vec![ProcessedItem::Text("all ".to_string())]);
+    }
+
     #[test]
     fn test_words_processed_immediately() {
         let mut buffer = WordBuffer::new();
@@ -443,6 +508,50 @@ mod tests {
         assert!(!buffer.has_pending());
     }
 
+    #[test]
+    fn test_flush_pending_uses_configured_timeout() {
+        let mut buffer = WordBuffer::new().with_look_ahead_timeout(Duration::from_millis(300));
+
+        buffer.process_word(
+            "select".to_string(),
+            "session1",
+            &simple_command_matcher,
+            &select_all_matcher,
+            &could_be_select,
+        );
+
+        // Past the default timeout but within the configured one
+        if let Some(ref mut pending) = buffer.pending {
+            pending.received_at = Instant::now() - Duration::from_millis(200);
+        }
+        let items = buffer.flush_pending(&simple_command_matcher);
+        assert!(items.is_empty(), "should wait for the configured timeout");
+
+        if let Some(ref mut pending) = buffer.pending {
+            pending.received_at = Instant::now() - Duration::from_millis(400);
+        }
+        let items = buffer.flush_pending(&simple_command_matcher);
+        assert_eq!(items, vec![ProcessedItem::Text("select ".to_string())]);
+    }
+
+    #[test]
+    fn test_flush_all_ignores_timeout() {
+        let mut buffer = WordBuffer::new();
+
+        buffer.process_word(
+            "select".to_string(),
+            "session1",
+            &simple_command_matcher,
+            &select_all_matcher,
+            &could_be_select,
+        );
+
+        let items = buffer.flush_all(&simple_command_matcher);
+        assert_eq!(items, vec![ProcessedItem::Text("select ".to_string())]);
+        assert!(!buffer.has_pending());
+        assert!(buffer.flush_all(&simple_command_matcher).is_empty());
+    }
+
     #[test]
     fn test_flush_pending_single_word_command() {
         let mut buffer = WordBuffer::new();
@@ -475,6 +584,42 @@ mod tests {
         assert_eq!(items, vec![ProcessedItem::Command("SELECT".to_string())]);
     }
 
+    #[test]
+    fn test_look_ahead_no_match_emits_pending_command() {
+        let mut buffer = WordBuffer::new();
+
+        // "cancel" is a command and also starts "cancel typing"
+        let cancel_is_command = |word: &str| -> Option<String> {
+            (word == "cancel").then(|| "CANCEL".to_string())
+        };
+        let could_be_cancel = |word: &str| word == "cancel";
+        let cancel_typing_matcher = |w1: &str, w2: &str| -> Option<String> {
+            (w1 == "cancel" && w2 == "typing").then(|| "CANCEL_TYPING".to_string())
+        };
+
+        buffer.process_word(
+            "cancel".to_string(),
+            "session1",
+            &cancel_is_command,
+            &cancel_typing_matcher,
+            &could_be_cancel,
+        );
+        let items = buffer.process_word(
+            "that".to_string(),
+            "session1",
+            &cancel_is_command,
+            &cancel_typing_matcher,
+            &could_be_cancel,
+        );
+        assert_eq!(
+            items,
+            vec![
+                ProcessedItem::Command("CANCEL".to_string()),
+                ProcessedItem::Text("that ".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn test_mixed_text_and_commands() {
         let mut buffer = WordBuffer::new();
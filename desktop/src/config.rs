@@ -16,10 +16,20 @@
 //!
 //! Handles loading and saving application settings.
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use gethostname::gethostname;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::{RwLock, RwLockReadGuard};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use crate::commands;
+use crate::input::KeyCombo;
 
 /// Get a sanitized hostname suitable for Bluetooth device name.
 /// Bluetooth names should only contain alphanumeric chars, spaces, and hyphens.
@@ -45,6 +55,41 @@ fn get_sanitized_hostname() -> String {
     }
 }
 
+/// Current config file format version.
+///
+/// Files without a version are treated as version 0.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Name of the app's data and config directories.
+const APP_DIR: &str = "speech2prompt";
+
+/// Environment variable overriding the data directory.
+pub const DATA_DIR_ENV: &str = "SPEECH2PROMPT_DATA_DIR";
+
+/// Environment variable overriding the directory of the config file.
+pub const CONFIG_DIR_ENV: &str = "SPEECH2PROMPT_CONFIG_DIR";
+
+/// The app's directory under `base`, unless `env_dir` (a non-empty
+/// environment override) says otherwise.
+fn app_dir(env_dir: Option<OsString>, base: Option<PathBuf>) -> PathBuf {
+    match env_dir {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => base.unwrap_or_else(|| PathBuf::from(".")).join(APP_DIR),
+    }
+}
+
+/// Data directory: `$SPEECH2PROMPT_DATA_DIR`, or `speech2prompt` in the
+/// user data directory.
+pub fn data_dir() -> PathBuf {
+    app_dir(std::env::var_os(DATA_DIR_ENV), dirs::data_dir())
+}
+
+/// Config directory: `$SPEECH2PROMPT_CONFIG_DIR`, or `speech2prompt` in the
+/// user config directory.
+pub fn config_dir() -> PathBuf {
+    app_dir(std::env::var_os(CONFIG_DIR_ENV), dirs::config_dir())
+}
+
 /// Application configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -52,11 +97,47 @@ pub struct Config {
     #[serde(skip)]
     pub data_dir: PathBuf,
 
+    /// Config file format version.
+    #[serde(default)]
+    pub version: u32,
+
+    /// Run without tray and dialogs (also enabled with `--headless`).
+    #[serde(default)]
+    pub headless: bool,
+
+    /// Accept text and commands from local programs on a Unix socket.
+    #[serde(default)]
+    pub control_socket: bool,
+
+    /// Report the app status as JSON on a Unix socket.
+    #[serde(default)]
+    pub status_socket: bool,
+
     /// Bluetooth settings.
     pub bluetooth: BluetoothConfig,
 
     /// Input settings.
     pub input: InputConfig,
+
+    /// Desktop notification settings.
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+
+    /// Outbound webhook settings.
+    #[serde(default)]
+    pub webhook: WebhookConfig,
+
+    /// Shell commands that voice commands may run.
+    #[serde(default)]
+    pub shell: ShellConfig,
+
+    /// Applications that spoken phrases may launch.
+    #[serde(default)]
+    pub apps: AppsConfig,
+
+    /// Log file settings.
+    #[serde(default)]
+    pub logging: LoggingConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,38 +149,417 @@ pub struct BluetoothConfig {
     pub device_name: String,
 
     /// Auto-accept connections from paired devices.
+    ///
+    /// Off by default: a paired device is only recognized by the device ID
+    /// it sends in the clear, so anyone who learns that ID could pair
+    /// without asking.
     pub auto_accept: bool,
+
+    /// Tries to find and power on the adapter at startup, waiting longer
+    /// after each one. The adapter is often slow to come up at boot.
+    #[serde(default = "default_power_on_attempts")]
+    pub power_on_attempts: u32,
+
+    /// Close the session after this many minutes without dictation, so the
+    /// phone has to pair again. 0 disables.
+    #[serde(default)]
+    pub idle_timeout_mins: u64,
+
+    /// Reject a pairing request after its dialog has been open this many
+    /// seconds. 0 keeps the dialog open until answered.
+    #[serde(default = "default_dialog_timeout_secs")]
+    pub dialog_timeout_secs: u64,
 }
 
 impl Default for BluetoothConfig {
     fn default() -> Self {
         Self {
             device_name: get_sanitized_hostname(),
-            auto_accept: true,
+            auto_accept: false,
+            power_on_attempts: default_power_on_attempts(),
+            idle_timeout_mins: 0,
+            dialog_timeout_secs: default_dialog_timeout_secs(),
         }
     }
 }
 
+impl BluetoothConfig {
+    /// How long a session may go without dictation.
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        (self.idle_timeout_mins > 0).then(|| Duration::from_secs(self.idle_timeout_mins * 60))
+    }
+
+    /// How long the pairing dialog waits for an answer.
+    pub fn dialog_timeout(&self) -> Option<Duration> {
+        (self.dialog_timeout_secs > 0).then(|| Duration::from_secs(self.dialog_timeout_secs))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InputConfig {
     /// Delay between keystrokes in milliseconds.
     pub typing_delay_ms: u32,
 
-    /// Preferred backend: "auto", "x11", or "wayland".
+    /// Preferred backend: "auto", "x11", "wayland" or "atspi".
     pub prefer_backend: String,
+
+    /// How long the Manage Commands recording dialog waits for a phrase, in seconds.
+    #[serde(default = "default_recording_timeout_secs")]
+    pub recording_timeout_secs: u64,
+
+    /// Global hotkey toggling input injection (X11 only), e.g. "super+shift+v".
+    /// Empty to disable.
+    #[serde(default = "default_toggle_hotkey")]
+    pub toggle_hotkey: String,
+
+    /// How often held-back words are checked for timeouts, in milliseconds.
+    #[serde(default = "default_flush_interval_ms")]
+    pub flush_interval_ms: u64,
+
+    /// How long a word that could start a 2-word command waits for the second
+    /// word, in milliseconds.
+    #[serde(default = "default_look_ahead_ms")]
+    pub look_ahead_ms: u64,
+
+    /// Process every word as it arrives instead of holding back words that
+    /// could start a 2-word command. Saves the look-ahead delay, but 2-word
+    /// commands then only run when the phone sends them as one word.
+    #[serde(default)]
+    pub disable_look_ahead: bool,
+
+    /// How long out-of-order words wait before they are flushed, in milliseconds.
+    #[serde(default = "default_stale_timeout_ms")]
+    pub stale_timeout_ms: u64,
+
+    /// Ignore a command repeated within this many milliseconds, e.g. when the
+    /// recognizer sends a word twice. 0 lets every repeat through.
+    #[serde(default)]
+    pub command_cooldown_ms: u64,
+
+    /// Type at most this many characters per second, so a runaway stream
+    /// can't flood the focused app. Text over the limit is typed at this
+    /// rate. 0 for no limit.
+    #[serde(default)]
+    pub max_chars_per_sec: u32,
+
+    /// What to do when the focused window changes mid-dictation: "off",
+    /// "warn" (log and notify) or "pause" (also pause typing).
+    #[serde(default = "default_focus_check")]
+    pub focus_check: String,
+
+    /// Drop a text identical to the previous one within this many
+    /// milliseconds, as the phone resends text it got no ACK for. 0 types
+    /// every text.
+    #[serde(default = "default_duplicate_text_window_ms")]
+    pub duplicate_text_window_ms: u64,
+
+    /// strftime format typed by "insert date", e.g. "%d.%m.%Y".
+    #[serde(default = "default_date_format")]
+    pub date_format: String,
+
+    /// strftime format typed by "insert time", e.g. "%I:%M %p".
+    #[serde(default = "default_time_format")]
+    pub time_format: String,
+
+    /// Key combinations pressed by "duplicate line", e.g. ["ctrl+shift+d"].
+    /// Empty copies and pastes the line.
+    #[serde(default)]
+    pub duplicate_line_keys: Vec<String>,
+
+    /// Also match phrases that only sound like a command's phrase, e.g.
+    /// "write click" for "right click". Off by default, as ordinary words
+    /// can then run commands.
+    #[serde(default)]
+    pub phonetic_matching: bool,
+
+    /// Language of the phrases, e.g. "tr_TR", so letters are lowercased by
+    /// its rules when matching ("I" is "ı" in Turkish). Empty uses the
+    /// Unicode defaults.
+    #[serde(default)]
+    pub locale: String,
+
+    /// Replacements for the default phrases of built-in commands, by
+    /// command code, e.g. `SELECT_ALL = "mark everything"`. Custom phrases
+    /// set in Manage Commands still win.
+    #[serde(default)]
+    pub default_phrases: BTreeMap<String, String>,
+}
+
+/// Input backends accepted in `prefer_backend`.
+const BACKENDS: &[&str] = &["auto", "x11", "wayland", "atspi"];
+
+/// Modes accepted in `focus_check`.
+const FOCUS_CHECKS: &[&str] = &["off", "warn", "pause"];
+
+/// Modes accepted in `notifications.pairing_prompt`.
+const PAIRING_PROMPTS: &[&str] = &["dialog", "notification"];
+
+/// Levels accepted in `logging.file_level`.
+const LOG_LEVELS: &[&str] = &["error", "warn", "info", "debug", "trace"];
+
+/// Maximum delay between keystrokes in milliseconds.
+const MAX_TYPING_DELAY_MS: u32 = 1000;
+
+/// Allowed range for the recording timeout in seconds.
+const RECORDING_TIMEOUT_RANGE: (u64, u64) = (5, 300);
+
+/// Allowed range for the periodic flush interval in milliseconds.
+const FLUSH_INTERVAL_RANGE: (u64, u64) = (10, 1000);
+
+/// Allowed range for the look-ahead and stale-word timeouts in milliseconds.
+const WORD_TIMEOUT_RANGE: (u64, u64) = (10, 10_000);
+
+/// Allowed range for the command cooldown in milliseconds.
+const COMMAND_COOLDOWN_RANGE: (u64, u64) = (0, 5_000);
+
+/// Allowed range for the duplicate text window in milliseconds.
+const DUPLICATE_TEXT_WINDOW_RANGE: (u64, u64) = (0, 10_000);
+
+/// Allowed range for the webhook timeout in milliseconds.
+const WEBHOOK_TIMEOUT_RANGE: (u64, u64) = (100, 30_000);
+
+/// Allowed range for the shell command timeout in seconds.
+const SHELL_TIMEOUT_RANGE: (u64, u64) = (1, 600);
+
+/// Allowed range for the adapter power-on attempts.
+const POWER_ON_ATTEMPTS_RANGE: (u32, u32) = (1, 20);
+
+fn default_power_on_attempts() -> u32 {
+    5
+}
+
+fn default_dialog_timeout_secs() -> u64 {
+    60
+}
+
+fn default_recording_timeout_secs() -> u64 {
+    30
+}
+
+fn default_toggle_hotkey() -> String {
+    "super+shift+v".to_string()
+}
+
+fn default_flush_interval_ms() -> u64 {
+    50
+}
+
+fn default_look_ahead_ms() -> u64 {
+    100
+}
+
+fn default_stale_timeout_ms() -> u64 {
+    500
+}
+
+fn default_focus_check() -> String {
+    "off".to_string()
+}
+
+fn default_duplicate_text_window_ms() -> u64 {
+    2000
+}
+
+fn default_date_format() -> String {
+    commands::DEFAULT_DATE_FORMAT.to_string()
+}
+
+fn default_time_format() -> String {
+    commands::DEFAULT_TIME_FORMAT.to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationConfig {
+    /// Show desktop notifications on connect, disconnect and pairing requests.
+    pub enabled: bool,
+
+    /// How pairing requests are approved: "dialog" or "notification", for
+    /// compositors where the dialog doesn't get focus.
+    pub pairing_prompt: String,
+}
+
+impl NotificationConfig {
+    /// Whether pairing requests are approved from a notification.
+    pub fn prompts_with_notification(&self) -> bool {
+        self.pairing_prompt == "notification"
+    }
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            pairing_prompt: default_pairing_prompt(),
+        }
+    }
+}
+
+fn default_pairing_prompt() -> String {
+    "dialog".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// `http://` URL that processed text and commands are POSTed to.
+    /// Empty to disable. Requests are not encrypted, so the URL must point
+    /// to this machine.
+    pub url: String,
+
+    /// How long a webhook request may take, in milliseconds.
+    pub timeout_ms: u64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            timeout_ms: 2000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShellConfig {
+    /// Allow voice commands to run shell commands. Off by default.
+    pub enabled: bool,
+
+    /// How long a shell command may run before it is killed, in seconds.
+    pub timeout_secs: u64,
+
+    /// Shell commands voice commands may run, by name. A voice command
+    /// mapping only refers to a name here, never to a command line.
+    pub allowed: BTreeMap<String, String>,
+}
+
+impl Default for ShellConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout_secs: 10,
+            allowed: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppsConfig {
+    /// Let spoken phrases launch the applications below. Off by default.
+    pub enabled: bool,
+
+    /// What each phrase launches, e.g. "open terminal" = "gnome-terminal".
+    /// URLs and paths are opened with `xdg-open`, anything else is run as a
+    /// program with arguments.
+    pub launch: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// Also write logs to daily rotated files in `logs` under the data
+    /// directory.
+    pub file: bool,
+
+    /// Most verbose level written to the log file: "error", "warn", "info",
+    /// "debug" or "trace".
+    pub file_level: String,
+
+    /// Log only the length of dictated text, unless trace logging is on.
+    pub redact_content: bool,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            file: false,
+            file_level: "debug".to_string(),
+            redact_content: true,
+        }
+    }
+}
+
+impl ShellConfig {
+    /// Command line of the allowlisted shell command `name`.
+    ///
+    /// Fails when shell commands are disabled or `name` isn't allowlisted.
+    pub fn command(&self, name: &str) -> Result<&str> {
+        if !self.enabled {
+            bail!("shell commands are disabled (set [shell] enabled = true)");
+        }
+        self.allowed
+            .get(name)
+            .map(String::as_str)
+            .with_context(|| format!("'{}' is not in the [shell.allowed] list", name))
+    }
+}
+
+impl AppsConfig {
+    /// What the phrase `phrase` launches.
+    ///
+    /// Fails when launching is disabled or `phrase` isn't configured.
+    pub fn target(&self, phrase: &str) -> Result<&str> {
+        if !self.enabled {
+            bail!("launching applications is disabled (set [apps] enabled = true)");
+        }
+        self.launch
+            .get(phrase)
+            .map(String::as_str)
+            .with_context(|| format!("'{}' is not in the [apps.launch] list", phrase))
+    }
+}
+
+/// Settings given on the command line.
+///
+/// Set fields take precedence over the config file.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    /// Bluetooth device name.
+    pub device_name: Option<String>,
+    /// Preferred input backend.
+    pub prefer_backend: Option<String>,
+    /// Data directory.
+    pub data_dir: Option<PathBuf>,
+    /// Run headless.
+    pub headless: bool,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            data_dir: dirs::data_dir()
-                .unwrap_or_else(|| PathBuf::from("."))
-                .join("speech2prompt"),
+            data_dir: data_dir(),
+            version: CONFIG_VERSION,
+            headless: false,
+            control_socket: false,
+            status_socket: false,
             bluetooth: BluetoothConfig::default(),
             input: InputConfig {
                 typing_delay_ms: 10,
                 prefer_backend: "auto".to_string(),
+                recording_timeout_secs: default_recording_timeout_secs(),
+                toggle_hotkey: default_toggle_hotkey(),
+                flush_interval_ms: default_flush_interval_ms(),
+                look_ahead_ms: default_look_ahead_ms(),
+                disable_look_ahead: false,
+                stale_timeout_ms: default_stale_timeout_ms(),
+                command_cooldown_ms: 0,
+                max_chars_per_sec: 0,
+                focus_check: default_focus_check(),
+                duplicate_text_window_ms: default_duplicate_text_window_ms(),
+                date_format: default_date_format(),
+                time_format: default_time_format(),
+                duplicate_line_keys: Vec::new(),
+                phonetic_matching: false,
+                locale: String::new(),
+                default_phrases: BTreeMap::new(),
             },
+            notifications: NotificationConfig::default(),
+            webhook: WebhookConfig::default(),
+            shell: ShellConfig::default(),
+            apps: AppsConfig::default(),
+            logging: LoggingConfig::default(),
         }
     }
 }
@@ -107,17 +567,13 @@ impl Default for Config {
 impl Config {
     /// Load configuration from file or create default.
     pub fn load() -> Result<Self> {
-        let config_dir = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("speech2prompt");
-
-        std::fs::create_dir_all(&config_dir)?;
-
-        let config_path = config_dir.join("config.toml");
+        let config_path = Self::path();
+        if let Some(config_dir) = config_path.parent() {
+            std::fs::create_dir_all(config_dir)?;
+        }
 
         let mut config = if config_path.exists() {
-            let content = std::fs::read_to_string(&config_path)?;
-            toml::from_str(&content)?
+            Self::load_file(&config_path)?
         } else {
             let config = Self::default();
             let content = toml::to_string_pretty(&config)?;
@@ -126,25 +582,836 @@ impl Config {
         };
 
         // Set data directory
-        config.data_dir = dirs::data_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("speech2prompt");
+        config.data_dir = data_dir();
         std::fs::create_dir_all(&config.data_dir)?;
 
         Ok(config)
     }
 
+    /// Path of the config file.
+    pub fn path() -> PathBuf {
+        config_dir().join("config.toml")
+    }
+
+    /// Load a config file, migrating and validating it.
+    ///
+    /// A file in an older format is rewritten in the current one.
+    fn load_file(path: &Path) -> Result<Self> {
+        let (mut config, migrated) = Self::read_file(path)?;
+        if migrated {
+            std::fs::write(path, toml::to_string_pretty(&config)?)
+                .with_context(|| format!("Failed to rewrite {:?}", path))?;
+            info!("Config file upgraded to version {}", CONFIG_VERSION);
+        }
+        config.validate();
+        Ok(config)
+    }
+
+    /// Re-read the config file at `path` as the app would run with it.
+    ///
+    /// The command-line `overrides` still apply, so they don't show up as
+    /// changes.
+    fn reread(path: &Path, overrides: &ConfigOverrides) -> Result<Self> {
+        let (mut file, _) = Self::read_file(path)?;
+        file.validate();
+        file.apply_overrides(overrides);
+        Ok(file)
+    }
+
+    /// Read and parse a config file, migrating it in memory.
+    ///
+    /// Returns the config and whether it was migrated.
+    fn read_file(path: &Path) -> Result<(Self, bool)> {
+        let content =
+            std::fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+        let mut table: toml::Table =
+            toml::from_str(&content).with_context(|| format!("Failed to parse {:?}", path))?;
+        let migrated = migrate(&mut table);
+        let config =
+            Self::deserialize(table).with_context(|| format!("Failed to parse {:?}", path))?;
+        Ok((config, migrated))
+    }
+
+    /// Clamp out-of-range values and replace unknown ones with defaults.
+    ///
+    /// Each corrected value is logged as a warning.
+    pub fn validate(&mut self) {
+        if self.input.typing_delay_ms > MAX_TYPING_DELAY_MS {
+            warn!(
+                "typing_delay_ms {} is too large, using {}",
+                self.input.typing_delay_ms, MAX_TYPING_DELAY_MS
+            );
+            self.input.typing_delay_ms = MAX_TYPING_DELAY_MS;
+        }
+
+        let backend = self.input.prefer_backend.trim().to_lowercase();
+        if BACKENDS.contains(&backend.as_str()) {
+            self.input.prefer_backend = backend;
+        } else {
+            warn!(
+                "Unknown prefer_backend '{}', using \"auto\" (expected one of {:?})",
+                self.input.prefer_backend, BACKENDS
+            );
+            self.input.prefer_backend = "auto".to_string();
+        }
+
+        let focus_check = self.input.focus_check.trim().to_lowercase();
+        if FOCUS_CHECKS.contains(&focus_check.as_str()) {
+            self.input.focus_check = focus_check;
+        } else {
+            warn!(
+                "Unknown focus_check '{}', using \"off\" (expected one of {:?})",
+                self.input.focus_check, FOCUS_CHECKS
+            );
+            self.input.focus_check = default_focus_check();
+        }
+
+        let pairing_prompt = self.notifications.pairing_prompt.trim().to_lowercase();
+        if PAIRING_PROMPTS.contains(&pairing_prompt.as_str()) {
+            self.notifications.pairing_prompt = pairing_prompt;
+        } else {
+            warn!(
+                "Unknown pairing_prompt '{}', using \"dialog\" (expected one of {:?})",
+                self.notifications.pairing_prompt, PAIRING_PROMPTS
+            );
+            self.notifications.pairing_prompt = default_pairing_prompt();
+        }
+
+        for (name, format, default) in [
+            (
+                "date_format",
+                &mut self.input.date_format,
+                commands::DEFAULT_DATE_FORMAT,
+            ),
+            (
+                "time_format",
+                &mut self.input.time_format,
+                commands::DEFAULT_TIME_FORMAT,
+            ),
+        ] {
+            if !commands::is_valid_format(format) {
+                warn!("Invalid {} '{}', using \"{}\"", name, format, default);
+                *format = default.to_string();
+            }
+        }
+
+        if let Some(keys) = self
+            .input
+            .duplicate_line_keys
+            .iter()
+            .find(|keys| KeyCombo::parse(keys).is_none())
+        {
+            warn!(
+                "Invalid duplicate_line_keys '{}', using the default sequence",
+                keys
+            );
+            self.input.duplicate_line_keys.clear();
+        }
+
+        let level = self.logging.file_level.trim().to_lowercase();
+        if LOG_LEVELS.contains(&level.as_str()) {
+            self.logging.file_level = level;
+        } else {
+            let default = LoggingConfig::default().file_level;
+            warn!(
+                "Unknown logging.file_level '{}', using \"{}\" (expected one of {:?})",
+                self.logging.file_level, default, LOG_LEVELS
+            );
+            self.logging.file_level = default;
+        }
+
+        let (min, max) = RECORDING_TIMEOUT_RANGE;
+        let timeout = self.input.recording_timeout_secs.clamp(min, max);
+        if timeout != self.input.recording_timeout_secs {
+            warn!(
+                "recording_timeout_secs {} is out of range, using {}",
+                self.input.recording_timeout_secs, timeout
+            );
+            self.input.recording_timeout_secs = timeout;
+        }
+
+        let (min, max) = POWER_ON_ATTEMPTS_RANGE;
+        let attempts = self.bluetooth.power_on_attempts.clamp(min, max);
+        if attempts != self.bluetooth.power_on_attempts {
+            warn!(
+                "power_on_attempts {} is out of range, using {}",
+                self.bluetooth.power_on_attempts, attempts
+            );
+            self.bluetooth.power_on_attempts = attempts;
+        }
+
+        for (name, value, (min, max)) in [
+            (
+                "flush_interval_ms",
+                &mut self.input.flush_interval_ms,
+                FLUSH_INTERVAL_RANGE,
+            ),
+            (
+                "look_ahead_ms",
+                &mut self.input.look_ahead_ms,
+                WORD_TIMEOUT_RANGE,
+            ),
+            (
+                "stale_timeout_ms",
+                &mut self.input.stale_timeout_ms,
+                WORD_TIMEOUT_RANGE,
+            ),
+            (
+                "command_cooldown_ms",
+                &mut self.input.command_cooldown_ms,
+                COMMAND_COOLDOWN_RANGE,
+            ),
+            (
+                "duplicate_text_window_ms",
+                &mut self.input.duplicate_text_window_ms,
+                DUPLICATE_TEXT_WINDOW_RANGE,
+            ),
+            (
+                "webhook.timeout_ms",
+                &mut self.webhook.timeout_ms,
+                WEBHOOK_TIMEOUT_RANGE,
+            ),
+            (
+                "shell.timeout_secs",
+                &mut self.shell.timeout_secs,
+                SHELL_TIMEOUT_RANGE,
+            ),
+        ] {
+            let clamped = (*value).clamp(min, max);
+            if clamped != *value {
+                warn!("{} {} is out of range, using {}", name, value, clamped);
+                *value = clamped;
+            }
+        }
+    }
+
+    /// Take over settings from a re-read config file.
+    ///
+    /// Only settings that can change at runtime are applied. The others keep
+    /// their running value and a notice is logged if they changed.
+    pub fn apply_reload(&mut self, file: &Config) {
+        for name in self.restart_required_changes(file) {
+            info!("Config setting '{}' changed; restart to apply it", name);
+        }
+
+        self.bluetooth.auto_accept = file.bluetooth.auto_accept;
+        self.bluetooth.dialog_timeout_secs = file.bluetooth.dialog_timeout_secs;
+        self.input.typing_delay_ms = file.input.typing_delay_ms;
+        self.input.recording_timeout_secs = file.input.recording_timeout_secs;
+        self.notifications.pairing_prompt = file.notifications.pairing_prompt.clone();
+    }
+
+    /// Names of settings that differ from `file` but need a restart.
+    fn restart_required_changes(&self, file: &Config) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.headless != file.headless {
+            changed.push("headless");
+        }
+        if self.bluetooth.power_on_attempts != file.bluetooth.power_on_attempts {
+            changed.push("bluetooth.power_on_attempts");
+        }
+        if self.bluetooth.idle_timeout_mins != file.bluetooth.idle_timeout_mins {
+            changed.push("bluetooth.idle_timeout_mins");
+        }
+        if self.control_socket != file.control_socket {
+            changed.push("control_socket");
+        }
+        if self.status_socket != file.status_socket {
+            changed.push("status_socket");
+        }
+        if self.input.prefer_backend != file.input.prefer_backend {
+            changed.push("input.prefer_backend");
+        }
+        if self.input.toggle_hotkey != file.input.toggle_hotkey {
+            changed.push("input.toggle_hotkey");
+        }
+        if self.input.flush_interval_ms != file.input.flush_interval_ms {
+            changed.push("input.flush_interval_ms");
+        }
+        if self.input.look_ahead_ms != file.input.look_ahead_ms {
+            changed.push("input.look_ahead_ms");
+        }
+        if self.input.disable_look_ahead != file.input.disable_look_ahead {
+            changed.push("input.disable_look_ahead");
+        }
+        if self.input.stale_timeout_ms != file.input.stale_timeout_ms {
+            changed.push("input.stale_timeout_ms");
+        }
+        if self.input.command_cooldown_ms != file.input.command_cooldown_ms {
+            changed.push("input.command_cooldown_ms");
+        }
+        if self.input.max_chars_per_sec != file.input.max_chars_per_sec {
+            changed.push("input.max_chars_per_sec");
+        }
+        if self.input.focus_check != file.input.focus_check {
+            changed.push("input.focus_check");
+        }
+        if self.input.duplicate_text_window_ms != file.input.duplicate_text_window_ms {
+            changed.push("input.duplicate_text_window_ms");
+        }
+        if self.input.date_format != file.input.date_format {
+            changed.push("input.date_format");
+        }
+        if self.input.time_format != file.input.time_format {
+            changed.push("input.time_format");
+        }
+        if self.input.duplicate_line_keys != file.input.duplicate_line_keys {
+            changed.push("input.duplicate_line_keys");
+        }
+        if self.input.phonetic_matching != file.input.phonetic_matching {
+            changed.push("input.phonetic_matching");
+        }
+        if self.input.locale != file.input.locale {
+            changed.push("input.locale");
+        }
+        if self.input.default_phrases != file.input.default_phrases {
+            changed.push("input.default_phrases");
+        }
+        if self.notifications.enabled != file.notifications.enabled {
+            changed.push("notifications.enabled");
+        }
+        if self.webhook.url != file.webhook.url {
+            changed.push("webhook.url");
+        }
+        if self.webhook.timeout_ms != file.webhook.timeout_ms {
+            changed.push("webhook.timeout_ms");
+        }
+        if self.shell != file.shell {
+            changed.push("shell");
+        }
+        if self.apps != file.apps {
+            changed.push("apps");
+        }
+        if self.logging != file.logging {
+            changed.push("logging");
+        }
+        changed
+    }
+
+    /// Apply command-line overrides on top of the loaded file.
+    pub fn apply_overrides(&mut self, overrides: &ConfigOverrides) {
+        if let Some(device_name) = &overrides.device_name {
+            self.bluetooth.device_name = device_name.clone();
+        }
+        if let Some(backend) = &overrides.prefer_backend {
+            self.input.prefer_backend = backend.clone();
+        }
+        if let Some(data_dir) = &overrides.data_dir {
+            self.data_dir = data_dir.clone();
+        }
+        if overrides.headless {
+            self.headless = true;
+        }
+    }
+
     /// Save configuration to file.
     #[allow(dead_code)]
     pub fn save(&self) -> Result<()> {
-        let config_dir = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("speech2prompt");
-
-        let config_path = config_dir.join("config.toml");
         let content = toml::to_string_pretty(self)?;
-        std::fs::write(config_path, content)?;
+        std::fs::write(Self::path(), content)?;
 
         Ok(())
     }
 }
+
+/// Upgrade a parsed config file to [`CONFIG_VERSION`].
+///
+/// Each step upgrades one version. Returns whether anything changed.
+fn migrate(table: &mut toml::Table) -> bool {
+    // Files written before versioning have no version field
+    let version = table
+        .get("version")
+        .and_then(toml::Value::as_integer)
+        .unwrap_or_default();
+    if version > i64::from(CONFIG_VERSION) {
+        warn!(
+            "Config file version {} is newer than supported version {}",
+            version, CONFIG_VERSION
+        );
+        return false;
+    }
+    if version == i64::from(CONFIG_VERSION) {
+        return false;
+    }
+
+    // Version 0 -> 1: unversioned files only gain the version field
+    debug!(
+        "Migrating config from version {} to {}",
+        version, CONFIG_VERSION
+    );
+
+    table.insert(
+        "version".to_string(),
+        toml::Value::Integer(i64::from(CONFIG_VERSION)),
+    );
+    true
+}
+
+/// Configuration that follows edits to `config.toml`.
+pub struct LiveConfig {
+    /// Current configuration.
+    config: Arc<RwLock<Config>>,
+    /// File watcher (kept alive).
+    _watcher: Option<RecommendedWatcher>,
+}
+
+impl LiveConfig {
+    /// Wrap `config` without watching the file.
+    pub fn new(config: Config) -> Self {
+        Self {
+            config: Arc::new(RwLock::new(config)),
+            _watcher: None,
+        }
+    }
+
+    /// Start from `config` and merge in changes to the file at `path`, with
+    /// the command-line `overrides` applied.
+    pub fn new_with_watcher(
+        config: Config,
+        path: &Path,
+        overrides: ConfigOverrides,
+    ) -> Result<Self> {
+        let config = Arc::new(RwLock::new(config));
+        let config_dir = path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."));
+
+        let path_watch = path.to_path_buf();
+        let config_watch = config.clone();
+
+        let mut watcher = RecommendedWatcher::new(
+            move |res: Result<Event, notify::Error>| match res {
+                Ok(event) => {
+                    let is_config = event
+                        .paths
+                        .iter()
+                        .any(|p| p.file_name() == path_watch.file_name());
+                    if is_config && (event.kind.is_modify() || event.kind.is_create()) {
+                        debug!("Config file changed, reloading...");
+                        match Config::reread(&path_watch, &overrides) {
+                            Ok(file) => {
+                                config_watch.write().apply_reload(&file);
+                                info!("Configuration reloaded");
+                            }
+                            Err(e) => warn!("Ignoring config change: {:#}", e),
+                        }
+                    }
+                }
+                Err(e) => {
+                    error!("Config watcher error: {}", e);
+                }
+            },
+            notify::Config::default().with_poll_interval(Duration::from_millis(500)),
+        )?;
+
+        watcher.watch(&config_dir, RecursiveMode::NonRecursive)?;
+        info!("Watching {:?} for config changes", path);
+
+        Ok(Self {
+            config,
+            _watcher: Some(watcher),
+        })
+    }
+
+    /// Read the current configuration.
+    pub fn read(&self) -> RwLockReadGuard<'_, Config> {
+        self.config.read()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"
+        headless = true
+
+        [bluetooth]
+        auto_accept = true
+
+        [input]
+        typing_delay_ms = 20
+        prefer_backend = "x11"
+    "#;
+
+    #[test]
+    fn test_file_overrides_default() {
+        let default = Config::default();
+        let config: Config = toml::from_str(FILE).unwrap();
+
+        assert_eq!(default.input.prefer_backend, "auto");
+        assert_eq!(config.input.prefer_backend, "x11");
+        assert_eq!(config.input.typing_delay_ms, 20);
+        assert!(!default.bluetooth.auto_accept);
+        assert!(config.bluetooth.auto_accept);
+        assert!(config.headless);
+        // Missing settings keep their defaults
+        assert_eq!(
+            config.input.recording_timeout_secs,
+            default.input.recording_timeout_secs
+        );
+    }
+
+    #[test]
+    fn test_cli_overrides_file() {
+        let mut config: Config = toml::from_str(FILE).unwrap();
+        config.apply_overrides(&ConfigOverrides {
+            device_name: Some("workstation".to_string()),
+            prefer_backend: Some("wayland".to_string()),
+            data_dir: Some(PathBuf::from("/tmp/s2p")),
+            headless: false,
+        });
+
+        assert_eq!(config.bluetooth.device_name, "workstation");
+        assert_eq!(config.input.prefer_backend, "wayland");
+        assert_eq!(config.data_dir, PathBuf::from("/tmp/s2p"));
+        // An absent flag doesn't turn headless off
+        assert!(config.headless);
+        // Settings without an override come from the file
+        assert_eq!(config.input.typing_delay_ms, 20);
+    }
+
+    #[test]
+    fn test_unversioned_file_is_migrated() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        std::fs::write(&path, FILE).unwrap();
+
+        let (config, migrated) = Config::read_file(&path).unwrap();
+        assert!(migrated);
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.input.prefer_backend, "x11");
+
+        // Loading rewrites the file in the current format
+        let config = Config::load_file(&path).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        let rewritten = std::fs::read_to_string(&path).unwrap();
+        assert!(rewritten.contains(&format!("version = {}", CONFIG_VERSION)));
+
+        let (config, migrated) = Config::read_file(&path).unwrap();
+        assert!(!migrated);
+        assert_eq!(config.input.typing_delay_ms, 20);
+    }
+
+    #[test]
+    fn test_newer_version_is_not_migrated() {
+        let mut table: toml::Table = toml::from_str("version = 99").unwrap();
+        assert!(!migrate(&mut table));
+        assert_eq!(table["version"].as_integer(), Some(99));
+    }
+
+    #[test]
+    fn test_validate_clamps_typing_delay() {
+        let mut config = Config::default();
+        config.input.typing_delay_ms = 100_000;
+        config.validate();
+        assert_eq!(config.input.typing_delay_ms, MAX_TYPING_DELAY_MS);
+
+        config.input.typing_delay_ms = 25;
+        config.validate();
+        assert_eq!(config.input.typing_delay_ms, 25);
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_backend() {
+        let mut config = Config::default();
+        config.input.prefer_backend = "foo".to_string();
+        config.validate();
+        assert_eq!(config.input.prefer_backend, "auto");
+
+        config.input.prefer_backend = " Wayland ".to_string();
+        config.validate();
+        assert_eq!(config.input.prefer_backend, "wayland");
+
+        config.input.prefer_backend = "ATSPI".to_string();
+        config.validate();
+        assert_eq!(config.input.prefer_backend, "atspi");
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_date_formats() {
+        let mut config = Config::default();
+        config.input.date_format = "%d.%m.%Y".to_string();
+        config.input.time_format = "%Q".to_string();
+        config.validate();
+        assert_eq!(config.input.date_format, "%d.%m.%Y");
+        assert_eq!(config.input.time_format, "%H:%M");
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_duplicate_line_keys() {
+        let mut config = Config::default();
+        config.input.duplicate_line_keys = vec!["ctrl+shift+d".to_string()];
+        config.validate();
+        assert_eq!(config.input.duplicate_line_keys, vec!["ctrl+shift+d"]);
+
+        config
+            .input
+            .duplicate_line_keys
+            .push("ctrl+nope".to_string());
+        config.validate();
+        assert!(config.input.duplicate_line_keys.is_empty());
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_focus_check() {
+        let mut config = Config::default();
+        assert_eq!(config.input.focus_check, "off");
+
+        config.input.focus_check = " Pause".to_string();
+        config.validate();
+        assert_eq!(config.input.focus_check, "pause");
+
+        config.input.focus_check = "notify".to_string();
+        config.validate();
+        assert_eq!(config.input.focus_check, "off");
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_pairing_prompt() {
+        let mut config = Config::default();
+        assert!(!config.notifications.prompts_with_notification());
+
+        config.notifications.pairing_prompt = "Notification ".to_string();
+        config.validate();
+        assert!(config.notifications.prompts_with_notification());
+
+        config.notifications.pairing_prompt = "popup".to_string();
+        config.validate();
+        assert_eq!(config.notifications.pairing_prompt, "dialog");
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_log_level() {
+        let mut config = Config::default();
+        config.logging.file_level = "verbose".to_string();
+        config.validate();
+        assert_eq!(config.logging.file_level, "debug");
+
+        config.logging.file_level = " TRACE".to_string();
+        config.validate();
+        assert_eq!(config.logging.file_level, "trace");
+    }
+
+    #[test]
+    fn test_validate_clamps_recording_timeout() {
+        let mut config = Config::default();
+        config.input.recording_timeout_secs = 0;
+        config.validate();
+        assert_eq!(config.input.recording_timeout_secs, 5);
+
+        config.input.recording_timeout_secs = 10_000;
+        config.validate();
+        assert_eq!(config.input.recording_timeout_secs, 300);
+
+        config.input.recording_timeout_secs = 45;
+        config.validate();
+        assert_eq!(config.input.recording_timeout_secs, 45);
+    }
+
+    #[test]
+    fn test_validate_clamps_power_on_attempts() {
+        let mut config = Config::default();
+        assert_eq!(config.bluetooth.power_on_attempts, 5);
+        config.bluetooth.power_on_attempts = 0;
+        config.validate();
+        assert_eq!(config.bluetooth.power_on_attempts, 1);
+
+        config.bluetooth.power_on_attempts = 100;
+        config.validate();
+        assert_eq!(config.bluetooth.power_on_attempts, 20);
+    }
+
+    #[test]
+    fn test_validate_clamps_flush_timeouts() {
+        let mut config = Config::default();
+        config.input.flush_interval_ms = 0;
+        config.input.look_ahead_ms = 0;
+        config.input.stale_timeout_ms = 60_000;
+        config.input.command_cooldown_ms = 60_000;
+        config.input.duplicate_text_window_ms = 60_000;
+        config.webhook.timeout_ms = 0;
+        config.validate();
+        assert_eq!(config.input.flush_interval_ms, 10);
+        assert_eq!(config.input.look_ahead_ms, 10);
+        assert_eq!(config.input.stale_timeout_ms, 10_000);
+        assert_eq!(config.input.command_cooldown_ms, 5_000);
+        assert_eq!(config.input.duplicate_text_window_ms, 10_000);
+        assert_eq!(config.webhook.timeout_ms, 100);
+    }
+
+    #[test]
+    fn test_shell_command_needs_enable_and_allowlist() {
+        let mut shell = ShellConfig::default();
+        shell
+            .allowed
+            .insert("build".to_string(), "make".to_string());
+        assert!(shell.command("build").is_err());
+
+        shell.enabled = true;
+        assert_eq!(shell.command("build").unwrap(), "make");
+        assert!(shell.command("make").is_err());
+    }
+
+    #[test]
+    fn test_app_launch_needs_enable_and_list() {
+        let mut apps: AppsConfig = toml::from_str(
+            r#"
+            [launch]
+            "open terminal" = "gnome-terminal"
+            "#,
+        )
+        .unwrap();
+        assert!(apps.target("open terminal").is_err());
+
+        apps.enabled = true;
+        assert_eq!(apps.target("open terminal").unwrap(), "gnome-terminal");
+        assert!(apps.target("open browser").is_err());
+    }
+
+    #[test]
+    fn test_serialized_config_round_trips() {
+        let mut config: Config = toml::from_str(FILE).unwrap();
+        config.notifications.enabled = false;
+        config.input.toggle_hotkey = "ctrl+alt+v".to_string();
+        config.webhook.url = "http://localhost:8080/hook".to_string();
+        config
+            .shell
+            .allowed
+            .insert("build".to_string(), "make -C ~/src".to_string());
+
+        let serialized = toml::to_string_pretty(&config).unwrap();
+        let parsed: Config = toml::from_str(&serialized).unwrap();
+
+        assert_eq!(parsed.version, config.version);
+        assert_eq!(parsed.headless, config.headless);
+        assert_eq!(parsed.bluetooth.auto_accept, config.bluetooth.auto_accept);
+        assert_eq!(parsed.input.typing_delay_ms, config.input.typing_delay_ms);
+        assert_eq!(parsed.input.prefer_backend, config.input.prefer_backend);
+        assert_eq!(
+            parsed.input.recording_timeout_secs,
+            config.input.recording_timeout_secs
+        );
+        assert_eq!(parsed.input.toggle_hotkey, config.input.toggle_hotkey);
+        assert_eq!(parsed.notifications.enabled, config.notifications.enabled);
+        assert_eq!(parsed.webhook.url, config.webhook.url);
+        assert_eq!(parsed.webhook.timeout_ms, config.webhook.timeout_ms);
+        assert_eq!(parsed.shell, config.shell);
+    }
+
+    #[test]
+    fn test_idle_timeout() {
+        let mut bluetooth = BluetoothConfig::default();
+        assert_eq!(bluetooth.idle_timeout(), None);
+
+        bluetooth.idle_timeout_mins = 15;
+        assert_eq!(bluetooth.idle_timeout(), Some(Duration::from_secs(900)));
+    }
+
+    #[test]
+    fn test_dialog_timeout() {
+        let mut bluetooth = BluetoothConfig::default();
+        assert_eq!(bluetooth.dialog_timeout(), Some(Duration::from_secs(60)));
+
+        bluetooth.dialog_timeout_secs = 0;
+        assert_eq!(bluetooth.dialog_timeout(), None);
+
+        let bluetooth: BluetoothConfig = toml::from_str("dialog_timeout_secs = 120").unwrap();
+        assert_eq!(bluetooth.dialog_timeout(), Some(Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn test_apply_reload_keeps_restart_settings() {
+        let mut config: Config = toml::from_str(FILE).unwrap();
+        let mut file = config.clone();
+        file.input.typing_delay_ms = 50;
+        file.bluetooth.auto_accept = false;
+        file.notifications.pairing_prompt = "notification".to_string();
+        file.input.prefer_backend = "wayland".to_string();
+
+        assert_eq!(
+            config.restart_required_changes(&file),
+            vec!["input.prefer_backend"]
+        );
+        config.apply_reload(&file);
+
+        assert_eq!(config.input.typing_delay_ms, 50);
+        assert!(!config.bluetooth.auto_accept);
+        assert!(config.notifications.prompts_with_notification());
+        assert_eq!(config.input.prefer_backend, "x11");
+    }
+
+    #[test]
+    fn test_reread_keeps_cli_overrides() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        std::fs::write(&path, FILE).unwrap();
+        let overrides = ConfigOverrides {
+            prefer_backend: Some("wayland".to_string()),
+            ..Default::default()
+        };
+
+        let mut config: Config = toml::from_str(FILE).unwrap();
+        config.apply_overrides(&overrides);
+        let file = Config::reread(&path, &overrides).unwrap();
+
+        // Only real edits need a restart
+        assert!(config.restart_required_changes(&file).is_empty());
+        assert_eq!(file.input.prefer_backend, "wayland");
+    }
+
+    #[test]
+    fn test_live_config_follows_file_edits() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        std::fs::write(&path, FILE).unwrap();
+
+        let config: Config = toml::from_str(FILE).unwrap();
+        let live = LiveConfig::new_with_watcher(config, &path, ConfigOverrides::default()).unwrap();
+        assert_eq!(live.read().input.typing_delay_ms, 20);
+
+        std::fs::write(
+            &path,
+            FILE.replace("typing_delay_ms = 20", "typing_delay_ms = 35"),
+        )
+        .unwrap();
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while live.read().input.typing_delay_ms != 35 && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert_eq!(live.read().input.typing_delay_ms, 35);
+    }
+
+    #[test]
+    fn test_app_dir() {
+        let base = Some(PathBuf::from("/home/user/.local/share"));
+        assert_eq!(
+            app_dir(None, base.clone()),
+            PathBuf::from("/home/user/.local/share/speech2prompt")
+        );
+        assert_eq!(
+            app_dir(Some(OsString::from("/tmp/s2p")), base.clone()),
+            PathBuf::from("/tmp/s2p")
+        );
+        // An empty variable is treated as unset
+        assert_eq!(
+            app_dir(Some(OsString::new()), base),
+            PathBuf::from("/home/user/.local/share/speech2prompt")
+        );
+        assert_eq!(app_dir(None, None), PathBuf::from("./speech2prompt"));
+    }
+
+    #[test]
+    fn test_empty_overrides_change_nothing() {
+        let mut config = Config::default();
+        let before = config.clone();
+        config.apply_overrides(&ConfigOverrides::default());
+
+        assert_eq!(config.bluetooth.device_name, before.bluetooth.device_name);
+        assert_eq!(config.input.prefer_backend, before.input.prefer_backend);
+        assert_eq!(config.data_dir, before.data_dir);
+        assert_eq!(config.headless, before.headless);
+    }
+}
@@ -0,0 +1,146 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Local control socket.
+//!
+//! Lets other programs on this machine use the desktop app as a dictation
+//! backend. Requests go into the same event channel as the BLE server, so
+//! they are handled by the `EventProcessor` like text from the phone.
+//!
+//! The protocol is line based. Each request is one line, answered with
+//! `OK` or `ERR <reason>`:
+//!
+//! ```text
+//! InjectText <text>
+//! ExecuteCommand <command>
+//! ```
+//!
+//! `<command>` is a command code such as `ENTER` or `COPY`. The socket is
+//! only accessible to the current user.
+
+use anyhow::{bail, Result};
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+use tokio::sync::mpsc;
+use tracing::debug;
+
+use crate::bluetooth::{CommandCode, ConnectionEvent};
+use crate::logging::redacted;
+use crate::socket::SocketServer;
+
+/// Socket file name.
+const SOCKET_NAME: &str = "speech2prompt.sock";
+
+/// Default socket path, in the user's runtime directory if there is one.
+pub fn default_socket_path(data_dir: &Path) -> PathBuf {
+    dirs::runtime_dir()
+        .unwrap_or_else(|| data_dir.to_path_buf())
+        .join(SOCKET_NAME)
+}
+
+/// Parse a request line into the event it stands for.
+pub fn parse_request(line: &str) -> Result<ConnectionEvent> {
+    let (method, arg) = line.split_once(' ').unwrap_or((line, ""));
+
+    match method {
+        "InjectText" => {
+            if arg.is_empty() {
+                bail!("InjectText needs text");
+            }
+            Ok(ConnectionEvent::TextReceived(arg.to_string()))
+        }
+        "ExecuteCommand" => {
+            let command = arg.trim();
+            if CommandCode::parse(command).is_none() {
+                bail!("unknown command '{}'", command);
+            }
+            Ok(ConnectionEvent::CommandReceived(command.to_string()))
+        }
+        _ => bail!("unknown request '{}'", method),
+    }
+}
+
+/// Running control socket.
+///
+/// Stops listening and removes the socket file when dropped.
+pub struct ControlServer {
+    _server: SocketServer,
+}
+
+impl ControlServer {
+    /// Listen on `path` and forward requests to `event_tx`.
+    ///
+    /// A stale socket file from an earlier run is replaced.
+    pub fn bind(path: &Path, event_tx: mpsc::Sender<ConnectionEvent>) -> Result<Self> {
+        let server = SocketServer::bind(path, "Control", move |stream| {
+            handle_client(stream, event_tx.clone())
+        })?;
+        Ok(Self { _server: server })
+    }
+}
+
+/// Answer requests from one client until it disconnects.
+async fn handle_client(stream: UnixStream, event_tx: mpsc::Sender<ConnectionEvent>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        debug!("Control request: {}", redacted(&line));
+        let reply = match parse_request(&line) {
+            Ok(event) => match event_tx.send(event).await {
+                Ok(()) => "OK".to_string(),
+                Err(_) => "ERR shutting down".to_string(),
+            },
+            Err(e) => format!("ERR {}", e),
+        };
+
+        if writer
+            .write_all(format!("{}\n", reply).as_bytes())
+            .await
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_inject_text() {
+        match parse_request("InjectText hello  world").unwrap() {
+            ConnectionEvent::TextReceived(text) => assert_eq!(text, "hello  world"),
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_execute_command() {
+        match parse_request("ExecuteCommand ENTER").unwrap() {
+            ConnectionEvent::CommandReceived(command) => assert_eq!(command, "ENTER"),
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_requests() {
+        assert!(parse_request("").is_err());
+        assert!(parse_request("InjectText").is_err());
+        assert!(parse_request("ExecuteCommand REBOOT").is_err());
+        assert!(parse_request("Shutdown now").is_err());
+    }
+}
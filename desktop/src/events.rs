@@ -14,37 +14,114 @@
 
 //! Event processing and message dispatch.
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use std::collections::{HashMap, VecDeque};
+use std::process::{ExitStatus, Stdio};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 
 use crate::bluetooth::{CommandCode, ConnectionEvent};
-use crate::commands::{CombinedMatcher, MatchResult, ProcessedItem, TextSegment, VoiceCommand, WordBuffer};
-use crate::input::InputInjector;
+use crate::commands::{
+    emoji_text, now_text, CombinedMatcher, MatchResult, MediaAction, MediaController, Playerctl,
+    ProcessedItem, TextSegment, VoiceCommand, WordBuffer, DEFAULT_DATE_FORMAT, DEFAULT_TIME_FORMAT,
+    EMOJI_CODE_PREFIX, LAUNCH_CODE_PREFIX,
+};
+use crate::config::{AppsConfig, ShellConfig};
+use crate::injection::InjectionQueue;
+use crate::input::{ActiveWindow, InputInjector, KeyCombo, WindowSource};
+use crate::logging::redacted;
+use crate::scripting::{self, ScriptAction, ScriptEngine};
 use crate::state::AppState;
 use crate::storage::VoiceCommandStore;
+use crate::ui::{Notification, Notifier};
+use crate::webhook::{Webhook, WebhookEvent};
+
+/// What to do when the focused window changes mid-dictation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusCheck {
+    /// Don't check.
+    Off,
+    /// Log and notify, then keep typing into the new window.
+    Warn,
+    /// Log, notify and pause until the user resumes.
+    Pause,
+}
+
+impl FocusCheck {
+    /// Parse the `input.focus_check` setting, `Off` for anything unknown.
+    pub fn from_config(value: &str) -> Self {
+        match value {
+            "warn" => Self::Warn,
+            "pause" => Self::Pause,
+            _ => Self::Off,
+        }
+    }
+}
 
 /// Process events from Bluetooth connections.
 pub struct EventProcessor {
-    injector: Box<dyn InputInjector>,
+    injection: InjectionQueue,
     input_enabled: bool,
     voice_command_store: Option<Arc<VoiceCommandStore>>,
     state: Option<Arc<AppState>>,
     matcher: Option<CombinedMatcher>,
     word_buffer: WordBuffer,
+    stale_timeout: Duration,
+    paused: bool,
+    paused_events: VecDeque<ConnectionEvent>,
+    /// Device ID of the phone in the current session.
+    connected_device: Option<String>,
+    scripts: ScriptEngine,
+    webhook: Option<Webhook>,
+    media: Box<dyn MediaController>,
+    shell: ShellConfig,
+    apps: AppsConfig,
+    /// strftime formats of "insert date" and "insert time".
+    date_format: String,
+    time_format: String,
+    /// Keys pressed by "duplicate line" instead of the default sequence.
+    duplicate_line_keys: Vec<KeyCombo>,
+    command_cooldown: Duration,
+    /// When each command last ran, for the cooldown.
+    last_commands: HashMap<String, Instant>,
+    /// Characters typed since the last command, erased by cancel typing.
+    typed_since_command: usize,
+    focus_check: FocusCheck,
+    windows: Arc<dyn WindowSource>,
+    /// Window dictation goes to, taken when the first text of a session is
+    /// typed.
+    focus_target: Option<String>,
+    notifier: Option<Box<dyn Notifier>>,
+    duplicate_text_window: Duration,
+    /// Last text received and when, to drop resends.
+    last_text: Option<(String, Instant)>,
+    /// Words received in recording mode, saved as one phrase when the
+    /// utterance ends.
+    recorded_phrase: Option<RecordedPhrase>,
+    /// Recognition session a phrase was just recorded from, whose remaining
+    /// words are dropped.
+    recorded_session: Option<String>,
+}
+
+/// Words of a phrase being recorded.
+struct RecordedPhrase {
+    words: Vec<String>,
+    session: String,
+    /// When the last word arrived.
+    updated: Instant,
 }
 
+/// Default age after which out-of-order words are flushed.
+pub const STALE_WORD_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Maximum number of text, word and command events kept while paused.
+pub const PAUSE_BUFFER_CAP: usize = 1000;
+
 impl EventProcessor {
     /// Create a new event processor.
     pub fn new(injector: Box<dyn InputInjector>) -> Self {
-        Self {
-            injector,
-            input_enabled: true,
-            voice_command_store: None,
-            state: None,
-            matcher: None,
-            word_buffer: WordBuffer::new(),
-        }
+        Self::from_parts(injector, None, None)
     }
 
     /// Create a new event processor with voice command support.
@@ -53,15 +130,156 @@ impl EventProcessor {
         voice_command_store: Arc<VoiceCommandStore>,
         state: Arc<AppState>,
     ) -> Self {
-        let matcher = CombinedMatcher::new(voice_command_store.clone());
+        Self::from_parts(injector, Some(voice_command_store), Some(state))
+    }
+
+    /// Create an event processor from any injector, e.g. a recording one in
+    /// tests.
+    ///
+    /// Phrases are only matched with a `voice_command_store`; `state` gets
+    /// the statistics and recording mode.
+    pub fn from_parts(
+        injector: Box<dyn InputInjector>,
+        voice_command_store: Option<Arc<VoiceCommandStore>>,
+        state: Option<Arc<AppState>>,
+    ) -> Self {
+        let matcher = voice_command_store.clone().map(CombinedMatcher::new);
         Self {
-            injector,
+            injection: InjectionQueue::new(injector, state.clone()),
             input_enabled: true,
-            voice_command_store: Some(voice_command_store),
-            state: Some(state),
-            matcher: Some(matcher),
+            voice_command_store,
+            state,
+            matcher,
             word_buffer: WordBuffer::new(),
+            stale_timeout: STALE_WORD_TIMEOUT,
+            paused: false,
+            paused_events: VecDeque::new(),
+            connected_device: None,
+            scripts: ScriptEngine::new(),
+            webhook: None,
+            media: Box::new(Playerctl),
+            shell: ShellConfig::default(),
+            apps: AppsConfig::default(),
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            time_format: DEFAULT_TIME_FORMAT.to_string(),
+            duplicate_line_keys: Vec::new(),
+            command_cooldown: Duration::ZERO,
+            last_commands: HashMap::new(),
+            typed_since_command: 0,
+            focus_check: FocusCheck::Off,
+            windows: Arc::new(ActiveWindow),
+            focus_target: None,
+            notifier: None,
+            duplicate_text_window: Duration::ZERO,
+            last_text: None,
+            recorded_phrase: None,
+            recorded_session: None,
+        }
+    }
+
+    /// Set the look-ahead and stale-word timeouts used by the periodic flush.
+    pub fn with_timeouts(mut self, look_ahead: Duration, stale: Duration) -> Self {
+        self.word_buffer = std::mem::take(&mut self.word_buffer).with_look_ahead_timeout(look_ahead);
+        self.stale_timeout = stale;
+        self
+    }
+
+    /// Hold back words that could start a 2-word command (the default), or
+    /// type every word as it arrives.
+    pub fn with_look_ahead(mut self, enabled: bool) -> Self {
+        self.word_buffer = std::mem::take(&mut self.word_buffer).with_look_ahead(enabled);
+        self
+    }
+
+    /// Report processed text and commands to a webhook.
+    pub fn with_webhook(mut self, webhook: Webhook) -> Self {
+        self.webhook = Some(webhook);
+        self
+    }
+
+    /// Allow shell commands from the `[shell]` config section.
+    pub fn with_shell_commands(mut self, shell: ShellConfig) -> Self {
+        self.shell = shell;
+        self
+    }
+
+    /// Launch applications by the phrases of the `[apps]` config section.
+    ///
+    /// The phrases only match while launching is enabled.
+    pub fn with_apps(mut self, apps: AppsConfig) -> Self {
+        if apps.enabled {
+            self.matcher = self
+                .matcher
+                .take()
+                .map(|matcher| matcher.with_app_phrases(apps.launch.keys()));
         }
+        self.apps = apps;
+        self
+    }
+
+    /// Type dates and times in these strftime formats, which must be valid.
+    pub fn with_date_formats(mut self, date: &str, time: &str) -> Self {
+        self.date_format = date.to_string();
+        self.time_format = time.to_string();
+        self
+    }
+
+    /// Press `keys` for "duplicate line", e.g. an editor's own shortcut.
+    ///
+    /// Keys that don't parse are skipped; none keeps the default sequence.
+    pub fn with_duplicate_line_keys(mut self, keys: &[String]) -> Self {
+        self.duplicate_line_keys = keys.iter().filter_map(|k| KeyCombo::parse(k)).collect();
+        self
+    }
+
+    /// Ignore a command repeated within `cooldown` of its last run.
+    pub fn with_command_cooldown(mut self, cooldown: Duration) -> Self {
+        self.command_cooldown = cooldown;
+        self
+    }
+
+    /// Drop a text identical to the previous one received within `window`.
+    ///
+    /// The phone resends a text when its ACK doesn't arrive in time, which
+    /// would otherwise type it twice. Words aren't affected, so a word said
+    /// twice is still typed twice.
+    pub fn with_duplicate_text_window(mut self, window: Duration) -> Self {
+        self.duplicate_text_window = window;
+        self
+    }
+
+    /// Type at most `max_chars_per_sec` characters per second, 0 for no limit.
+    ///
+    /// Text over the limit is held back and typed at the allowed rate.
+    pub fn with_rate_limit(self, max_chars_per_sec: u32) -> Self {
+        self.injection.set_rate_limit(max_chars_per_sec);
+        self
+    }
+
+    /// Watch for the focused window changing mid-dictation, as told by
+    /// `windows`.
+    pub fn with_focus_check(mut self, check: FocusCheck, windows: Arc<dyn WindowSource>) -> Self {
+        self.focus_check = check;
+        self.windows = windows;
+        self
+    }
+
+    /// Show desktop notifications, e.g. when the focus changes.
+    pub fn with_notifier(mut self, notifier: Box<dyn Notifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Send media commands to `controller` instead of `playerctl`.
+    #[allow(dead_code)]
+    pub fn with_media_controller(mut self, controller: Box<dyn MediaController>) -> Self {
+        self.media = controller;
+        self
+    }
+
+    /// Age after which out-of-order words are flushed.
+    pub fn stale_timeout(&self) -> Duration {
+        self.stale_timeout
     }
 
     /// Enable or disable input injection.
@@ -70,33 +288,119 @@ impl EventProcessor {
         info!("Input injection {}", if enabled { "enabled" } else { "disabled" });
     }
 
+    /// Pause or resume processing.
+    ///
+    /// While paused, received text, words and commands are kept instead of
+    /// discarded (up to `PAUSE_BUFFER_CAP`, later ones are dropped) and
+    /// processed in order on resume.
+    pub async fn set_paused(&mut self, paused: bool) -> Result<()> {
+        if paused == self.paused {
+            return Ok(());
+        }
+        self.paused = paused;
+
+        if paused {
+            info!("Processing paused");
+            return Ok(());
+        }
+
+        // Whatever has the focus now is where the user wants the text
+        self.focus_target = None;
+        info!("Processing resumed, replaying {} events", self.paused_events.len());
+        while let Some(event) = self.paused_events.pop_front() {
+            self.dispatch_event(event).await?;
+            // The focus moved again, the event waits for the next resume
+            if self.paused {
+                info!("Paused again, {} events left", self.paused_events.len());
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set the delay between typed keystrokes.
+    pub fn set_typing_delay(&mut self, delay_ms: u32) {
+        self.injection
+            .set_typing_delay(Duration::from_millis(u64::from(delay_ms)));
+    }
+
+    /// Whether `event` reports the connection of the current session again.
+    ///
+    /// BlueZ may report the same connection more than once. A pairing
+    /// request starts a new session, so the same phone connecting after one
+    /// is not a duplicate.
+    pub fn is_duplicate_connect(&self, event: &ConnectionEvent) -> bool {
+        match event {
+            ConnectionEvent::Connected { device_id, .. } => {
+                self.connected_device.as_deref() == Some(device_id.as_str())
+            }
+            _ => false,
+        }
+    }
+
     /// Process a single event.
     pub async fn process_event(&mut self, event: ConnectionEvent) -> Result<()> {
+        // Resetting again would drop a word held back mid-stream
+        if self.is_duplicate_connect(&event) {
+            debug!("Ignoring duplicate connect");
+            return Ok(());
+        }
+        if self.paused
+            && matches!(
+                event,
+                ConnectionEvent::TextReceived(_)
+                    | ConnectionEvent::WordReceived { .. }
+                    | ConnectionEvent::CommandReceived(_)
+            )
+        {
+            if self.paused_events.len() < PAUSE_BUFFER_CAP {
+                self.paused_events.push_back(event);
+            } else {
+                warn!("Pause buffer full, dropping {:?}", event);
+            }
+            return Ok(());
+        }
+
+        self.dispatch_event(event).await
+    }
+
+    /// Process an event that isn't held back by pausing.
+    async fn dispatch_event(&mut self, event: ConnectionEvent) -> Result<()> {
         match event {
             ConnectionEvent::TextReceived(text) => {
                 self.handle_text(&text).await?;
             }
-            ConnectionEvent::WordReceived { word, seq, session } => {
-                self.handle_word(&word, seq, &session).await?;
+            ConnectionEvent::WordReceived { word, seq, session, eou } => {
+                self.handle_word(&word, seq, &session, eou).await?;
             }
             ConnectionEvent::CommandReceived(cmd) => {
                 self.handle_command(&cmd).await?;
             }
-            ConnectionEvent::Connected { device_name } => {
+            ConnectionEvent::Connected { device_id, device_name } => {
                 info!("Device connected: {}", device_name);
+                self.connected_device = Some(device_id);
                 // Reset word buffer state for the new connection to prevent
                 // stale session/sequence state from blocking words
                 self.word_buffer.reset();
+                self.recorded_session = None;
+                self.focus_target = None;
                 info!("Word buffer reset for new connection");
             }
             ConnectionEvent::Disconnected => {
                 info!("Device disconnected");
+                self.connected_device = None;
+                self.focus_target = None;
             }
-            ConnectionEvent::PairRequested { device_id, device_name } => {
+            ConnectionEvent::PairRequested { device_id, device_name, .. } => {
                 info!("Pairing requested by: {} ({})", 
                       device_name.as_deref().unwrap_or("Unknown"), 
                       device_id);
-                // Handled by main event loop
+                // Handled by main event loop. It starts a new session, even
+                // with the same phone.
+                self.connected_device = None;
+            }
+            ConnectionEvent::Battery(_) => {
+                // Shown in the tray by the main event loop
             }
             ConnectionEvent::Error(e) => {
                 error!("Connection error: {}", e);
@@ -105,24 +409,306 @@ impl EventProcessor {
         Ok(())
     }
 
+    /// Wait until all queued text and commands have been injected.
+    pub async fn wait_for_injection(&self) {
+        self.injection.wait_idle().await;
+    }
+
+    /// Finish processing before the app exits.
+    ///
+    /// Types the word held back for look-ahead and waits until everything
+    /// queued is injected. Events kept while paused are dropped, typing them
+    /// while the app quits would surprise the user.
+    pub async fn shutdown(&mut self) -> Result<()> {
+        if !self.paused_events.is_empty() {
+            info!("Dropping {} events received while paused", self.paused_events.len());
+            self.paused_events.clear();
+        }
+
+        self.flush_now().await?;
+        self.wait_for_injection().await;
+        Ok(())
+    }
+
+    /// Process the word held back for look-ahead right away.
+    ///
+    /// For callers that know an utterance ended, so it doesn't wait for the
+    /// look-ahead timeout. A possible two-word command start is typed as
+    /// text unless it is a command on its own.
+    pub async fn flush_now(&mut self) -> Result<()> {
+        self.save_recorded_phrase(true);
+
+        let matcher = self.matcher.as_ref();
+        let single_word_matcher = |w: &str| -> Option<String> {
+            matcher.and_then(|m| m.match_single_word(w))
+        };
+        let items = self.word_buffer.flush_all(&single_word_matcher);
+        for item in items {
+            self.process_item(item).await?;
+        }
+        Ok(())
+    }
+
+    /// Queue text for typing.
+    async fn type_text(&mut self, text: String) {
+        if let Some(webhook) = &self.webhook {
+            webhook.send(WebhookEvent::text(&text));
+        }
+        self.typed_since_command += text.chars().count();
+        self.injection.type_text(text).await;
+    }
+
+    /// Check whether `text` repeats the previous text within the duplicate
+    /// window, otherwise note it as the previous text.
+    fn is_duplicate_text(&mut self, text: &str) -> bool {
+        if self.duplicate_text_window.is_zero() {
+            return false;
+        }
+        let now = Instant::now();
+        if let Some((last, at)) = &self.last_text {
+            if last == text && now.duration_since(*at) < self.duplicate_text_window {
+                return true;
+            }
+        }
+        self.last_text = Some((text.to_string(), now));
+        false
+    }
+
+    /// Check that the focused window is still the one dictation started in.
+    ///
+    /// On a change, warns and adopts the new window, or pauses with
+    /// [`FocusCheck::Pause`]. Returns false when paused.
+    async fn check_focus(&mut self) -> bool {
+        if self.focus_check == FocusCheck::Off {
+            return true;
+        }
+
+        let windows = self.windows.clone();
+        let Ok(Some(current)) = tokio::task::spawn_blocking(move || windows.active_window()).await
+        else {
+            // Nothing to compare, e.g. on Wayland
+            return true;
+        };
+        let Some(previous) = self.focus_target.replace(current.clone()) else {
+            debug!("Dictating into '{}'", current);
+            return true;
+        };
+        if previous == current {
+            return true;
+        }
+
+        let pause = self.focus_check == FocusCheck::Pause;
+        warn!(
+            "Focus moved from '{}' to '{}' mid-dictation",
+            previous, current
+        );
+        let notification = Notification {
+            summary: if pause {
+                "Dictation paused".to_string()
+            } else {
+                "Focus changed".to_string()
+            },
+            body: format!("Text now goes to \"{}\".", current),
+        };
+        if let Some(notifier) = &self.notifier {
+            if let Err(e) = notifier.show(&notification) {
+                warn!("{}", e);
+            }
+        }
+
+        if pause {
+            info!("Processing paused until resumed");
+            self.paused = true;
+            if let Some(state) = &self.state {
+                state.set_paused(true);
+            }
+        }
+        !pause
+    }
+
+    /// Queue a command, running script commands here.
+    async fn run_command(&mut self, command: VoiceCommand) {
+        if self.in_cooldown(&command) {
+            info!("Ignoring {:?} repeated within the cooldown", command);
+            return;
+        }
+        if let Some(webhook) = &self.webhook {
+            webhook.send(WebhookEvent::command(&command));
+        }
+        // Every command starts a new stretch of text for cancel typing
+        let typed = std::mem::take(&mut self.typed_since_command);
+        match command {
+            VoiceCommand::CancelTyping => {
+                debug!("Erasing {} typed characters", typed);
+                self.injection.execute(VoiceCommand::Backspace(typed)).await
+            }
+            VoiceCommand::Emoji(name) => self.type_text(emoji_text(&name)).await,
+            VoiceCommand::InsertDate => self.type_text(now_text(&self.date_format)).await,
+            VoiceCommand::InsertTime => self.type_text(now_text(&self.time_format)).await,
+            VoiceCommand::DuplicateLine if !self.duplicate_line_keys.is_empty() => {
+                for combo in self.duplicate_line_keys.clone() {
+                    self.injection.execute(VoiceCommand::KeyCombo(combo)).await;
+                }
+            }
+            VoiceCommand::Script(code) => self.run_script(&code).await,
+            VoiceCommand::Media(action) => self.run_media(action),
+            VoiceCommand::Shell(code) => self.run_shell(&code),
+            VoiceCommand::Launch(phrase) => self.run_launch(&phrase),
+            command => self.injection.execute(command).await,
+        }
+    }
+
+    /// Check whether `command` ran within the cooldown, otherwise note it ran now.
+    fn in_cooldown(&mut self, command: &VoiceCommand) -> bool {
+        if self.command_cooldown.is_zero() {
+            return false;
+        }
+        // User-defined commands share a kind, tell them apart by code
+        let key = match command {
+            VoiceCommand::KeyCombo(combo) => combo.to_string(),
+            VoiceCommand::Script(code) | VoiceCommand::Shell(code) => code.clone(),
+            VoiceCommand::Emoji(name) => format!("{}{}", EMOJI_CODE_PREFIX, name),
+            VoiceCommand::Launch(phrase) => format!("{}{}", LAUNCH_CODE_PREFIX, phrase),
+            command => command.as_str().to_string(),
+        };
+        let now = Instant::now();
+        if let Some(last) = self.last_commands.get(&key) {
+            if now.duration_since(*last) < self.command_cooldown {
+                return true;
+            }
+        }
+        self.last_commands.insert(key, now);
+        false
+    }
+
+    /// Send a media command to the player.
+    fn run_media(&self, action: MediaAction) {
+        match self.media.send(action) {
+            Ok(()) => {
+                if let Some(state) = &self.state {
+                    state.record_command();
+                }
+            }
+            Err(e) => error!("Media command {:?} failed: {:#}", action, e),
+        }
+    }
+
+    /// Run the allowlisted shell command of a shell command mapping.
+    ///
+    /// The command line comes from config.toml only; the mapping just names
+    /// an allowlist entry. Runs in the background, killed after the timeout.
+    fn run_shell(&self, code: &str) {
+        let Some(name) = self
+            .voice_command_store
+            .as_ref()
+            .and_then(|store| store.get_shell(code))
+        else {
+            warn!("No shell command for '{}'", code);
+            return;
+        };
+
+        let command = match self.shell.command(&name) {
+            Ok(command) => command.to_string(),
+            Err(e) => {
+                warn!("Not running shell command for '{}': {:#}", code, e);
+                return;
+            }
+        };
+
+        info!("Running shell command '{}' for '{}'", name, code);
+        let timeout = Duration::from_secs(self.shell.timeout_secs);
+        tokio::task::spawn_blocking(move || match run_shell_command(&command, timeout) {
+            Ok(status) if status.success() => debug!("Shell command '{}' finished", name),
+            Ok(status) => warn!("Shell command '{}' exited with {}", name, status),
+            Err(e) => warn!("Shell command '{}' failed: {:#}", name, e),
+        });
+
+        if let Some(state) = &self.state {
+            state.record_command();
+        }
+    }
+
+    /// Launch the application configured for `phrase`.
+    ///
+    /// The target comes from config.toml only. The application is left
+    /// running; it is only waited for so it doesn't linger as a zombie.
+    fn run_launch(&self, phrase: &str) {
+        let target = match self.apps.target(phrase) {
+            Ok(target) => target,
+            Err(e) => {
+                warn!("Not launching '{}': {:#}", phrase, e);
+                return;
+            }
+        };
+
+        info!("Launching '{}' for '{}'", target, phrase);
+        match launch_command(target).spawn() {
+            Ok(mut child) => {
+                tokio::task::spawn_blocking(move || child.wait());
+                if let Some(state) = &self.state {
+                    state.record_command();
+                }
+            }
+            Err(e) => warn!("Failed to launch '{}': {}", target, e),
+        }
+    }
+
+    /// Run a script command and queue the actions it returns.
+    async fn run_script(&mut self, code: &str) {
+        let Some((phrase, path)) = self
+            .voice_command_store
+            .as_ref()
+            .and_then(|store| store.get_script(code))
+        else {
+            warn!("No script for command '{}'", code);
+            return;
+        };
+
+        let actions = match scripting::run_file(&self.scripts, &path, &phrase) {
+            Ok(actions) => actions,
+            Err(e) => {
+                error!("Script for '{}' failed: {:#}", code, e);
+                return;
+            }
+        };
+
+        debug!("Script for '{}' returned {} actions", code, actions.len());
+        for action in actions {
+            match action {
+                ScriptAction::Text(text) => self.type_text(text).await,
+                ScriptAction::Keys(combo) => {
+                    self.injection.execute(VoiceCommand::KeyCombo(combo)).await
+                }
+            }
+        }
+    }
+
     /// Handle received text.
     async fn handle_text(&mut self, text: &str) -> Result<()> {
         info!("Processing text: {} chars", text.len());
 
-        // Check if we're in recording mode
-        if let (Some(state), Some(store)) = (&self.state, &self.voice_command_store) {
-            if let Some(command) = state.get_recording_command() {
-                // We're recording - save this text as the phrase for the command
-                info!("Recording phrase '{}' for command '{}'", text, command);
-                if let Err(e) = store.set_phrase(&command, text) {
-                    error!("Failed to save phrase: {}", e);
-                } else {
-                    info!("Successfully saved phrase '{}' for command '{}'", text, command);
-                }
-                // Stop recording mode
-                state.stop_recording();
-                return Ok(());
-            }
+        // Nothing to type, and not a phrase worth recording
+        if text.trim().is_empty() {
+            debug!("Ignoring empty text");
+            return Ok(());
+        }
+
+        if self.is_duplicate_text(text) {
+            info!("Ignoring text repeated within {:?}", self.duplicate_text_window);
+            return Ok(());
+        }
+
+        // In recording mode, save this text as the phrase for the command
+        if self.record_phrase(text) {
+            return Ok(());
+        }
+
+        if self.input_enabled && !self.check_focus().await {
+            // Typed into the window focused on resume, where it isn't a repeat
+            self.last_text = None;
+            self.paused_events
+                .push_front(ConnectionEvent::TextReceived(text.to_string()));
+            return Ok(());
         }
 
         // Check if this text matches a voice command phrase (with context support)
@@ -130,11 +716,9 @@ impl EventProcessor {
             match matcher.match_with_context(text) {
                 MatchResult::ExactCommand(voice_cmd) => {
                     // Entire text is a command
-                    info!("Text '{}' matched voice command: {:?}", text, voice_cmd);
+                    info!("Text '{}' matched voice command: {:?}", redacted(text), voice_cmd);
                     if self.input_enabled {
-                        if let Err(e) = crate::commands::execute(&voice_cmd, self.injector.as_ref()) {
-                            error!("Failed to execute voice command: {}", e);
-                        }
+                        self.run_command(voice_cmd).await;
                     } else {
                         debug!("Input disabled, ignoring voice command");
                     }
@@ -148,15 +732,11 @@ impl EventProcessor {
                             match segment {
                                 TextSegment::Text(text_part) => {
                                     debug!("Typing text segment: {} chars", text_part.len());
-                                    if let Err(e) = self.injector.type_text(&text_part) {
-                                        error!("Failed to inject text segment: {}", e);
-                                    }
+                                    self.type_text(text_part).await;
                                 }
                                 TextSegment::Command(cmd) => {
                                     debug!("Executing command segment: {:?}", cmd);
-                                    if let Err(e) = crate::commands::execute(&cmd, self.injector.as_ref()) {
-                                        error!("Failed to execute command segment: {}", e);
-                                    }
+                                    self.run_command(cmd).await;
                                 }
                             }
                         }
@@ -175,13 +755,9 @@ impl EventProcessor {
         // Inject text if enabled (no command match)
         if self.input_enabled {
             info!("Injecting text into active window: {} chars", text.len());
-            if let Err(e) = self.injector.type_text(text) {
-                error!("Failed to inject text: {}", e);
-            } else {
-                info!("Text injection successful");
-            }
+            self.type_text(text.to_string()).await;
         } else {
-            debug!("Input disabled, ignoring text: {}", text);
+            debug!("Input disabled, ignoring text: {}", redacted(text));
         }
 
         Ok(())
@@ -191,21 +767,46 @@ impl EventProcessor {
     async fn handle_command(&mut self, cmd: &str) -> Result<()> {
         debug!("Processing command: {}", cmd);
 
+        if self.input_enabled && !self.check_focus().await {
+            // Run in the window focused on resume
+            self.paused_events
+                .push_front(ConnectionEvent::CommandReceived(cmd.to_string()));
+            return Ok(());
+        }
+
         // Parse and execute command
         if let Some(command) = CommandCode::parse(cmd) {
             let voice_cmd = match command {
                 CommandCode::Enter => VoiceCommand::Enter,
+                CommandCode::NewParagraph => VoiceCommand::NewParagraph,
                 CommandCode::SelectAll => VoiceCommand::SelectAll,
+                CommandCode::ClearField => VoiceCommand::ClearField,
+                CommandCode::SelectWord => VoiceCommand::SelectWord,
+                CommandCode::SelectLine => VoiceCommand::SelectLine,
                 CommandCode::Copy => VoiceCommand::Copy,
                 CommandCode::Paste => VoiceCommand::Paste,
                 CommandCode::Cut => VoiceCommand::Cut,
+                CommandCode::Indent => VoiceCommand::Indent,
+                CommandCode::Dedent => VoiceCommand::Dedent,
+                CommandCode::CapsLock => VoiceCommand::CapsLock,
+                CommandCode::DuplicateLine => VoiceCommand::DuplicateLine,
+                CommandCode::Click => VoiceCommand::Click,
+                CommandCode::RightClick => VoiceCommand::RightClick,
+                CommandCode::DoubleClick => VoiceCommand::DoubleClick,
+                CommandCode::ScrollUp => VoiceCommand::ScrollUp,
+                CommandCode::ScrollDown => VoiceCommand::ScrollDown,
+                CommandCode::InsertDate => VoiceCommand::InsertDate,
+                CommandCode::InsertTime => VoiceCommand::InsertTime,
                 CommandCode::Cancel => VoiceCommand::Cancel,
+                CommandCode::CancelTyping => VoiceCommand::CancelTyping,
+                CommandCode::Play => VoiceCommand::Media(MediaAction::Play),
+                CommandCode::Pause => VoiceCommand::Media(MediaAction::Pause),
+                CommandCode::NextTrack => VoiceCommand::Media(MediaAction::NextTrack),
+                CommandCode::PreviousTrack => VoiceCommand::Media(MediaAction::PreviousTrack),
             };
 
             if self.input_enabled {
-                if let Err(e) = crate::commands::execute(&voice_cmd, self.injector.as_ref()) {
-                    error!("Failed to execute command: {}", e);
-                }
+                self.run_command(voice_cmd).await;
             } else {
                 debug!("Input disabled, ignoring command");
             }
@@ -217,8 +818,71 @@ impl EventProcessor {
     }
 
     /// Handle received word (from word-by-word streaming).
-    async fn handle_word(&mut self, word: &str, seq: Option<u64>, session: &str) -> Result<()> {
-        info!("Processing word: '{}' seq={:?} session={}", word, seq, session);
+    async fn handle_word(
+        &mut self,
+        word: &str,
+        seq: Option<u64>,
+        session: &str,
+        eou: bool,
+    ) -> Result<()> {
+        info!("Processing word: '{}' seq={:?} session={} eou={}", redacted(word), seq, session, eou);
+
+        // The rest of an utterance a phrase was recorded from isn't typed,
+        // unless recording starts again
+        if let Some(recorded) = self.recorded_session.take() {
+            let recording = self.state.as_ref().is_some_and(|s| s.is_recording());
+            if recorded == session && !recording {
+                debug!("Ignoring word after the recorded phrase");
+                if !eou {
+                    self.recorded_session = Some(recorded);
+                }
+                return Ok(());
+            }
+        }
+
+        // An utterance switching sessions has ended
+        if self
+            .recorded_phrase
+            .as_ref()
+            .is_some_and(|p| p.session != session)
+        {
+            self.save_recorded_phrase(false);
+        }
+
+        // In recording mode, collect the whole utterance as the phrase, as
+        // the words of a 2-word phrase arrive one by one
+        if self.state.as_ref().is_some_and(|s| s.is_recording()) {
+            let phrase = self.recorded_phrase.get_or_insert_with(|| RecordedPhrase {
+                words: Vec::new(),
+                session: session.to_string(),
+                updated: Instant::now(),
+            });
+            if !word.trim().is_empty() {
+                phrase.words.push(word.trim().to_string());
+                phrase.updated = Instant::now();
+            }
+            if eou {
+                self.save_recorded_phrase(true);
+            }
+            return Ok(());
+        }
+
+        // An empty word would be typed as a lone space, it can only end an utterance
+        if word.trim().is_empty() && !eou {
+            debug!("Ignoring empty word");
+            return Ok(());
+        }
+
+        if self.input_enabled && !self.check_focus().await {
+            // Typed into the window focused on resume
+            self.paused_events.push_front(ConnectionEvent::WordReceived {
+                word: word.to_string(),
+                seq,
+                session: session.to_string(),
+                eou,
+            });
+            return Ok(());
+        }
 
         // Create closures for the matcher functions
         let matcher = self.matcher.as_ref();
@@ -236,56 +900,95 @@ impl EventProcessor {
         };
 
         // Process through buffer (seq is ignored, kept for logging only)
-        let items = self.word_buffer.process_word(
-            word.to_string(),
-            session,
-            &single_word_matcher,
-            &two_word_matcher,
-            &could_start,
-        );
+        let mut items = if word.trim().is_empty() {
+            Vec::new()
+        } else {
+            self.word_buffer.process_word(
+                word.to_string(),
+                session,
+                &single_word_matcher,
+                &two_word_matcher,
+                &could_start,
+            )
+        };
+
+        // No second word will follow the end of an utterance, so don't wait
+        // for the look-ahead timeout
+        if eou {
+            items.extend(self.word_buffer.flush_all(&single_word_matcher));
+        }
 
         // Process each item
         for item in items {
             self.process_item(item).await?;
         }
 
+        // The next utterance is typed again
+        if eou {
+            self.recorded_session = None;
+        }
+
         Ok(())
     }
 
+    /// Save the words collected in recording mode as the phrase.
+    ///
+    /// Unless the utterance `ended`, its remaining words are dropped.
+    fn save_recorded_phrase(&mut self, ended: bool) {
+        let Some(phrase) = self.recorded_phrase.take() else {
+            return;
+        };
+        if phrase.words.is_empty() {
+            return;
+        }
+        if !self.record_phrase(&phrase.words.join(" ")) {
+            debug!("Recording stopped, dropping {} words", phrase.words.len());
+        } else if !ended {
+            self.recorded_session = Some(phrase.session);
+        }
+    }
+
+    /// Save `phrase` for the command being recorded, if in recording mode.
+    ///
+    /// Only the first phrase is saved, as recording stops with it.
+    fn record_phrase(&self, phrase: &str) -> bool {
+        let (Some(state), Some(store)) = (&self.state, &self.voice_command_store) else {
+            return false;
+        };
+        state
+            .finish_recording(|command| {
+                info!("Recording phrase '{}' for command '{}'", redacted(phrase), command);
+                match store.set_phrase(command, phrase) {
+                    Ok(()) => info!(
+                        "Successfully saved phrase '{}' for command '{}'",
+                        redacted(phrase),
+                        command
+                    ),
+                    Err(e) => error!("Failed to save phrase: {}", e),
+                }
+            })
+            .is_some()
+    }
+
     /// Process a single item from the word buffer.
     async fn process_item(&mut self, item: ProcessedItem) -> Result<()> {
         match item {
             ProcessedItem::Text(text) => {
-                // Check recording mode
-                if let (Some(state), Some(store)) = (&self.state, &self.voice_command_store) {
-                    if let Some(command) = state.get_recording_command() {
-                        info!("Recording word '{}' for command '{}'", text.trim(), command);
-                        if let Err(e) = store.set_phrase(&command, text.trim()) {
-                            error!("Failed to save phrase: {}", e);
-                        }
-                        state.stop_recording();
-                        return Ok(());
-                    }
-                }
-
                 // Type the text (includes trailing space)
                 if self.input_enabled {
-                    if let Err(e) = self.injector.type_text(&text) {
-                        error!("Failed to inject text: {}", e);
-                    } else {
-                        info!("Word delivered: '{}' -> typed", text.trim());
-                    }
+                    debug!("Queueing word '{}'", redacted(text.trim()));
+                    self.type_text(text).await;
                 }
             }
             ProcessedItem::Command(cmd_code) => {
                 info!("Executing command from word buffer: {}", cmd_code);
                 if self.input_enabled {
-                    if let Some(cmd) = VoiceCommand::parse(&cmd_code) {
-                        if let Err(e) = crate::commands::execute(&cmd, self.injector.as_ref()) {
-                            error!("Failed to execute command: {}", e);
-                        } else {
-                            info!("Command delivered: {} -> executed", cmd_code);
-                        }
+                    let cmd = match &self.matcher {
+                        Some(matcher) => matcher.resolve_command(&cmd_code),
+                        None => VoiceCommand::parse(&cmd_code),
+                    };
+                    if let Some(cmd) = cmd {
+                        self.run_command(cmd).await;
                     }
                 }
             }
@@ -305,21 +1008,31 @@ impl EventProcessor {
 
     /// Flush stale out-of-order words that have been waiting too long.
     /// Call this periodically to handle lost packets.
-    pub fn flush_stale_words(&mut self, max_age: std::time::Duration) -> Vec<ProcessedItem> {
+    pub fn flush_stale_words(&mut self, max_age: Duration) -> Vec<ProcessedItem> {
         self.word_buffer.flush_stale(max_age)
     }
 
     /// Process all pending flushes and return items ready for processing.
     /// This combines flush_pending and flush_stale for convenience.
     pub async fn process_periodic_flush(&mut self) -> Result<()> {
-        // Flush look-ahead pending words (100ms timeout is built into the buffer)
+        // Save a recorded phrase once no further word came within the look-ahead
+        let look_ahead = self.word_buffer.look_ahead_timeout();
+        if self
+            .recorded_phrase
+            .as_ref()
+            .is_some_and(|p| p.updated.elapsed() >= look_ahead)
+        {
+            self.save_recorded_phrase(false);
+        }
+
+        // Flush look-ahead pending words (the timeout is configured on the buffer)
         let pending_items = self.flush_pending_words();
         for item in pending_items {
             self.process_item(item).await?;
         }
 
-        // Flush stale out-of-order words (500ms is a reasonable timeout for BLE)
-        let stale_items = self.flush_stale_words(std::time::Duration::from_millis(500));
+        // Flush stale out-of-order words
+        let stale_items = self.flush_stale_words(self.stale_timeout);
         if !stale_items.is_empty() {
             info!("Flushing {} stale out-of-order words (likely lost packets)", stale_items.len());
         }
@@ -330,3 +1043,1178 @@ impl EventProcessor {
         Ok(())
     }
 }
+
+/// Command launching `target`.
+///
+/// URLs and paths are opened with `xdg-open`, anything else is run as a
+/// program with whitespace-separated arguments, without a shell.
+fn launch_command(target: &str) -> std::process::Command {
+    let target = target.trim();
+    let mut command = if target.contains("://") || target.starts_with(['/', '~']) {
+        let path = match (target.strip_prefix("~/"), dirs::home_dir()) {
+            (Some(rest), Some(home)) => home.join(rest).to_string_lossy().into_owned(),
+            _ => target.to_string(),
+        };
+        let mut command = std::process::Command::new("xdg-open");
+        command.arg(path);
+        command
+    } else {
+        let mut parts = target.split_whitespace();
+        let mut command = std::process::Command::new(parts.next().unwrap_or_default());
+        command.args(parts);
+        command
+    };
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    command
+}
+
+/// Run `command` with `sh -c`, killing it after `timeout`.
+fn run_shell_command(command: &str, timeout: Duration) -> Result<ExitStatus> {
+    let mut child = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to start sh")?;
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            bail!("timed out after {:?}", timeout);
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{Key, Modifier};
+    use parking_lot::Mutex;
+    use tempfile::TempDir;
+
+    /// Window source reporting whatever title the test sets.
+    struct StubWindows(Arc<Mutex<Option<String>>>);
+
+    impl WindowSource for StubWindows {
+        fn active_window(&self) -> Option<String> {
+            self.0.lock().clone()
+        }
+    }
+
+    /// Window source reporting the given titles in turn, then the last one.
+    struct ScriptedWindows(Mutex<VecDeque<&'static str>>);
+
+    impl WindowSource for ScriptedWindows {
+        fn active_window(&self) -> Option<String> {
+            let mut titles = self.0.lock();
+            let title = if titles.len() > 1 {
+                titles.pop_front()
+            } else {
+                titles.front().copied()
+            };
+            title.map(str::to_string)
+        }
+    }
+
+    /// Notifier that records notifications instead of showing them.
+    struct RecordingNotifier(Arc<Mutex<Vec<Notification>>>);
+
+    impl Notifier for RecordingNotifier {
+        fn show(&self, notification: &Notification) -> Result<()> {
+            self.0.lock().push(notification.clone());
+            Ok(())
+        }
+    }
+
+    /// Injector that records typed text, optionally typing slowly.
+    struct RecordingInjector {
+        typed: Arc<Mutex<Vec<String>>>,
+        keys: Arc<Mutex<Vec<Key>>>,
+        delay: Duration,
+    }
+
+    impl InputInjector for RecordingInjector {
+        fn backend_name(&self) -> &'static str {
+            "Recording"
+        }
+
+        fn type_text(&self, text: &str) -> Result<()> {
+            std::thread::sleep(self.delay);
+            self.typed.lock().push(text.to_string());
+            Ok(())
+        }
+
+        fn press_key(&self, key: Key) -> Result<()> {
+            self.keys.lock().push(key);
+            Ok(())
+        }
+
+        fn key_combo(&self, _modifiers: &[Modifier], _key: Key) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    type Typed = Arc<Mutex<Vec<String>>>;
+
+    fn processor(temp_dir: &TempDir) -> (EventProcessor, Typed, Arc<AppState>) {
+        processor_with_delay(temp_dir, Duration::ZERO)
+    }
+
+    fn processor_with_delay(
+        temp_dir: &TempDir,
+        delay: Duration,
+    ) -> (EventProcessor, Typed, Arc<AppState>) {
+        let (processor, typed, _, state) = recording_processor(temp_dir, delay);
+        (processor, typed, state)
+    }
+
+    /// Like [`processor`], also returning the keys pressed.
+    fn processor_with_keys(temp_dir: &TempDir) -> (EventProcessor, Typed, Arc<Mutex<Vec<Key>>>) {
+        let (processor, typed, keys, _) = recording_processor(temp_dir, Duration::ZERO);
+        (processor, typed, keys)
+    }
+
+    fn recording_processor(
+        temp_dir: &TempDir,
+        delay: Duration,
+    ) -> (EventProcessor, Typed, Arc<Mutex<Vec<Key>>>, Arc<AppState>) {
+        let typed = Arc::new(Mutex::new(Vec::new()));
+        let keys = Arc::new(Mutex::new(Vec::new()));
+        let store = Arc::new(VoiceCommandStore::new(temp_dir.path()).unwrap());
+        let state = AppState::new();
+        let processor = EventProcessor::with_voice_commands(
+            Box::new(RecordingInjector {
+                typed: typed.clone(),
+                keys: keys.clone(),
+                delay,
+            }),
+            store,
+            state.clone(),
+        );
+        (processor, typed, keys, state)
+    }
+
+    /// Media controller that records the actions it is sent.
+    struct RecordingMedia(Arc<Mutex<Vec<MediaAction>>>);
+
+    impl MediaController for RecordingMedia {
+        fn send(&self, action: MediaAction) -> Result<()> {
+            self.0.lock().push(action);
+            Ok(())
+        }
+    }
+
+    fn word(word: &str) -> ConnectionEvent {
+        ConnectionEvent::WordReceived {
+            word: word.to_string(),
+            seq: None,
+            session: "session1".to_string(),
+            eou: false,
+        }
+    }
+
+    fn end_of_utterance(word: &str) -> ConnectionEvent {
+        ConnectionEvent::WordReceived {
+            word: word.to_string(),
+            seq: None,
+            session: "session1".to_string(),
+            eou: true,
+        }
+    }
+
+    #[test]
+    fn test_default_timeouts() {
+        let temp_dir = TempDir::new().unwrap();
+        let (processor, _, _) = processor(&temp_dir);
+        assert_eq!(processor.stale_timeout(), STALE_WORD_TIMEOUT);
+    }
+
+    #[test]
+    fn test_configured_stale_timeout() {
+        let temp_dir = TempDir::new().unwrap();
+        let (processor, _, _) = processor(&temp_dir);
+        let processor =
+            processor.with_timeouts(Duration::from_millis(100), Duration::from_millis(2000));
+        assert_eq!(processor.stale_timeout(), Duration::from_millis(2000));
+    }
+
+    #[tokio::test]
+    async fn test_periodic_flush_uses_configured_look_ahead() {
+        let temp_dir = TempDir::new().unwrap();
+        let (processor, typed, _) = processor(&temp_dir);
+        let mut processor =
+            processor.with_timeouts(Duration::from_millis(10), Duration::from_millis(500));
+
+        // "select" could start "select all", so it is held back
+        processor.process_event(word("select")).await.unwrap();
+        processor.wait_for_injection().await;
+        assert!(typed.lock().is_empty());
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        processor.process_periodic_flush().await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(*typed.lock(), vec!["select ".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_look_ahead_types_word_immediately() {
+        let temp_dir = TempDir::new().unwrap();
+        let (processor, typed, _) = processor(&temp_dir);
+        let mut processor = processor.with_look_ahead(false);
+
+        // "select" could start "select all", but isn't held back
+        processor.process_event(word("select")).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(*typed.lock(), vec!["select ".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_end_of_utterance_flushes_pending_word() {
+        let temp_dir = TempDir::new().unwrap();
+        let (processor, typed, _) = processor(&temp_dir);
+        // Long enough that only the marker can flush
+        let mut processor =
+            processor.with_timeouts(Duration::from_secs(60), Duration::from_secs(60));
+
+        processor.process_event(word("select")).await.unwrap();
+        processor.process_event(end_of_utterance("")).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(*typed.lock(), vec!["select ".to_string()]);
+
+        // Nothing left for the periodic flush
+        processor.process_periodic_flush().await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(*typed.lock(), vec!["select ".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_end_of_utterance_on_last_word() {
+        let temp_dir = TempDir::new().unwrap();
+        let (processor, typed, state) = processor(&temp_dir);
+        let mut processor =
+            processor.with_timeouts(Duration::from_secs(60), Duration::from_secs(60));
+
+        // Marked word that could start a command is typed right away
+        processor.process_event(word("please")).await.unwrap();
+        processor.process_event(end_of_utterance("select")).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(
+            *typed.lock(),
+            vec!["please ".to_string(), "select ".to_string()]
+        );
+
+        // A two-word command completed by the marked word still runs
+        processor.process_event(word("select")).await.unwrap();
+        processor.process_event(end_of_utterance("all")).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(typed.lock().len(), 2);
+        assert_eq!(state.commands_executed(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_typing_erases_text_since_last_command() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, typed, keys) = processor_with_keys(&temp_dir);
+
+        processor.process_event(word("hello")).await.unwrap();
+        processor.process_event(word("enter")).await.unwrap();
+        processor.process_event(word("wörld")).await.unwrap();
+        processor.process_event(word("again")).await.unwrap();
+        processor.process_event(word("cancel")).await.unwrap();
+        processor.process_event(word("typing")).await.unwrap();
+        processor.wait_for_injection().await;
+
+        assert_eq!(typed.lock().len(), 3);
+        // "wörld again " counted in characters, not bytes; Enter was the boundary
+        assert_eq!(keys.lock().len(), 1 + 12);
+        assert!(keys.lock()[1..].iter().all(|key| *key == Key::Backspace));
+
+        // Cancel typing is a boundary too, so a repeat erases nothing
+        processor.process_event(word("cancel")).await.unwrap();
+        processor.process_event(word("typing")).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(keys.lock().len(), 1 + 12);
+    }
+
+    #[tokio::test]
+    async fn test_flush_now_types_two_word_candidate() {
+        let temp_dir = TempDir::new().unwrap();
+        let (processor, typed, _) = processor(&temp_dir);
+        let mut processor =
+            processor.with_timeouts(Duration::from_secs(60), Duration::from_secs(60));
+
+        // "select" could start "select all", so it waits for the next word
+        processor.process_event(word("select")).await.unwrap();
+        processor.process_periodic_flush().await.unwrap();
+        processor.wait_for_injection().await;
+        assert!(typed.lock().is_empty());
+
+        processor.flush_now().await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(*typed.lock(), vec!["select ".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_emoji_by_name() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, typed, _) = processor(&temp_dir);
+
+        for w in ["emoji", "smile", "emoji", "unicorn", "done"] {
+            processor.process_event(word(w)).await.unwrap();
+        }
+        processor.wait_for_injection().await;
+        assert_eq!(
+            *typed.lock(),
+            vec![
+                "\u{1F642} ".to_string(),
+                "emoji unicorn ".to_string(),
+                "done ".to_string()
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clear_field_only_with_input_enabled() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, _, keys) = processor_with_keys(&temp_dir);
+
+        processor.set_input_enabled(false);
+        processor.process_event(word("clear")).await.unwrap();
+        processor.process_event(word("field")).await.unwrap();
+        processor.wait_for_injection().await;
+        assert!(keys.lock().is_empty());
+
+        processor.set_input_enabled(true);
+        processor.process_event(word("clear")).await.unwrap();
+        processor.process_event(word("field")).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(*keys.lock(), vec![Key::Delete]);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_line_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        let (processor, _, keys) = processor_with_keys(&temp_dir);
+        let mut processor =
+            processor.with_duplicate_line_keys(&["f5".to_string(), "nope".to_string()]);
+
+        processor.process_event(word("duplicate")).await.unwrap();
+        processor.process_event(word("line")).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(*keys.lock(), vec![Key::F5]);
+    }
+
+    #[tokio::test]
+    async fn test_insert_date_and_time() {
+        let temp_dir = TempDir::new().unwrap();
+        let (processor, typed, _) = processor(&temp_dir);
+        let mut processor = processor.with_date_formats("%Y", "at %%H");
+
+        for w in ["insert", "date", "insert", "time"] {
+            processor.process_event(word(w)).await.unwrap();
+        }
+        processor.wait_for_injection().await;
+        let year = chrono::Local::now().format("%Y ").to_string();
+        assert_eq!(*typed.lock(), vec![year, "at %H ".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_end_of_utterance_without_pending_word() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, typed, _) = processor(&temp_dir);
+
+        processor.process_event(end_of_utterance("")).await.unwrap();
+        processor.process_event(end_of_utterance("hello")).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(*typed.lock(), vec!["hello ".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_statistics_count_typed_words_and_commands() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, _, state) = processor(&temp_dir);
+
+        processor.process_event(word("hello")).await.unwrap();
+        processor.process_event(word("enter")).await.unwrap();
+        processor
+            .process_event(ConnectionEvent::TextReceived("two words".to_string()))
+            .await
+            .unwrap();
+        processor
+            .process_event(ConnectionEvent::CommandReceived("COPY".to_string()))
+            .await
+            .unwrap();
+        processor.wait_for_injection().await;
+
+        assert_eq!(state.words_typed(), 3);
+        assert_eq!(state.bytes_injected(), ("hello ".len() + "two words".len()) as u64);
+        assert_eq!(state.commands_executed(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_statistics_skip_disabled_input() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, _, state) = processor(&temp_dir);
+        processor.set_input_enabled(false);
+
+        processor.process_event(word("hello")).await.unwrap();
+        processor.process_event(word("enter")).await.unwrap();
+        processor.wait_for_injection().await;
+
+        assert_eq!(state.words_typed(), 0);
+        assert_eq!(state.commands_executed(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_slow_injection_does_not_block_next_event() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, typed, _) =
+            processor_with_delay(&temp_dir, Duration::from_millis(200));
+
+        let start = std::time::Instant::now();
+        processor.process_event(word("hello")).await.unwrap();
+        processor.process_event(word("world")).await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(200));
+
+        processor.wait_for_injection().await;
+        assert_eq!(*typed.lock(), vec!["hello ".to_string(), "world ".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_word_burst_is_typed_in_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, typed, _) = processor_with_delay(&temp_dir, Duration::from_millis(1));
+
+        let words: Vec<String> = (0..100).map(|i| format!("word{}", i)).collect();
+        for w in &words {
+            processor.process_event(word(w)).await.unwrap();
+        }
+        processor.wait_for_injection().await;
+
+        let expected: Vec<String> = words.iter().map(|w| format!("{} ", w)).collect();
+        assert_eq!(*typed.lock(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_paused_words_are_replayed_on_resume() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, typed, _) = processor(&temp_dir);
+
+        processor.set_paused(true).await.unwrap();
+        processor.process_event(word("hello")).await.unwrap();
+        processor
+            .process_event(ConnectionEvent::TextReceived("there".to_string()))
+            .await
+            .unwrap();
+        processor.wait_for_injection().await;
+        assert!(typed.lock().is_empty());
+
+        processor.set_paused(false).await.unwrap();
+        processor.process_event(word("world")).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(
+            *typed.lock(),
+            vec!["hello ".to_string(), "there".to_string(), "world ".to_string()]
+        );
+    }
+
+    fn text(text: &str) -> ConnectionEvent {
+        ConnectionEvent::TextReceived(text.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_focus_change_pauses_typing() {
+        let temp_dir = TempDir::new().unwrap();
+        let (processor, typed, state) = processor(&temp_dir);
+        let window = Arc::new(Mutex::new(Some("Editor".to_string())));
+        let shown = Arc::new(Mutex::new(Vec::new()));
+        let mut processor = processor
+            .with_focus_check(FocusCheck::Pause, Arc::new(StubWindows(window.clone())))
+            .with_notifier(Box::new(RecordingNotifier(shown.clone())));
+
+        processor.process_event(text("hello ")).await.unwrap();
+        processor.process_event(text("again ")).await.unwrap();
+        *window.lock() = Some("Terminal".to_string());
+        processor.process_event(text("world")).await.unwrap();
+        processor.wait_for_injection().await;
+
+        assert_eq!(
+            *typed.lock(),
+            vec!["hello ".to_string(), "again ".to_string()]
+        );
+        assert!(state.is_paused());
+        assert_eq!(shown.lock().len(), 1);
+        assert_eq!(shown.lock()[0].summary, "Dictation paused");
+
+        // Resuming types into the window focused now
+        processor.set_paused(false).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(typed.lock().last().unwrap(), "world");
+    }
+
+    #[tokio::test]
+    async fn test_replay_stops_when_focus_moves_again() {
+        let temp_dir = TempDir::new().unwrap();
+        let (processor, typed, state) = processor(&temp_dir);
+        let windows = ["Editor", "Terminal", "Terminal", "Browser"];
+        let mut processor = processor.with_focus_check(
+            FocusCheck::Pause,
+            Arc::new(ScriptedWindows(Mutex::new(windows.into()))),
+        );
+
+        processor.process_event(text("hello ")).await.unwrap();
+        processor.process_event(text("a ")).await.unwrap();
+        processor.process_event(text("b ")).await.unwrap();
+        processor.process_event(text("c ")).await.unwrap();
+        assert!(state.is_paused());
+
+        // "b " finds the focus moved on
+        processor.set_paused(false).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(
+            *typed.lock(),
+            vec!["hello ".to_string(), "a ".to_string()]
+        );
+        assert!(state.is_paused());
+
+        processor.set_paused(false).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(typed.lock()[2..], ["b ".to_string(), "c ".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_focus_change_pauses_words_and_commands() {
+        let temp_dir = TempDir::new().unwrap();
+        let (processor, typed, keys) = processor_with_keys(&temp_dir);
+        let window = Arc::new(Mutex::new(Some("Editor".to_string())));
+        let mut processor =
+            processor.with_focus_check(FocusCheck::Pause, Arc::new(StubWindows(window.clone())));
+
+        processor.process_event(word("hello")).await.unwrap();
+        *window.lock() = Some("Terminal".to_string());
+        processor
+            .process_event(ConnectionEvent::CommandReceived("ENTER".to_string()))
+            .await
+            .unwrap();
+        processor.wait_for_injection().await;
+        assert!(processor.paused);
+        assert!(keys.lock().is_empty());
+
+        processor.set_paused(false).await.unwrap();
+        *window.lock() = Some("Browser".to_string());
+        processor.process_event(word("world")).await.unwrap();
+        processor.wait_for_injection().await;
+        assert!(processor.paused);
+        assert_eq!(keys.lock().len(), 1);
+        assert_eq!(*typed.lock(), vec!["hello ".to_string()]);
+
+        processor.set_paused(false).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(typed.lock().last().unwrap(), "world ");
+    }
+
+    #[tokio::test]
+    async fn test_text_deferred_by_focus_change_is_not_a_duplicate() {
+        let temp_dir = TempDir::new().unwrap();
+        let (processor, typed, _) = processor(&temp_dir);
+        let window = Arc::new(Mutex::new(Some("Editor".to_string())));
+        let mut processor = processor
+            .with_focus_check(FocusCheck::Pause, Arc::new(StubWindows(window.clone())))
+            .with_duplicate_text_window(Duration::from_secs(60));
+
+        processor.process_event(text("hello ")).await.unwrap();
+        *window.lock() = Some("Terminal".to_string());
+        processor.process_event(text("world")).await.unwrap();
+        // Resent by the phone while paused
+        processor.process_event(text("world")).await.unwrap();
+
+        processor.set_paused(false).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(
+            *typed.lock(),
+            vec!["hello ".to_string(), "world".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_focus_change_warns_and_keeps_typing() {
+        let temp_dir = TempDir::new().unwrap();
+        let (processor, typed, state) = processor(&temp_dir);
+        let window = Arc::new(Mutex::new(Some("Editor".to_string())));
+        let shown = Arc::new(Mutex::new(Vec::new()));
+        let mut processor = processor
+            .with_focus_check(FocusCheck::Warn, Arc::new(StubWindows(window.clone())))
+            .with_notifier(Box::new(RecordingNotifier(shown.clone())));
+
+        processor.process_event(text("hello ")).await.unwrap();
+        *window.lock() = Some("Terminal".to_string());
+        processor.process_event(text("world ")).await.unwrap();
+        processor.process_event(text("again")).await.unwrap();
+        // An unknown focus isn't a change
+        *window.lock() = None;
+        processor.process_event(text("!")).await.unwrap();
+        processor.wait_for_injection().await;
+
+        assert_eq!(typed.lock().len(), 4);
+        assert!(!state.is_paused());
+        assert_eq!(shown.lock().len(), 1);
+        assert_eq!(shown.lock()[0].body, "Text now goes to \"Terminal\".");
+    }
+
+    #[tokio::test]
+    async fn test_pause_buffer_is_capped() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, typed, _) = processor(&temp_dir);
+
+        processor.set_paused(true).await.unwrap();
+        for i in 0..PAUSE_BUFFER_CAP + 10 {
+            processor.process_event(word(&format!("w{}", i))).await.unwrap();
+        }
+        processor.set_paused(false).await.unwrap();
+        processor.wait_for_injection().await;
+
+        let typed = typed.lock();
+        assert_eq!(typed.len(), PAUSE_BUFFER_CAP);
+        assert_eq!(typed[0], "w0 ");
+    }
+
+    #[tokio::test]
+    async fn test_disabled_input_discards_instead_of_buffering() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, typed, _) = processor(&temp_dir);
+
+        processor.set_input_enabled(false);
+        processor.process_event(word("hello")).await.unwrap();
+        processor.set_input_enabled(true);
+        processor.process_event(word("world")).await.unwrap();
+        processor.wait_for_injection().await;
+
+        assert_eq!(*typed.lock(), vec!["world ".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_types_pending_word() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, typed, _) = processor(&temp_dir);
+
+        // "select" waits for a possible "all"
+        processor.process_event(word("select")).await.unwrap();
+        processor.shutdown().await.unwrap();
+
+        assert_eq!(*typed.lock(), vec!["select ".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_empty_and_whitespace_input_is_not_typed() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, typed, _) = processor(&temp_dir);
+
+        for text in ["", " ", "\t\n  "] {
+            processor
+                .process_event(ConnectionEvent::TextReceived(text.to_string()))
+                .await
+                .unwrap();
+            processor.process_event(word(text)).await.unwrap();
+        }
+        processor.process_periodic_flush().await.unwrap();
+        processor.wait_for_injection().await;
+        assert!(typed.lock().is_empty());
+
+        // Real words still get their trailing space
+        processor.process_event(word("hello")).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(*typed.lock(), vec!["hello ".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_only_first_recorded_phrase_is_saved() {
+        let temp_dir = TempDir::new().unwrap();
+        let (processor, typed, state) = processor(&temp_dir);
+        let mut processor =
+            processor.with_timeouts(Duration::from_millis(10), Duration::from_millis(500));
+        let store = processor.voice_command_store.clone().unwrap();
+
+        state.start_recording("ENTER".to_string());
+        processor.process_event(word("hello")).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        processor.process_periodic_flush().await.unwrap();
+        assert_eq!(store.get_phrase("ENTER"), "hello");
+        assert!(!state.is_recording());
+
+        // The rest of the utterance is neither saved nor typed
+        processor.process_event(word("world")).await.unwrap();
+        processor.process_event(end_of_utterance("again")).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(store.get_phrase("ENTER"), "hello");
+        assert!(typed.lock().is_empty());
+
+        // The next utterance is typed
+        processor.process_event(word("typed")).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(*typed.lock(), vec!["typed ".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_rearmed_recording_saves_next_phrase() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, _, state) = processor(&temp_dir);
+        let store = processor.voice_command_store.clone().unwrap();
+
+        state.start_recording("ENTER".to_string());
+        processor.process_event(end_of_utterance("hello")).await.unwrap();
+        state.start_recording("COPY".to_string());
+        processor.process_event(end_of_utterance("world")).await.unwrap();
+
+        assert_eq!(store.get_phrase("ENTER"), "hello");
+        assert_eq!(store.get_phrase("COPY"), "world");
+    }
+
+    #[tokio::test]
+    async fn test_records_two_word_phrase() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, typed, keys) = processor_with_keys(&temp_dir);
+        let state = processor.state.clone().unwrap();
+        let store = processor.voice_command_store.clone().unwrap();
+
+        // "enter" is a command on its own, but is part of the phrase here
+        state.start_recording("COPY".to_string());
+        processor.process_event(word("do")).await.unwrap();
+        processor.process_event(word("enter")).await.unwrap();
+        assert!(state.is_recording());
+        processor.process_event(end_of_utterance("")).await.unwrap();
+        processor.wait_for_injection().await;
+
+        assert_eq!(store.get_phrase("COPY"), "do enter");
+        assert!(!state.is_recording());
+        assert!(typed.lock().is_empty());
+        assert!(keys.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_only_first_recorded_text_is_saved() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, typed, state) = processor(&temp_dir);
+        let store = processor.voice_command_store.clone().unwrap();
+
+        state.start_recording("ENTER".to_string());
+        processor.process_event(text("hello there")).await.unwrap();
+        processor.process_event(text("something else")).await.unwrap();
+        processor.wait_for_injection().await;
+
+        assert_eq!(store.get_phrase("ENTER"), "hello there");
+        assert_eq!(*typed.lock(), vec!["something else".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_empty_word_does_not_end_recording() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, _, state) = processor(&temp_dir);
+
+        state.start_recording("ENTER".to_string());
+        processor.process_event(word(" ")).await.unwrap();
+        assert!(state.is_recording());
+    }
+
+    fn connected(device_id: &str) -> ConnectionEvent {
+        ConnectionEvent::Connected {
+            device_id: device_id.to_string(),
+            // The Android app names every phone the same
+            device_name: "Android Device".to_string(),
+        }
+    }
+
+    fn pair_requested(device_id: &str) -> ConnectionEvent {
+        ConnectionEvent::PairRequested {
+            device_id: device_id.to_string(),
+            device_name: Some("Android Device".to_string()),
+            public_key: "key".to_string(),
+            scanned: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_connected_does_not_reset_word_buffer() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, typed, _) = processor(&temp_dir);
+
+        processor.process_event(connected("phone-1")).await.unwrap();
+        // "select" is held back waiting for a possible "all"
+        processor.process_event(word("select")).await.unwrap();
+        processor.process_event(connected("phone-1")).await.unwrap();
+        processor.shutdown().await.unwrap();
+
+        assert_eq!(*typed.lock(), vec!["select ".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_connected_after_disconnect_resets_word_buffer() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, typed, _) = processor(&temp_dir);
+
+        processor.process_event(connected("phone-1")).await.unwrap();
+        processor.process_event(word("select")).await.unwrap();
+        processor.process_event(ConnectionEvent::Disconnected).await.unwrap();
+        processor.process_event(connected("phone-1")).await.unwrap();
+        processor.shutdown().await.unwrap();
+
+        assert!(typed.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_new_session_resets_word_buffer() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, typed, _) = processor(&temp_dir);
+
+        // The same phone pairing again, without a disconnect in between
+        processor.process_event(connected("phone-1")).await.unwrap();
+        processor.process_event(word("select")).await.unwrap();
+        processor
+            .process_event(pair_requested("phone-1"))
+            .await
+            .unwrap();
+        assert!(!processor.is_duplicate_connect(&connected("phone-1")));
+        processor.process_event(connected("phone-1")).await.unwrap();
+
+        // Another phone with the same name
+        processor.process_event(word("select")).await.unwrap();
+        assert!(processor.is_duplicate_connect(&connected("phone-1")));
+        assert!(!processor.is_duplicate_connect(&connected("phone-2")));
+        processor.process_event(connected("phone-2")).await.unwrap();
+        processor.shutdown().await.unwrap();
+
+        assert!(typed.lock().is_empty());
+    }
+
+    #[cfg(feature = "scripting")]
+    #[tokio::test]
+    async fn test_script_command_types_its_result() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("voice_commands.json"),
+            r#"{"version": 1, "mappings": [
+                {"phrase": "greet", "command": "GREET", "created_at": "2026-01-01T00:00:00Z", "script": "greet.rhai"}
+            ]}"#,
+        )
+        .unwrap();
+        std::fs::create_dir(temp_dir.path().join("scripts")).unwrap();
+        std::fs::write(
+            temp_dir.path().join("scripts").join("greet.rhai"),
+            r#"text("Hello from " + phrase)"#,
+        )
+        .unwrap();
+        let (mut processor, typed, _) = processor(&temp_dir);
+
+        processor.process_event(word("greet")).await.unwrap();
+        processor.wait_for_injection().await;
+
+        assert_eq!(*typed.lock(), vec!["Hello from greet".to_string()]);
+    }
+
+    #[cfg(feature = "scripting")]
+    #[tokio::test]
+    async fn test_cancel_typing_erases_script_text() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("voice_commands.json"),
+            r#"{"version": 1, "mappings": [
+                {"phrase": "greet", "command": "GREET", "created_at": "2026-01-01T00:00:00Z", "script": "greet.rhai"}
+            ]}"#,
+        )
+        .unwrap();
+        std::fs::create_dir(temp_dir.path().join("scripts")).unwrap();
+        std::fs::write(
+            temp_dir.path().join("scripts").join("greet.rhai"),
+            r#"text("Hi")"#,
+        )
+        .unwrap();
+        let (mut processor, typed, keys) = processor_with_keys(&temp_dir);
+
+        processor.process_event(word("greet")).await.unwrap();
+        processor.process_event(word("cancel")).await.unwrap();
+        processor.process_event(word("typing")).await.unwrap();
+        processor.wait_for_injection().await;
+
+        assert_eq!(*typed.lock(), vec!["Hi".to_string()]);
+        assert_eq!(keys.lock().len(), 2);
+        assert!(keys.lock().iter().all(|key| *key == Key::Backspace));
+    }
+
+    #[tokio::test]
+    async fn test_media_commands_go_to_the_player() {
+        let temp_dir = TempDir::new().unwrap();
+        let (processor, typed, state) = processor(&temp_dir);
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let mut processor = processor.with_media_controller(Box::new(RecordingMedia(sent.clone())));
+
+        processor.process_event(word("next")).await.unwrap();
+        processor.process_event(word("track")).await.unwrap();
+        processor
+            .process_event(ConnectionEvent::TextReceived("pause".to_string()))
+            .await
+            .unwrap();
+        processor
+            .process_event(ConnectionEvent::CommandReceived("PREVIOUS_TRACK".to_string()))
+            .await
+            .unwrap();
+        processor.wait_for_injection().await;
+
+        assert_eq!(
+            *sent.lock(),
+            vec![MediaAction::NextTrack, MediaAction::Pause, MediaAction::PreviousTrack]
+        );
+        assert!(typed.lock().is_empty());
+        assert_eq!(state.commands_executed(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_media_commands_respect_input_toggle() {
+        let temp_dir = TempDir::new().unwrap();
+        let (processor, _, _) = processor(&temp_dir);
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let mut processor = processor.with_media_controller(Box::new(RecordingMedia(sent.clone())));
+        processor.set_input_enabled(false);
+
+        processor
+            .process_event(ConnectionEvent::TextReceived("play".to_string()))
+            .await
+            .unwrap();
+
+        assert!(sent.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_command_cooldown_suppresses_repeats() {
+        let temp_dir = TempDir::new().unwrap();
+        let (processor, _, state) = processor(&temp_dir);
+        let mut processor = processor.with_command_cooldown(Duration::from_millis(200));
+        let enter = || ConnectionEvent::CommandReceived("ENTER".to_string());
+
+        processor.process_event(enter()).await.unwrap();
+        processor.process_event(enter()).await.unwrap();
+        // Other commands have their own cooldown
+        processor.process_event(word("copy")).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(state.commands_executed(), 2);
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        processor.process_event(enter()).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(state.commands_executed(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_text_is_dropped() {
+        let temp_dir = TempDir::new().unwrap();
+        let (processor, typed, _) = processor(&temp_dir);
+        let mut processor = processor.with_duplicate_text_window(Duration::from_millis(200));
+
+        // A resend within the window is dropped
+        processor.process_event(text("hello world")).await.unwrap();
+        processor.process_event(text("hello world")).await.unwrap();
+        // Other text in between makes a repeat legitimate
+        processor.process_event(text("again")).await.unwrap();
+        processor.process_event(text("hello world")).await.unwrap();
+        // Words are never deduplicated
+        processor.process_event(end_of_utterance("yes")).await.unwrap();
+        processor.process_event(end_of_utterance("yes")).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(
+            *typed.lock(),
+            vec!["hello world", "again", "hello world", "yes ", "yes "]
+        );
+
+        // After the window a repeat is typed
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        processor.process_event(text("hello world")).await.unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(typed.lock().len(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_no_cooldown_by_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut processor, _, state) = processor(&temp_dir);
+
+        processor
+            .process_event(ConnectionEvent::TextReceived("enter enter".to_string()))
+            .await
+            .unwrap();
+        processor.wait_for_injection().await;
+        assert_eq!(state.commands_executed(), 2);
+    }
+
+    /// Processor with a "touch it" shell command mapping named "touch".
+    fn shell_processor(temp_dir: &TempDir, shell: ShellConfig) -> EventProcessor {
+        std::fs::write(
+            temp_dir.path().join("voice_commands.json"),
+            r#"{"version": 1, "mappings": [
+                {"phrase": "touch it", "command": "TOUCH", "created_at": "2026-01-01T00:00:00Z", "shell": "touch"}
+            ]}"#,
+        )
+        .unwrap();
+        let (processor, _, _) = processor(temp_dir);
+        processor.with_shell_commands(shell)
+    }
+
+    fn touch_config(enabled: bool, name: &str, marker: &std::path::Path) -> ShellConfig {
+        let mut shell = ShellConfig {
+            enabled,
+            ..ShellConfig::default()
+        };
+        shell
+            .allowed
+            .insert(name.to_string(), format!("touch '{}'", marker.display()));
+        shell
+    }
+
+    async fn wait_for_file(path: &std::path::Path) -> bool {
+        for _ in 0..100 {
+            if path.exists() {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        false
+    }
+
+    #[tokio::test]
+    async fn test_allowed_shell_command_runs() {
+        let temp_dir = TempDir::new().unwrap();
+        let marker = temp_dir.path().join("ran");
+        let mut processor = shell_processor(&temp_dir, touch_config(true, "touch", &marker));
+
+        processor
+            .process_event(ConnectionEvent::TextReceived("touch it".to_string()))
+            .await
+            .unwrap();
+
+        assert!(wait_for_file(&marker).await);
+    }
+
+    #[tokio::test]
+    async fn test_shell_command_blocked_when_disabled() {
+        let temp_dir = TempDir::new().unwrap();
+        let marker = temp_dir.path().join("ran");
+        let mut processor = shell_processor(&temp_dir, touch_config(false, "touch", &marker));
+
+        processor
+            .process_event(ConnectionEvent::TextReceived("touch it".to_string()))
+            .await
+            .unwrap();
+
+        assert!(!wait_for_file(&marker).await);
+    }
+
+    #[tokio::test]
+    async fn test_shell_command_blocked_when_not_allowlisted() {
+        let temp_dir = TempDir::new().unwrap();
+        let marker = temp_dir.path().join("ran");
+        let mut processor = shell_processor(&temp_dir, touch_config(true, "other", &marker));
+
+        processor
+            .process_event(ConnectionEvent::TextReceived("touch it".to_string()))
+            .await
+            .unwrap();
+
+        assert!(!wait_for_file(&marker).await);
+    }
+
+    #[tokio::test]
+    async fn test_shell_command_cannot_be_sent_as_command() {
+        let temp_dir = TempDir::new().unwrap();
+        let marker = temp_dir.path().join("ran");
+        let mut processor = shell_processor(&temp_dir, touch_config(true, "touch", &marker));
+
+        processor
+            .process_event(ConnectionEvent::CommandReceived("TOUCH".to_string()))
+            .await
+            .unwrap();
+
+        assert!(!wait_for_file(&marker).await);
+    }
+
+    #[test]
+    fn test_shell_command_timeout() {
+        let start = Instant::now();
+        assert!(run_shell_command("sleep 5", Duration::from_millis(100)).is_err());
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(run_shell_command("exit 0", Duration::from_secs(5))
+            .unwrap()
+            .success());
+    }
+
+    fn app_processor(
+        temp_dir: &TempDir,
+        enabled: bool,
+        marker: &std::path::Path,
+    ) -> EventProcessor {
+        let mut apps = AppsConfig {
+            enabled,
+            ..AppsConfig::default()
+        };
+        apps.launch.insert(
+            "open marker".to_string(),
+            format!("touch {}", marker.display()),
+        );
+        let (processor, _, _) = processor(temp_dir);
+        processor.with_apps(apps)
+    }
+
+    #[tokio::test]
+    async fn test_app_phrase_launches_app() {
+        let temp_dir = TempDir::new().unwrap();
+        let marker = temp_dir.path().join("launched");
+        let mut processor = app_processor(&temp_dir, true, &marker);
+
+        processor.process_event(word("open")).await.unwrap();
+        processor.process_event(word("marker")).await.unwrap();
+
+        assert!(wait_for_file(&marker).await);
+    }
+
+    #[tokio::test]
+    async fn test_app_phrase_is_typed_when_disabled() {
+        let temp_dir = TempDir::new().unwrap();
+        let marker = temp_dir.path().join("launched");
+        let mut processor = app_processor(&temp_dir, false, &marker);
+
+        processor
+            .process_event(ConnectionEvent::TextReceived("open marker".to_string()))
+            .await
+            .unwrap();
+        // Not a command the phone can send either
+        processor
+            .process_event(ConnectionEvent::CommandReceived(
+                "LAUNCH:open marker".to_string(),
+            ))
+            .await
+            .unwrap();
+
+        assert!(!wait_for_file(&marker).await);
+    }
+
+    #[test]
+    fn test_launch_command() {
+        let command = launch_command("code --new-window");
+        assert_eq!(command.get_program(), "code");
+        assert_eq!(command.get_args().collect::<Vec<_>>(), ["--new-window"]);
+
+        let command = launch_command("https://example.com");
+        assert_eq!(command.get_program(), "xdg-open");
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            ["https://example.com"]
+        );
+    }
+}
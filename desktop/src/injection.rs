@@ -0,0 +1,402 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Input injection off the event loop.
+//!
+//! Typing with a keystroke delay can take a while. Injections are queued to
+//! a dedicated thread so BLE events and the periodic flush keep being
+//! processed meanwhile. The thread runs injections in the order they were
+//! queued.
+//!
+//! The queue is bounded. Bursts of words up to its capacity are absorbed
+//! without delay. When it is full, queueing waits for the injection thread to
+//! catch up instead of dropping input, which holds back the event loop and in
+//! turn the BLE event channel. Nothing dictated is lost and the order is kept.
+//!
+//! Typing can be limited to a number of characters per second so a runaway
+//! stream can't flood the focused app. Text over the limit stays queued and
+//! is typed in pieces at the allowed rate.
+
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, error, info};
+
+use crate::commands::{self, VoiceCommand};
+use crate::input::InputInjector;
+use crate::state::AppState;
+
+/// Number of injections that can be queued before queueing waits.
+pub const QUEUE_CAPACITY: usize = 256;
+
+/// A queued injection.
+enum Injection {
+    /// Type text at the cursor.
+    Text(String),
+    /// Execute a voice command.
+    Command(VoiceCommand),
+    /// Signal once everything queued before it is done.
+    Barrier(oneshot::Sender<()>),
+}
+
+/// Token bucket limiting typed characters per second.
+///
+/// Holds up to one second worth of characters, so short bursts go through
+/// at once. Reservations beyond that are granted with a wait.
+struct RateLimiter {
+    /// Characters per second.
+    rate: u32,
+    /// Characters that may be typed now. Negative when reservations are
+    /// ahead of the rate.
+    available: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    fn new(rate: u32, now: Instant) -> Self {
+        Self {
+            rate,
+            available: f64::from(rate),
+            last: now,
+        }
+    }
+
+    /// Reserve `chars` characters at `now`, returning how long to wait
+    /// before typing them.
+    fn reserve(&mut self, chars: usize, now: Instant) -> Duration {
+        let rate = f64::from(self.rate);
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.available = (self.available + elapsed * rate).min(rate);
+        self.last = now;
+
+        self.available -= chars as f64;
+        if self.available >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.available / rate)
+        }
+    }
+}
+
+/// Split `text` into pieces of at most `max_chars` characters.
+fn split_chars(text: &str, max_chars: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let end = rest
+            .char_indices()
+            .nth(max_chars)
+            .map_or(rest.len(), |(index, _)| index);
+        let (piece, tail) = rest.split_at(end);
+        pieces.push(piece);
+        rest = tail;
+    }
+    pieces
+}
+
+/// Typing rate limit shared with the injection thread, `None` for no limit.
+type SharedLimit = Arc<Mutex<Option<RateLimiter>>>;
+
+/// Queue feeding the injection thread.
+///
+/// The thread exits when the queue is dropped.
+pub struct InjectionQueue {
+    tx: mpsc::Sender<Injection>,
+    injector: Arc<dyn InputInjector>,
+    limit: SharedLimit,
+}
+
+impl InjectionQueue {
+    /// Start the injection thread.
+    ///
+    /// Successful injections are counted in the statistics of `state`.
+    pub fn new(injector: Box<dyn InputInjector>, state: Option<Arc<AppState>>) -> Self {
+        Self::with_capacity(injector, state, QUEUE_CAPACITY)
+    }
+
+    /// Start the injection thread with a queue holding up to `capacity` injections.
+    pub fn with_capacity(
+        injector: Box<dyn InputInjector>,
+        state: Option<Arc<AppState>>,
+        capacity: usize,
+    ) -> Self {
+        let injector: Arc<dyn InputInjector> = Arc::from(injector);
+        let (tx, rx) = mpsc::channel(capacity);
+
+        let limit = SharedLimit::default();
+
+        let worker_injector = injector.clone();
+        let worker_limit = limit.clone();
+        thread::Builder::new()
+            .name("injection".to_string())
+            .spawn(move || {
+                run(
+                    rx,
+                    worker_injector.as_ref(),
+                    state.as_deref(),
+                    &worker_limit,
+                )
+            })
+            .expect("Failed to spawn injection thread");
+
+        Self {
+            tx,
+            injector,
+            limit,
+        }
+    }
+
+    /// Queue text to be typed, waiting while the queue is full.
+    pub async fn type_text(&self, text: String) {
+        self.send(Injection::Text(text)).await;
+    }
+
+    /// Queue a voice command to be executed, waiting while the queue is full.
+    pub async fn execute(&self, command: VoiceCommand) {
+        self.send(Injection::Command(command)).await;
+    }
+
+    /// Set the delay between keystrokes.
+    ///
+    /// Applies to injections that haven't started yet.
+    pub fn set_typing_delay(&self, delay: Duration) {
+        self.injector.set_typing_delay(delay);
+    }
+
+    /// Type at most `max_chars_per_sec` characters per second, 0 for no limit.
+    pub fn set_rate_limit(&self, max_chars_per_sec: u32) {
+        *self.limit.lock() =
+            (max_chars_per_sec > 0).then(|| RateLimiter::new(max_chars_per_sec, Instant::now()));
+    }
+
+    /// Wait until everything queued so far has been injected.
+    pub async fn wait_idle(&self) {
+        let (done_tx, done_rx) = oneshot::channel();
+        self.send(Injection::Barrier(done_tx)).await;
+        let _ = done_rx.await;
+    }
+
+    async fn send(&self, injection: Injection) {
+        if self.tx.capacity() == 0 {
+            debug!("Injection queue full, waiting for the injector");
+        }
+        if self.tx.send(injection).await.is_err() {
+            error!("Injection thread is gone, dropping input");
+        }
+    }
+}
+
+/// Type `text`, in pieces paced to `limit` if there is one.
+fn type_paced(injector: &dyn InputInjector, text: &str, limit: &SharedLimit) -> anyhow::Result<()> {
+    let Some(rate) = limit.lock().as_ref().map(|limiter| limiter.rate) else {
+        return injector.type_text(text);
+    };
+
+    for piece in split_chars(text, rate as usize) {
+        let wait = limit.lock().as_mut().map_or(Duration::ZERO, |limiter| {
+            limiter.reserve(piece.chars().count(), Instant::now())
+        });
+        if !wait.is_zero() {
+            debug!("Typing rate limit reached, waiting {:?}", wait);
+            thread::sleep(wait);
+        }
+        injector.type_text(piece)?;
+    }
+    Ok(())
+}
+
+/// Run queued injections until the queue is dropped.
+///
+/// `state` is busy from the first injection until the queue runs empty.
+fn run(
+    mut rx: mpsc::Receiver<Injection>,
+    injector: &dyn InputInjector,
+    state: Option<&AppState>,
+    limit: &SharedLimit,
+) {
+    let set_busy = |busy: bool| {
+        if let Some(state) = state {
+            state.set_busy(busy);
+        }
+    };
+
+    while let Some(injection) = rx.blocking_recv() {
+        if !matches!(injection, Injection::Barrier(_)) {
+            set_busy(true);
+        }
+        match injection {
+            Injection::Text(text) => match type_paced(injector, &text, limit) {
+                Ok(()) => {
+                    info!("Text delivered: {} chars", text.len());
+                    if let Some(state) = state {
+                        state.record_typed(&text);
+                    }
+                }
+                Err(e) => error!("Failed to inject text: {}", e),
+            },
+            Injection::Command(command) => match commands::execute(&command, injector) {
+                Ok(()) => {
+                    info!("Command delivered: {:?}", command);
+                    if let Some(state) = state {
+                        state.record_command();
+                    }
+                }
+                Err(e) => error!("Failed to execute command: {}", e),
+            },
+            Injection::Barrier(done) => {
+                // Report idle before releasing the waiter
+                if rx.is_empty() {
+                    set_busy(false);
+                }
+                let _ = done.send(());
+            }
+        }
+        if rx.is_empty() {
+            set_busy(false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{Key, Modifier};
+    use anyhow::Result;
+
+    /// Injector that types slowly and records what it typed.
+    struct SlowInjector {
+        delay: Duration,
+        typed: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl InputInjector for SlowInjector {
+        fn backend_name(&self) -> &'static str {
+            "Slow"
+        }
+
+        fn type_text(&self, text: &str) -> Result<()> {
+            thread::sleep(self.delay);
+            self.typed.lock().push(text.to_string());
+            Ok(())
+        }
+
+        fn press_key(&self, _key: Key) -> Result<()> {
+            self.typed.lock().push("<key>".to_string());
+            Ok(())
+        }
+
+        fn key_combo(&self, _modifiers: &[Modifier], _key: Key) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_injection_does_not_block_caller() {
+        let typed = Arc::new(Mutex::new(Vec::new()));
+        let queue = InjectionQueue::new(
+            Box::new(SlowInjector {
+                delay: Duration::from_millis(200),
+                typed: typed.clone(),
+            }),
+            None,
+        );
+
+        let start = Instant::now();
+        queue.type_text("hello ".to_string()).await;
+        queue.execute(VoiceCommand::Enter).await;
+        assert!(start.elapsed() < Duration::from_millis(100));
+        assert!(typed.lock().is_empty());
+
+        queue.wait_idle().await;
+        assert_eq!(
+            *typed.lock(),
+            vec!["hello ".to_string(), "<key>".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(10, start);
+
+        // One second worth goes through at once, then it's paced
+        assert_eq!(limiter.reserve(10, start), Duration::ZERO);
+        assert_eq!(limiter.reserve(5, start), Duration::from_millis(500));
+        let later = start + Duration::from_millis(500);
+        assert_eq!(limiter.reserve(5, later), Duration::from_millis(500));
+
+        // Idle time refills up to one second worth
+        let idle = later + Duration::from_secs(60);
+        assert_eq!(limiter.reserve(10, idle), Duration::ZERO);
+        assert!(limiter.reserve(1, idle) > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_split_chars() {
+        assert_eq!(split_chars("hello world", 4), vec!["hell", "o wo", "rld"]);
+        assert_eq!(split_chars("héllo", 2), vec!["hé", "ll", "o"]);
+        assert_eq!(split_chars("hi", 10), vec!["hi"]);
+        assert!(split_chars("", 10).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_burst_is_paced_to_rate_limit() {
+        let typed = Arc::new(Mutex::new(Vec::new()));
+        let queue = InjectionQueue::new(
+            Box::new(SlowInjector {
+                delay: Duration::ZERO,
+                typed: typed.clone(),
+            }),
+            None,
+        );
+        queue.set_rate_limit(100);
+
+        // 250 characters at 100 per second: the first 100 at once, the rest
+        // over 1.5 seconds
+        let start = Instant::now();
+        for _ in 0..5 {
+            queue.type_text("x".repeat(50)).await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(100));
+        queue.wait_idle().await;
+        let elapsed = start.elapsed();
+
+        assert!(elapsed >= Duration::from_millis(1400), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_secs(3), "{:?}", elapsed);
+        assert_eq!(typed.lock().concat(), "x".repeat(250));
+    }
+
+    #[tokio::test]
+    async fn test_burst_larger_than_queue_keeps_order() {
+        let typed = Arc::new(Mutex::new(Vec::new()));
+        let queue = InjectionQueue::with_capacity(
+            Box::new(SlowInjector {
+                delay: Duration::from_millis(1),
+                typed: typed.clone(),
+            }),
+            None,
+            4,
+        );
+
+        let words: Vec<String> = (0..50).map(|i| format!("word{} ", i)).collect();
+        for word in &words {
+            queue.type_text(word.clone()).await;
+        }
+
+        queue.wait_idle().await;
+        assert_eq!(*typed.lock(), words);
+    }
+}
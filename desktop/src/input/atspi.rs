@@ -0,0 +1,328 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Text injection through the AT-SPI accessibility bus.
+//!
+//! For screen-reader and assistive setups. Text is synthesized by the AT-SPI
+//! registry (`DeviceEventController.GenerateKeyboardEvent` with a string),
+//! called with `gdbus`. Keys and combinations, and text the registry
+//! refuses, go to the X11/Wayland injector.
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use super::keys::{Key, Modifier, MouseButton};
+use super::InputInjector;
+
+/// AT-SPI `KeySynthType` for synthesizing a whole string.
+const KEY_SYNTH_STRING: u32 = 4;
+
+/// Something that inserts text through accessibility APIs.
+pub trait AccessibleText: Send + Sync {
+    /// Insert text at the focused accessible's cursor.
+    fn insert_text(&self, text: &str) -> Result<()>;
+}
+
+/// The AT-SPI accessibility bus of the current session.
+pub struct AtspiBus {
+    address: String,
+}
+
+impl AtspiBus {
+    /// Look up the accessibility bus address.
+    ///
+    /// Fails when `gdbus` is missing or no AT-SPI bus is running.
+    pub fn connect() -> Result<Self> {
+        let output = Command::new("gdbus")
+            .args([
+                "call",
+                "--session",
+                "--dest",
+                "org.a11y.Bus",
+                "--object-path",
+                "/org/a11y/bus",
+                "--method",
+                "org.a11y.Bus.GetAddress",
+            ])
+            .stdin(Stdio::null())
+            .output()
+            .context("Failed to run gdbus (is it installed?)")?;
+
+        if !output.status.success() {
+            bail!(
+                "No AT-SPI bus: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        let address = parse_address(&String::from_utf8_lossy(&output.stdout))
+            .ok_or_else(|| anyhow!("Unexpected AT-SPI bus address"))?;
+        debug!("AT-SPI bus at {}", address);
+        Ok(Self { address })
+    }
+}
+
+impl AccessibleText for AtspiBus {
+    fn insert_text(&self, text: &str) -> Result<()> {
+        let output = Command::new("gdbus")
+            .args([
+                "call",
+                "--address",
+                &self.address,
+                "--dest",
+                "org.a11y.atspi.Registry",
+                "--object-path",
+                "/org/a11y/atspi/registry/deviceeventcontroller",
+                "--method",
+                "org.a11y.atspi.DeviceEventController.GenerateKeyboardEvent",
+                "0",
+                &gvariant_string(text),
+                &KEY_SYNTH_STRING.to_string(),
+            ])
+            .stdin(Stdio::null())
+            .output()
+            .context("Failed to run gdbus")?;
+
+        if !output.status.success() {
+            bail!(
+                "AT-SPI text insertion failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Parse the `gdbus` reply to `GetAddress`, e.g. `('unix:path=/run/...',)`.
+fn parse_address(reply: &str) -> Option<String> {
+    let address = reply.trim().strip_prefix("('")?.strip_suffix("',)")?;
+    (!address.is_empty()).then(|| address.to_string())
+}
+
+/// Quote text as a GVariant string literal for `gdbus`.
+fn gvariant_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('\'');
+    for c in text.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '\'' => quoted.push_str("\\'"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// Injector typing text through AT-SPI.
+pub struct AtspiInjector {
+    text: Box<dyn AccessibleText>,
+    fallback: Box<dyn InputInjector>,
+}
+
+impl AtspiInjector {
+    /// Create an injector inserting text through `text`, and using
+    /// `fallback` for keys and when insertion fails.
+    pub fn new(text: Box<dyn AccessibleText>, fallback: Box<dyn InputInjector>) -> Self {
+        Self { text, fallback }
+    }
+}
+
+/// Use AT-SPI if the bus could be reached, otherwise just `fallback`.
+pub fn atspi_or_fallback(
+    bus: Result<Box<dyn AccessibleText>>,
+    fallback: Box<dyn InputInjector>,
+) -> Box<dyn InputInjector> {
+    match bus {
+        Ok(bus) => {
+            info!(
+                "Using AT-SPI input injector ({} for keys)",
+                fallback.backend_name()
+            );
+            Box::new(AtspiInjector::new(bus, fallback))
+        }
+        Err(e) => {
+            warn!(
+                "AT-SPI unavailable ({}), using {}",
+                e,
+                fallback.backend_name()
+            );
+            fallback
+        }
+    }
+}
+
+impl InputInjector for AtspiInjector {
+    fn backend_name(&self) -> &'static str {
+        "AT-SPI"
+    }
+
+    fn type_text(&self, text: &str) -> Result<()> {
+        debug!("Inserting text: {} chars", text.chars().count());
+
+        match self.text.insert_text(text) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                warn!("{}, typing with {}", e, self.fallback.backend_name());
+                self.fallback.type_text(text)
+            }
+        }
+    }
+
+    fn press_key(&self, key: Key) -> Result<()> {
+        self.fallback.press_key(key)
+    }
+
+    fn key_combo(&self, modifiers: &[Modifier], key: Key) -> Result<()> {
+        self.fallback.key_combo(modifiers, key)
+    }
+
+    fn click(&self, button: MouseButton) -> Result<()> {
+        self.fallback.click(button)
+    }
+
+    fn double_click(&self, button: MouseButton) -> Result<()> {
+        self.fallback.double_click(button)
+    }
+
+    fn scroll(&self, delta: i32) -> Result<()> {
+        self.fallback.scroll(delta)
+    }
+
+    fn set_typing_delay(&self, delay: Duration) {
+        self.fallback.set_typing_delay(delay);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    type Log = Arc<Mutex<Vec<String>>>;
+
+    /// Accessible text stub recording insertions, or failing them.
+    struct StubText {
+        inserted: Log,
+        fail: bool,
+    }
+
+    impl AccessibleText for StubText {
+        fn insert_text(&self, text: &str) -> Result<()> {
+            if self.fail {
+                bail!("not editable");
+            }
+            self.inserted.lock().push(text.to_string());
+            Ok(())
+        }
+    }
+
+    /// Fallback injector recording what reaches it.
+    struct Fallback(Log);
+
+    impl InputInjector for Fallback {
+        fn backend_name(&self) -> &'static str {
+            "Fallback"
+        }
+
+        fn type_text(&self, text: &str) -> Result<()> {
+            self.0.lock().push(format!("type {}", text));
+            Ok(())
+        }
+
+        fn press_key(&self, key: Key) -> Result<()> {
+            self.0.lock().push(format!("key {}", key.name()));
+            Ok(())
+        }
+
+        fn key_combo(&self, modifiers: &[Modifier], key: Key) -> Result<()> {
+            self.0
+                .lock()
+                .push(format!("combo {}+{}", modifiers.len(), key.name()));
+            Ok(())
+        }
+    }
+
+    fn injector(fail: bool) -> (AtspiInjector, Log, Log) {
+        let inserted = Log::default();
+        let fallback = Log::default();
+        let injector = AtspiInjector::new(
+            Box::new(StubText {
+                inserted: inserted.clone(),
+                fail,
+            }),
+            Box::new(Fallback(fallback.clone())),
+        );
+        (injector, inserted, fallback)
+    }
+
+    #[test]
+    fn test_text_goes_through_atspi() {
+        let (injector, inserted, fallback) = injector(false);
+        injector.type_text("héllo ").unwrap();
+        injector.press_key(Key::Enter).unwrap();
+        injector.key_combo(&[Modifier::Ctrl], Key::A).unwrap();
+
+        assert_eq!(*inserted.lock(), vec!["héllo ".to_string()]);
+        assert_eq!(
+            *fallback.lock(),
+            vec!["key enter".to_string(), "combo 1+a".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_failed_insertion_falls_back() {
+        let (injector, inserted, fallback) = injector(true);
+        injector.type_text("hello ").unwrap();
+
+        assert!(inserted.lock().is_empty());
+        assert_eq!(*fallback.lock(), vec!["type hello ".to_string()]);
+    }
+
+    #[test]
+    fn test_backend_selection() {
+        let bus: Result<Box<dyn AccessibleText>> = Ok(Box::new(StubText {
+            inserted: Log::default(),
+            fail: false,
+        }));
+        let injector = atspi_or_fallback(bus, Box::new(Fallback(Log::default())));
+        assert_eq!(injector.backend_name(), "AT-SPI");
+
+        let injector =
+            atspi_or_fallback(Err(anyhow!("no bus")), Box::new(Fallback(Log::default())));
+        assert_eq!(injector.backend_name(), "Fallback");
+    }
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(
+            parse_address("('unix:path=/run/user/1000/at-spi/bus_0,guid=ab12',)\n"),
+            Some("unix:path=/run/user/1000/at-spi/bus_0,guid=ab12".to_string())
+        );
+        assert_eq!(parse_address("('',)"), None);
+        assert_eq!(parse_address("garbage"), None);
+    }
+
+    #[test]
+    fn test_gvariant_string() {
+        assert_eq!(gvariant_string("hello "), "'hello '");
+        assert_eq!(gvariant_string("it's"), "'it\\'s'");
+        assert_eq!(gvariant_string("a\\b\nc"), "'a\\\\b\\nc'");
+    }
+}
@@ -0,0 +1,252 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Global hotkey support.
+//!
+//! On X11 the hotkey is grabbed on the root window. Wayland has no portable
+//! way for an application to grab keys (the GlobalShortcuts portal is not
+//! widely available yet), so there the hotkey is disabled with a warning and
+//! users can bind a compositor shortcut instead.
+
+use anyhow::{bail, Result};
+use tracing::warn;
+
+use super::injector::DisplayServer;
+use super::keys::KeyCombo;
+
+/// Parse a hotkey string like "super+shift+v".
+///
+/// A global hotkey needs at least one modifier so it doesn't swallow a
+/// plain key for the whole desktop.
+pub fn parse_hotkey(hotkey: &str) -> Result<KeyCombo> {
+    let Some(combo) = KeyCombo::parse(hotkey) else {
+        bail!("Invalid hotkey '{}'", hotkey.trim());
+    };
+    if combo.modifiers.is_empty() {
+        bail!("Hotkey '{}' needs at least one modifier", hotkey.trim());
+    }
+    Ok(combo)
+}
+
+/// Start listening for a global hotkey in a background thread.
+///
+/// `on_press` is called from that thread each time the hotkey is pressed.
+/// Returns `Ok(false)` if global hotkeys are not supported on this display
+/// server.
+pub fn spawn_hotkey_listener<F>(combo: KeyCombo, on_press: F) -> Result<bool>
+where
+    F: Fn() + Send + 'static,
+{
+    match DisplayServer::detect() {
+        #[cfg(feature = "x11")]
+        DisplayServer::X11 => {
+            x11::spawn(combo, on_press)?;
+            Ok(true)
+        }
+        DisplayServer::Wayland => {
+            warn!(
+                "Global hotkey '{}' is not supported on Wayland; bind a compositor shortcut instead",
+                combo
+            );
+            Ok(false)
+        }
+        _ => {
+            warn!("Global hotkey '{}' is not supported on this display", combo);
+            Ok(false)
+        }
+    }
+}
+
+#[cfg(feature = "x11")]
+mod x11 {
+    //! Hotkey grab on the X11 root window.
+
+    use anyhow::{Context, Result};
+    use tracing::{debug, error, info};
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{ConnectionExt, GrabMode, ModMask};
+    use x11rb::protocol::Event;
+
+    use crate::input::keys::{Key, KeyCombo, Modifier};
+
+    /// Get the X11 keysym for a key.
+    fn keysym(key: Key) -> u32 {
+        match key {
+            Key::A => 0x61,
+            Key::B => 0x62,
+            Key::C => 0x63,
+            Key::D => 0x64,
+            Key::E => 0x65,
+            Key::F => 0x66,
+            Key::G => 0x67,
+            Key::H => 0x68,
+            Key::I => 0x69,
+            Key::J => 0x6a,
+            Key::K => 0x6b,
+            Key::L => 0x6c,
+            Key::M => 0x6d,
+            Key::N => 0x6e,
+            Key::O => 0x6f,
+            Key::P => 0x70,
+            Key::Q => 0x71,
+            Key::R => 0x72,
+            Key::S => 0x73,
+            Key::T => 0x74,
+            Key::U => 0x75,
+            Key::V => 0x76,
+            Key::W => 0x77,
+            Key::X => 0x78,
+            Key::Y => 0x79,
+            Key::Z => 0x7a,
+            Key::Enter => 0xff0d,
+            Key::Backspace => 0xff08,
+            Key::Tab => 0xff09,
+            Key::Escape => 0xff1b,
+            Key::CapsLock => 0xffe5,
+            Key::Space => 0x20,
+            Key::Delete => 0xffff,
+            Key::Home => 0xff50,
+            Key::End => 0xff57,
+            Key::PageUp => 0xff55,
+            Key::PageDown => 0xff56,
+            Key::Left => 0xff51,
+            Key::Right => 0xff53,
+            Key::Up => 0xff52,
+            Key::Down => 0xff54,
+            Key::F1 => 0xffbe,
+            Key::F2 => 0xffbf,
+            Key::F3 => 0xffc0,
+            Key::F4 => 0xffc1,
+            Key::F5 => 0xffc2,
+            Key::F6 => 0xffc3,
+            Key::F7 => 0xffc4,
+            Key::F8 => 0xffc5,
+            Key::F9 => 0xffc6,
+            Key::F10 => 0xffc7,
+            Key::F11 => 0xffc8,
+            Key::F12 => 0xffc9,
+        }
+    }
+
+    /// Get the X11 modifier mask for a set of modifiers.
+    fn mod_mask(modifiers: &[Modifier]) -> u16 {
+        modifiers
+            .iter()
+            .map(|m| match m {
+                Modifier::Ctrl => u16::from(ModMask::CONTROL),
+                Modifier::Alt => u16::from(ModMask::M1),
+                Modifier::Shift => u16::from(ModMask::SHIFT),
+                Modifier::Super => u16::from(ModMask::M4),
+            })
+            .fold(0, |mask, m| mask | m)
+    }
+
+    /// Grab the hotkey and dispatch presses on a background thread.
+    pub fn spawn<F>(combo: KeyCombo, on_press: F) -> Result<()>
+    where
+        F: Fn() + Send + 'static,
+    {
+        let (conn, screen_num) = x11rb::connect(None).context("Failed to connect to X server")?;
+        let root = conn.setup().roots[screen_num].root;
+        let min_keycode = conn.setup().min_keycode;
+        let max_keycode = conn.setup().max_keycode;
+
+        // Find the keycode producing the keysym
+        let mapping = conn
+            .get_keyboard_mapping(min_keycode, max_keycode - min_keycode + 1)?
+            .reply()?;
+        let per_keycode = usize::from(mapping.keysyms_per_keycode);
+        let wanted = keysym(combo.key);
+        let keycode = mapping
+            .keysyms
+            .chunks(per_keycode.max(1))
+            .position(|syms| syms.contains(&wanted))
+            .map(|index| min_keycode + index as u8)
+            .with_context(|| format!("No keycode for hotkey '{}'", combo))?;
+
+        // Grab with and without CapsLock/NumLock so the hotkey works either way
+        let mask = mod_mask(&combo.modifiers);
+        let lock = u16::from(ModMask::LOCK);
+        let num_lock = u16::from(ModMask::M2);
+        for extra in [0, lock, num_lock, lock | num_lock] {
+            conn.grab_key(
+                false,
+                root,
+                ModMask::from(mask | extra),
+                keycode,
+                GrabMode::ASYNC,
+                GrabMode::ASYNC,
+            )?
+            .check()
+            .with_context(|| format!("Failed to grab hotkey '{}' (already in use?)", combo))?;
+        }
+        conn.flush()?;
+        info!("Global hotkey '{}' registered", combo);
+
+        std::thread::spawn(move || loop {
+            match conn.wait_for_event() {
+                Ok(Event::KeyPress(event)) if event.detail == keycode => {
+                    debug!("Global hotkey pressed");
+                    on_press();
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    error!("Global hotkey listener stopped: {}", e);
+                    break;
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_mod_mask() {
+            assert_eq!(mod_mask(&[]), 0);
+            assert_eq!(
+                mod_mask(&[Modifier::Super, Modifier::Shift]),
+                u16::from(ModMask::M4) | u16::from(ModMask::SHIFT)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::keys::{Key, Modifier};
+
+    #[test]
+    fn test_parse_hotkey() {
+        let combo = parse_hotkey("Super+Shift+V").unwrap();
+        assert_eq!(combo.modifiers, vec![Modifier::Super, Modifier::Shift]);
+        assert_eq!(combo.key, Key::V);
+
+        let combo = parse_hotkey("ctrl + alt + f9").unwrap();
+        assert_eq!(combo.modifiers, vec![Modifier::Ctrl, Modifier::Alt]);
+        assert_eq!(combo.key, Key::F9);
+    }
+
+    #[test]
+    fn test_parse_hotkey_rejects_invalid() {
+        assert!(parse_hotkey("").is_err());
+        assert!(parse_hotkey("v").is_err());
+        assert!(parse_hotkey("super+shift").is_err());
+        assert!(parse_hotkey("hyper+v").is_err());
+    }
+}
@@ -14,15 +14,17 @@
 
 //! Text injection abstraction and factory.
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use std::env;
+use std::time::Duration;
 use tracing::{info, warn};
 
-use super::keys::{Key, Modifier};
+use super::keys::{Key, Modifier, MouseButton};
 
 #[cfg(feature = "x11")]
 use super::x11::X11Injector;
 
+use super::atspi::{atspi_or_fallback, AccessibleText, AtspiBus};
 use super::wayland::WaylandInjector;
 
 /// Trait for input injection backends.
@@ -38,6 +40,28 @@ pub trait InputInjector: Send + Sync {
 
     /// Press a key combination (modifiers + key).
     fn key_combo(&self, modifiers: &[Modifier], key: Key) -> Result<()>;
+
+    /// Click a mouse button where the pointer is.
+    fn click(&self, button: MouseButton) -> Result<()> {
+        bail!("{} can't click {:?}", self.backend_name(), button)
+    }
+
+    /// Double-click a mouse button where the pointer is.
+    fn double_click(&self, button: MouseButton) -> Result<()> {
+        self.click(button)?;
+        self.click(button)
+    }
+
+    /// Turn the mouse wheel by `delta` notches, down if positive and up if
+    /// negative.
+    fn scroll(&self, delta: i32) -> Result<()> {
+        bail!("{} can't scroll by {}", self.backend_name(), delta)
+    }
+
+    /// Set the delay between keystrokes.
+    ///
+    /// Backends that don't pace keystrokes themselves ignore it.
+    fn set_typing_delay(&self, _delay: Duration) {}
 }
 
 /// Detected display server type.
@@ -75,6 +99,7 @@ impl DisplayServer {
 }
 
 /// Create the appropriate input injector for the current display server.
+#[allow(dead_code)]
 pub fn create_injector() -> Result<Box<dyn InputInjector>> {
     create_injector_with_preference("auto")
 }
@@ -84,7 +109,15 @@ pub fn create_injector() -> Result<Box<dyn InputInjector>> {
 /// - "auto": Auto-detect display server
 /// - "x11": Force X11 backend
 /// - "wayland": Force Wayland backend
+/// - "atspi": Type text through AT-SPI, with the auto-detected backend for
+///   keys and as fallback
 pub fn create_injector_with_preference(preference: &str) -> Result<Box<dyn InputInjector>> {
+    if preference.eq_ignore_ascii_case("atspi") {
+        let fallback = create_injector_with_preference("auto")?;
+        let bus = AtspiBus::connect().map(|bus| Box::new(bus) as Box<dyn AccessibleText>);
+        return Ok(atspi_or_fallback(bus, fallback));
+    }
+
     let display_server = match preference.to_lowercase().as_str() {
         "x11" => DisplayServer::X11,
         "wayland" => DisplayServer::Wayland,
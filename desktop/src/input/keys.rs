@@ -14,6 +14,8 @@
 
 //! Key and modifier definitions.
 
+use std::fmt;
+
 /// Keyboard modifiers.
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -45,6 +47,27 @@ impl Modifier {
             Modifier::Super => "LEFTMETA",
         }
     }
+
+    /// Parse a modifier name (case-insensitive), e.g. "ctrl" or "Super".
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "ctrl" | "control" => Some(Modifier::Ctrl),
+            "alt" => Some(Modifier::Alt),
+            "shift" => Some(Modifier::Shift),
+            "super" | "meta" | "win" => Some(Modifier::Super),
+            _ => None,
+        }
+    }
+
+    /// Get the display name of this modifier.
+    pub fn name(self) -> &'static str {
+        match self {
+            Modifier::Ctrl => "ctrl",
+            Modifier::Alt => "alt",
+            Modifier::Shift => "shift",
+            Modifier::Super => "super",
+        }
+    }
 }
 
 /// Special keys.
@@ -84,6 +107,7 @@ pub enum Key {
     Backspace,
     Tab,
     Escape,
+    CapsLock,
     Space,
     Delete,
     Home,
@@ -146,6 +170,7 @@ impl Key {
             Key::Backspace => EKey::Backspace,
             Key::Tab => EKey::Tab,
             Key::Escape => EKey::Escape,
+            Key::CapsLock => EKey::CapsLock,
             Key::Space => EKey::Space,
             Key::Delete => EKey::Delete,
             Key::Home => EKey::Home,
@@ -204,6 +229,7 @@ impl Key {
             Key::Backspace => "BACKSPACE",
             Key::Tab => "TAB",
             Key::Escape => "ESC",
+            Key::CapsLock => "CAPSLOCK",
             Key::Space => "SPACE",
             Key::Delete => "DELETE",
             Key::Home => "HOME",
@@ -228,4 +254,229 @@ impl Key {
             Key::F12 => "F12",
         }
     }
+
+    /// Parse a key name (case-insensitive), e.g. "a", "Enter" or "F5".
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim().to_uppercase();
+        let name = match name.as_str() {
+            "RETURN" => "ENTER",
+            "ESCAPE" => "ESC",
+            "DEL" => "DELETE",
+            other => other,
+        };
+        ALL_KEYS
+            .iter()
+            .copied()
+            .find(|key| key.to_ydotool() == name)
+    }
+
+    /// Get the display name of this key.
+    pub fn name(self) -> String {
+        self.to_ydotool().to_lowercase()
+    }
+}
+
+/// All keys, used for name lookup.
+const ALL_KEYS: &[Key] = &[
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+    Key::Enter,
+    Key::Backspace,
+    Key::Tab,
+    Key::Escape,
+    Key::CapsLock,
+    Key::Space,
+    Key::Delete,
+    Key::Home,
+    Key::End,
+    Key::PageUp,
+    Key::PageDown,
+    Key::Left,
+    Key::Right,
+    Key::Up,
+    Key::Down,
+    Key::F1,
+    Key::F2,
+    Key::F3,
+    Key::F4,
+    Key::F5,
+    Key::F6,
+    Key::F7,
+    Key::F8,
+    Key::F9,
+    Key::F10,
+    Key::F11,
+    Key::F12,
+];
+
+/// A key combination: zero or more modifiers plus a key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyCombo {
+    /// Modifiers held while the key is pressed.
+    pub modifiers: Vec<Modifier>,
+    /// The key to press.
+    pub key: Key,
+}
+
+impl KeyCombo {
+    /// Parse a combination like "ctrl+shift+s" (case-insensitive).
+    ///
+    /// The last part is the key; all preceding parts must be modifiers.
+    pub fn parse(s: &str) -> Option<Self> {
+        let parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let (key, modifiers) = parts.split_last()?;
+
+        let key = Key::parse(key)?;
+        let mut parsed = Vec::new();
+        for name in modifiers {
+            let modifier = Modifier::parse(name)?;
+            if !parsed.contains(&modifier) {
+                parsed.push(modifier);
+            }
+        }
+
+        Some(Self {
+            modifiers: parsed,
+            key,
+        })
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for modifier in &self.modifiers {
+            write!(f, "{}+", modifier.name())?;
+        }
+        write!(f, "{}", self.key.name())
+    }
+}
+
+/// Mouse buttons.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+impl MouseButton {
+    /// Get the enigo button.
+    #[cfg(feature = "x11")]
+    pub fn to_enigo(self) -> enigo::Button {
+        match self {
+            MouseButton::Left => enigo::Button::Left,
+            MouseButton::Right => enigo::Button::Right,
+            MouseButton::Middle => enigo::Button::Middle,
+        }
+    }
+
+    /// Get the ydotool button code for a click (press and release).
+    pub fn to_ydotool(self) -> &'static str {
+        match self {
+            MouseButton::Left => "0xC0",
+            MouseButton::Right => "0xC1",
+            MouseButton::Middle => "0xC2",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_parse() {
+        assert_eq!(Key::parse("a"), Some(Key::A));
+        assert_eq!(Key::parse("Enter"), Some(Key::Enter));
+        assert_eq!(Key::parse("return"), Some(Key::Enter));
+        assert_eq!(Key::parse("esc"), Some(Key::Escape));
+        assert_eq!(Key::parse("PageDown"), Some(Key::PageDown));
+        assert_eq!(Key::parse("f12"), Some(Key::F12));
+        assert_eq!(Key::parse("CapsLock"), Some(Key::CapsLock));
+        assert_eq!(Key::parse("nope"), None);
+    }
+
+    #[test]
+    fn test_mouse_button_mapping() {
+        assert_eq!(MouseButton::Left.to_ydotool(), "0xC0");
+        assert_eq!(MouseButton::Right.to_ydotool(), "0xC1");
+    }
+
+    #[test]
+    fn test_caps_lock_mapping() {
+        assert_eq!(Key::CapsLock.to_ydotool(), "CAPSLOCK");
+        assert_eq!(Key::CapsLock.name(), "capslock");
+    }
+
+    #[cfg(feature = "x11")]
+    #[test]
+    fn test_caps_lock_enigo_mapping() {
+        assert_eq!(Key::CapsLock.to_enigo(), enigo::Key::CapsLock);
+        assert_eq!(Key::Enter.to_enigo(), enigo::Key::Return);
+    }
+
+    #[test]
+    fn test_key_combo_parse() {
+        assert_eq!(
+            KeyCombo::parse("ctrl+shift+s"),
+            Some(KeyCombo {
+                modifiers: vec![Modifier::Ctrl, Modifier::Shift],
+                key: Key::S,
+            })
+        );
+        assert_eq!(
+            KeyCombo::parse(" Super + Shift + V "),
+            Some(KeyCombo {
+                modifiers: vec![Modifier::Super, Modifier::Shift],
+                key: Key::V,
+            })
+        );
+        assert_eq!(
+            KeyCombo::parse("f5"),
+            Some(KeyCombo {
+                modifiers: vec![],
+                key: Key::F5,
+            })
+        );
+    }
+
+    #[test]
+    fn test_key_combo_parse_invalid() {
+        assert_eq!(KeyCombo::parse(""), None);
+        assert_eq!(KeyCombo::parse("ctrl+"), None);
+        assert_eq!(KeyCombo::parse("ctrl+shift"), None);
+        assert_eq!(KeyCombo::parse("hyper+a"), None);
+    }
+
+    #[test]
+    fn test_key_combo_display_roundtrip() {
+        let combo = KeyCombo::parse("Control+Alt+Delete").unwrap();
+        assert_eq!(combo.to_string(), "ctrl+alt+delete");
+        assert_eq!(KeyCombo::parse(&combo.to_string()), Some(combo));
+    }
 }
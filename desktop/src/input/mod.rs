@@ -16,12 +16,17 @@
 //!
 //! Handles simulating keyboard input on X11 and Wayland.
 
+mod atspi;
+mod hotkey;
 mod injector;
 mod keys;
 mod wayland;
+mod window;
 
 #[cfg(feature = "x11")]
 mod x11;
 
-pub use injector::{create_injector, InputInjector};
-pub use keys::{Key, Modifier};
+pub use hotkey::{parse_hotkey, spawn_hotkey_listener};
+pub use injector::{create_injector, create_injector_with_preference, InputInjector};
+pub use keys::{Key, KeyCombo, Modifier, MouseButton};
+pub use window::{active_window_title, ActiveWindow, WindowSource};
@@ -16,15 +16,22 @@
 
 use anyhow::{anyhow, Result};
 use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 use tracing::{debug, warn};
 
-use super::keys::{Key, Modifier};
+use super::keys::{Key, Modifier, MouseButton};
 use super::InputInjector;
 
+/// Delay between keystrokes in milliseconds.
+const KEYSTROKE_DELAY_MS: u64 = 10;
+
 /// Wayland input injector using ydotool.
 pub struct WaylandInjector {
     /// Whether ydotool daemon is available.
     ydotool_available: bool,
+    /// Delay between keystrokes in milliseconds (changeable at runtime).
+    delay_ms: AtomicU64,
 }
 
 impl WaylandInjector {
@@ -52,7 +59,10 @@ impl WaylandInjector {
             warn!("ydotoold daemon not running. Start with: sudo systemctl start ydotool");
         }
 
-        Ok(Self { ydotool_available })
+        Ok(Self {
+            ydotool_available,
+            delay_ms: AtomicU64::new(KEYSTROKE_DELAY_MS),
+        })
     }
 
     /// Run ydotool command.
@@ -61,7 +71,12 @@ impl WaylandInjector {
             return Err(anyhow!("ydotool not available"));
         }
 
-        debug!("Running: ydotool {:?}", args);
+        // Arguments can hold dictated text, so only the subcommand is logged
+        debug!(
+            "Running: ydotool {} with {} arguments",
+            args.first().copied().unwrap_or_default(),
+            args.len().saturating_sub(1)
+        );
 
         let output = Command::new("ydotool")
             .args(args)
@@ -85,8 +100,9 @@ impl InputInjector for WaylandInjector {
     fn type_text(&self, text: &str) -> Result<()> {
         debug!("Typing text: {} chars", text.len());
 
-        // ydotool type command
-        self.run_ydotool(&["type", "--clearmodifiers", text])
+        // ydotool type command, paced by ydotool itself
+        let delay_ms = self.delay_ms.load(Ordering::Relaxed).to_string();
+        self.run_ydotool(&["type", "--key-delay", &delay_ms, "--clearmodifiers", text])
     }
 
     fn press_key(&self, key: Key) -> Result<()> {
@@ -109,4 +125,38 @@ impl InputInjector for WaylandInjector {
 
         self.run_ydotool(&["key", &combo])
     }
+
+    fn click(&self, button: MouseButton) -> Result<()> {
+        debug!("Clicking {:?}", button);
+
+        self.run_ydotool(&["click", button.to_ydotool()])
+    }
+
+    fn double_click(&self, button: MouseButton) -> Result<()> {
+        debug!("Double-clicking {:?}", button);
+
+        self.run_ydotool(&[
+            "click",
+            "--repeat",
+            "2",
+            "--next-delay",
+            "50",
+            button.to_ydotool(),
+        ])
+    }
+
+    fn scroll(&self, delta: i32) -> Result<()> {
+        debug!("Scrolling by {}", delta);
+
+        // The wheel axis counts up as positive
+        let notches = (-delta).to_string();
+        self.run_ydotool(&["mousemove", "--wheel", "-x", "0", "-y", &notches])
+    }
+
+    fn set_typing_delay(&self, delay: Duration) {
+        let delay_ms = delay.as_millis() as u64;
+        if self.delay_ms.swap(delay_ms, Ordering::Relaxed) != delay_ms {
+            debug!("Wayland: Typing delay set to {}ms", delay_ms);
+        }
+    }
 }
@@ -0,0 +1,92 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Active window lookup.
+
+/// Title of the focused window.
+///
+/// Reads `_NET_ACTIVE_WINDOW` from the X server. Returns `None` on Wayland,
+/// without the `x11` feature, or when the window manager doesn't say.
+/// Blocks on the X server, so call it off the async threads.
+pub fn active_window_title() -> Option<String> {
+    #[cfg(feature = "x11")]
+    {
+        if std::env::var_os("DISPLAY").is_some() {
+            return x11::active_window_title();
+        }
+    }
+    None
+}
+
+/// Tells which window has the focus.
+pub trait WindowSource: Send + Sync {
+    /// Title of the focused window, `None` if unknown. May block.
+    fn active_window(&self) -> Option<String>;
+}
+
+/// Window source asking the display server, see [`active_window_title`].
+pub struct ActiveWindow;
+
+impl WindowSource for ActiveWindow {
+    fn active_window(&self) -> Option<String> {
+        active_window_title()
+    }
+}
+
+#[cfg(feature = "x11")]
+mod x11 {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{Atom, AtomEnum, ConnectionExt, Window};
+    use x11rb::rust_connection::RustConnection;
+
+    pub fn active_window_title() -> Option<String> {
+        let (conn, screen_num) = x11rb::connect(None).ok()?;
+        let root = conn.setup().roots[screen_num].root;
+
+        let active = atom(&conn, b"_NET_ACTIVE_WINDOW")?;
+        let window = conn
+            .get_property(false, root, active, AtomEnum::WINDOW, 0, 1)
+            .ok()?
+            .reply()
+            .ok()?
+            .value32()?
+            .next()
+            .filter(|&w| w != 0)?;
+
+        let net_wm_name = atom(&conn, b"_NET_WM_NAME")?;
+        let utf8 = atom(&conn, b"UTF8_STRING")?;
+        title(&conn, window, net_wm_name, utf8).or_else(|| {
+            title(
+                &conn,
+                window,
+                AtomEnum::WM_NAME.into(),
+                AtomEnum::STRING.into(),
+            )
+        })
+    }
+
+    fn atom(conn: &RustConnection, name: &[u8]) -> Option<Atom> {
+        Some(conn.intern_atom(false, name).ok()?.reply().ok()?.atom)
+    }
+
+    fn title(conn: &RustConnection, window: Window, property: Atom, kind: Atom) -> Option<String> {
+        let reply = conn
+            .get_property(false, window, property, kind, 0, 1024)
+            .ok()?
+            .reply()
+            .ok()?;
+        let title = String::from_utf8_lossy(&reply.value).into_owned();
+        (!title.is_empty()).then_some(title)
+    }
+}
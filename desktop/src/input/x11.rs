@@ -15,13 +15,14 @@
 //! X11 text injection using enigo.
 
 use anyhow::Result;
-use enigo::{Direction, Enigo, Keyboard, Settings};
+use enigo::{Axis, Direction, Enigo, Keyboard, Mouse, Settings};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Mutex;
 use std::thread;
 use std::time::Duration;
 use tracing::{debug, info, trace};
 
-use super::keys::{Key, Modifier};
+use super::keys::{Key, Modifier, MouseButton};
 use super::InputInjector;
 
 /// Delay between keystrokes in milliseconds.
@@ -30,7 +31,8 @@ const KEYSTROKE_DELAY_MS: u64 = 10;
 /// X11 input injector using enigo.
 pub struct X11Injector {
     enigo: Mutex<Enigo>,
-    delay: Duration,
+    /// Delay between keystrokes in milliseconds (changeable at runtime).
+    delay_ms: AtomicU64,
 }
 
 impl X11Injector {
@@ -39,14 +41,15 @@ impl X11Injector {
         let enigo = Enigo::new(&Settings::default())?;
         Ok(Self {
             enigo: Mutex::new(enigo),
-            delay: Duration::from_millis(KEYSTROKE_DELAY_MS),
+            delay_ms: AtomicU64::new(KEYSTROKE_DELAY_MS),
         })
     }
 
     /// Small delay between operations.
     fn pause(&self) {
-        if !self.delay.is_zero() {
-            thread::sleep(self.delay);
+        let delay_ms = self.delay_ms.load(Ordering::Relaxed);
+        if delay_ms > 0 {
+            thread::sleep(Duration::from_millis(delay_ms));
         }
     }
 }
@@ -109,4 +112,43 @@ impl InputInjector for X11Injector {
 
         Ok(())
     }
+
+    fn click(&self, button: MouseButton) -> Result<()> {
+        debug!("Clicking {:?}", button);
+
+        let mut enigo = self.enigo.lock().unwrap();
+        enigo.button(button.to_enigo(), Direction::Click)?;
+        self.pause();
+
+        Ok(())
+    }
+
+    fn double_click(&self, button: MouseButton) -> Result<()> {
+        debug!("Double-clicking {:?}", button);
+
+        // Both clicks without a pause, so they count as a double click
+        let mut enigo = self.enigo.lock().unwrap();
+        enigo.button(button.to_enigo(), Direction::Click)?;
+        enigo.button(button.to_enigo(), Direction::Click)?;
+        self.pause();
+
+        Ok(())
+    }
+
+    fn scroll(&self, delta: i32) -> Result<()> {
+        debug!("Scrolling by {}", delta);
+
+        let mut enigo = self.enigo.lock().unwrap();
+        enigo.scroll(delta, Axis::Vertical)?;
+        self.pause();
+
+        Ok(())
+    }
+
+    fn set_typing_delay(&self, delay: Duration) {
+        let delay_ms = delay.as_millis() as u64;
+        if self.delay_ms.swap(delay_ms, Ordering::Relaxed) != delay_ms {
+            debug!("X11: Typing delay set to {}ms", delay_ms);
+        }
+    }
 }
@@ -15,11 +15,23 @@
 //! Speech2Prompt Desktop Library
 
 pub mod bluetooth;
+pub mod cli;
 pub mod commands;
 pub mod config;
-pub mod crypto;
+pub mod control;
 pub mod events;
+pub mod injection;
 pub mod input;
+pub mod logging;
+pub mod pairing;
+pub mod qr;
+pub mod scripting;
+pub mod socket;
 pub mod state;
+pub mod status;
 pub mod storage;
 pub mod ui;
+pub mod webhook;
+
+// Kept at its old path for existing users
+pub use speech2prompt_protocol::crypto;
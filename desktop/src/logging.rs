@@ -0,0 +1,180 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Logging setup.
+//!
+//! Logs always go to stderr. With `[logging] file = true` they are also
+//! written to daily rotated files, which is handy for intermittent BLE
+//! problems that are gone by the time anyone looks at a terminal.
+//!
+//! Dictated text is logged through [`redacted`], which shows only its length
+//! unless redaction is off or trace logging is on.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+
+use crate::config::LoggingConfig;
+
+/// Log file name; the date is appended on rotation.
+pub const LOG_FILE_NAME: &str = "speech2prompt.log";
+
+/// Crates whose logs go to the file at the configured level. Others only
+/// log warnings there.
+const OWN_CRATES: &[&str] = &["speech2prompt_desktop", "speech2prompt_protocol"];
+
+/// Whether dictated text is left out of logs.
+static REDACT_CONTENT: AtomicBool = AtomicBool::new(true);
+
+/// Where and how verbosely to write log files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileLog {
+    /// Directory the daily files are written to.
+    pub dir: PathBuf,
+    /// Most verbose level written.
+    pub level: LevelFilter,
+}
+
+/// File logging to set up, or `None` to log to stderr only.
+pub fn file_log(config: &LoggingConfig, data_dir: &Path) -> Option<FileLog> {
+    if !config.file {
+        return None;
+    }
+    Some(FileLog {
+        dir: data_dir.join("logs"),
+        // Validated when the config is loaded
+        level: LevelFilter::from_str(&config.file_level).unwrap_or(LevelFilter::DEBUG),
+    })
+}
+
+/// Install the global subscriber.
+///
+/// `stderr_level` applies to this crate on stderr, on top of `RUST_LOG`.
+/// Keep the returned guard until exit, dropping it flushes the log file.
+pub fn init(stderr_level: &str, file: Option<FileLog>) -> Option<WorkerGuard> {
+    let stderr_filter = EnvFilter::from_default_env().add_directive(
+        format!("speech2prompt_desktop={}", stderr_level)
+            .parse()
+            .unwrap_or_else(|_| LevelFilter::INFO.into()),
+    );
+
+    let (file_layer, guard) = match file {
+        Some(file) => {
+            let appender = tracing_appender::rolling::daily(&file.dir, LOG_FILE_NAME);
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let layer = tracing_subscriber::fmt::layer()
+                .with_writer(writer)
+                .with_ansi(false)
+                .with_filter(file_filter(file.level));
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(stderr_filter))
+        .with(file_layer)
+        .init();
+    guard
+}
+
+/// Set whether dictated text is left out of logs below trace level.
+pub fn set_redact_content(redact: bool) {
+    REDACT_CONTENT.store(redact, Ordering::Relaxed);
+}
+
+/// Dictated text for a log message, see [`redacted`].
+pub struct Redacted<'a>(&'a str);
+
+impl fmt::Display for Redacted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show =
+            !REDACT_CONTENT.load(Ordering::Relaxed) || tracing::enabled!(tracing::Level::TRACE);
+        f.write_str(&redact(self.0, show))
+    }
+}
+
+/// Wrap dictated text for logging.
+pub fn redacted(text: &str) -> Redacted<'_> {
+    Redacted(text)
+}
+
+/// `text` itself if `show`, otherwise just its length.
+fn redact(text: &str, show: bool) -> Cow<'_, str> {
+    if show {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(format!("<{} chars>", text.chars().count()))
+    }
+}
+
+/// Filter for the log file: `level` for our crates, warnings for the rest.
+fn file_filter(level: LevelFilter) -> Targets {
+    OWN_CRATES.iter().fold(
+        Targets::new().with_default(LevelFilter::WARN),
+        |targets, name| targets.with_target(*name, level),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::Level;
+
+    #[test]
+    fn test_stderr_only_by_default() {
+        let config = LoggingConfig::default();
+        assert_eq!(file_log(&config, Path::new("/data")), None);
+    }
+
+    #[test]
+    fn test_file_log_under_data_dir() {
+        let config = LoggingConfig {
+            file: true,
+            file_level: "trace".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            file_log(&config, Path::new("/data")),
+            Some(FileLog {
+                dir: PathBuf::from("/data/logs"),
+                level: LevelFilter::TRACE,
+            })
+        );
+    }
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(redact("my password", false), "<11 chars>");
+        assert_eq!(redact("héllo", false), "<5 chars>");
+        assert_eq!(redact("my password", true), "my password");
+        // Tests run without a subscriber, so trace is off
+        assert_eq!(redacted("secret").to_string(), "<6 chars>");
+    }
+
+    #[test]
+    fn test_file_filter() {
+        let filter = file_filter(LevelFilter::DEBUG);
+        assert!(filter.would_enable("speech2prompt_desktop::events", &Level::DEBUG));
+        assert!(filter.would_enable("speech2prompt_protocol", &Level::DEBUG));
+        assert!(!filter.would_enable("speech2prompt_desktop", &Level::TRACE));
+        assert!(!filter.would_enable("bluer", &Level::INFO));
+        assert!(filter.would_enable("bluer", &Level::WARN));
+    }
+}
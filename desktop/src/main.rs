@@ -15,59 +15,106 @@
 //! Speech2Prompt Desktop Application
 
 mod bluetooth;
+mod cli;
 mod commands;
 mod config;
-mod crypto;
+mod control;
 mod events;
+mod injection;
 mod input;
+mod logging;
+mod pairing;
+mod qr;
+mod scripting;
+mod socket;
 mod state;
+mod status;
 mod storage;
 mod ui;
+mod webhook;
 
 use anyhow::Result;
+use clap::Parser;
 use gtk4::glib;
 use gtk4::prelude::*;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 use tracing::{debug, error, info, warn};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use bluetooth::GattServer;
 use events::EventProcessor;
-use state::AppState;
-use storage::VoiceCommandStore;
+use pairing::PairingDecision;
+use state::{AppState, StateChange};
+use storage::{CaseFolding, PairedDeviceStore, VoiceCommandStore};
+
+/// Environment variable with the passphrase for `--export-devices` and
+/// `--import-devices`. Prompted for on the terminal when unset.
+const BACKUP_PASSPHRASE_ENV: &str = "SPEECH2PROMPT_BACKUP_PASSPHRASE";
 
 /// Request to show confirmation dialog for pairing.
 #[derive(Debug, Clone)]
 struct PairingRequest {
     device_id: String,
     device_name: Option<String>,
+    public_key: String,
+    scanned: bool,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let cli = cli::Cli::parse();
+
+    // Load configuration first, it says where log files go
+    let mut config = config::Config::load()?;
+    config.apply_overrides(&cli.overrides());
+    std::fs::create_dir_all(&config.data_dir)?;
+
     // Initialize logging
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
-        .with(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive("speech2prompt_desktop=info".parse().unwrap()),
-        )
-        .init();
+    let log_level = cli.log_level.as_deref().unwrap_or("info");
+    let file_log = logging::file_log(&config.logging, &config.data_dir);
+    let log_dir = file_log.as_ref().map(|file| file.dir.clone());
+    let _log_guard = logging::init(log_level, file_log);
+    logging::set_redact_content(config.logging.redact_content);
 
     info!(
         "Starting Speech2Prompt Desktop v{}...",
         env!("CARGO_PKG_VERSION")
     );
-
-    // Load configuration
-    let config = config::Config::load()?;
+    if let Some(dir) = log_dir {
+        info!("Writing logs to {:?}", dir);
+    }
     info!("Configuration loaded");
 
+    // Backup and restore run instead of the app
+    if cli.export_devices.is_some() || cli.import_devices.is_some() {
+        return backup_paired_devices(&cli, &config.data_dir);
+    }
+
+    let headless = config.headless;
+
+    // Follow edits to config.toml for settings that can change at runtime
+    let live_config = match config::LiveConfig::new_with_watcher(config.clone(), &config::Config::path(), cli.overrides()) {
+        Ok(live_config) => Arc::new(live_config),
+        Err(e) => {
+            warn!("Failed to watch config file: {}. Changes need a restart.", e);
+            Arc::new(config::LiveConfig::new(config.clone()))
+        }
+    };
+    if headless {
+        info!("Running headless (no tray or dialogs)");
+    }
+
     // Initialize voice command store with file watcher
     let voice_command_store = match VoiceCommandStore::new_with_watcher(&config.data_dir) {
         Ok(store) => {
-            let store = Arc::new(store);
+            let store = Arc::new(
+                store
+                    .with_phonetic_matching(config.input.phonetic_matching)
+                    .with_case_folding(CaseFolding::for_locale(&config.input.locale))
+                    .with_default_phrases(&config.input.default_phrases),
+            );
             info!("Voice command store initialized at {:?}", store.config_path());
             Some(store)
         }
@@ -77,100 +124,259 @@ async fn main() -> Result<()> {
         }
     };
 
-    // Initialize GTK (required for PIN dialog)
-    gtk4::init().expect("Failed to initialize GTK");
-    let gtk_app = gtk4::Application::builder()
-        .application_id("com.speech2prompt.desktop")
-        .build();
-    // Register the application so windows can be created
-    gtk_app.register(None::<&gtk4::gio::Cancellable>)?;
-    info!("GTK initialized");
+    // Devices the user approved before (for auto_accept)
+    let paired_devices = match PairedDeviceStore::new(&config.data_dir) {
+        Ok(store) => Some(store),
+        Err(e) => {
+            warn!("Failed to load paired devices: {}. Every pairing will need confirmation.", e);
+            None
+        }
+    };
+
+    // Initialize GTK (required for dialogs), skipped when headless
+    let gtk_app = if headless {
+        None
+    } else {
+        gtk4::init().expect("Failed to initialize GTK");
+        let gtk_app = gtk4::Application::builder()
+            .application_id("com.speech2prompt.desktop")
+            .build();
+        // Register the application so windows can be created
+        gtk_app.register(None::<&gtk4::gio::Cancellable>)?;
+        info!("GTK initialized");
+        Some(gtk_app)
+    };
+
+    // Follow the desktop dark/light preference (kept alive for runtime changes)
+    let _theme_watcher = if headless { None } else { ui::follow_system_theme() };
 
     // Initialize input injector
-    let injector = input::create_injector()?;
+    let injector = input::create_injector_with_preference(&config.input.prefer_backend)?;
     info!("Input injector: {}", injector.backend_name());
 
     // Create application state
     let state = AppState::new();
 
+    // The phone reconnects by itself; with auto_accept the last paired device
+    // gets its session back without asking
+    let last_device = paired_devices.as_ref().and_then(|store| store.last_device());
+    if let Some(device) = pairing::expected_reconnect(config.bluetooth.auto_accept, last_device.as_ref()) {
+        info!("Waiting for {} to reconnect", device);
+        state.set_reconnect_device(Some(device));
+    }
+
     // Initialize BLE GATT server
     info!("Initializing BLE GATT server...");
     let (gatt_event_tx, gatt_event_rx) = tokio::sync::mpsc::channel::<bluetooth::ConnectionEvent>(32);
-    let gatt_server = Arc::new(Mutex::new(GattServer::new(gatt_event_tx).await?));
-    {
-        let mut server = gatt_server.lock().await;
-        server.set_name(&config.bluetooth.device_name).await?;
-        server.start().await?;
-    }
+    let gatt_server = start_gatt_server(gatt_event_tx.clone(), &config.bluetooth).await?;
+    let adapter_watch = bluetooth::AdapterWatch::new(gatt_server.adapter_name());
+    let gatt_server = Arc::new(Mutex::new(gatt_server));
     info!(
         "BLE GATT server started and advertising as '{}'",
         config.bluetooth.device_name
     );
 
+    // Rebuild the server when the adapter comes back after being unplugged
+    match bluetooth::watch_adapters().await {
+        Ok(adapter_events) => {
+            tokio::spawn(recover_unplugged_adapter(
+                adapter_events,
+                adapter_watch,
+                gatt_server.clone(),
+                gatt_event_tx.clone(),
+                config.bluetooth.clone(),
+                state.clone(),
+            ));
+        }
+        Err(e) => warn!("Not watching for Bluetooth adapter changes: {}", e),
+    }
+
+    // Let the phone show progress while text or a command is injected
+    tokio::spawn(report_busy(state.clone(), gatt_server.clone()));
+
+    // Local control socket feeding the same event channel
+    let _control_server = if config.control_socket {
+        let path = control::default_socket_path(&config.data_dir);
+        match control::ControlServer::bind(&path, gatt_event_tx) {
+            Ok(server) => Some(server),
+            Err(e) => {
+                warn!("Control socket unavailable: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let _status_server = if config.status_socket {
+        let path = status::default_socket_path(&config.data_dir);
+        match status::StatusServer::bind(&path, state.clone()) {
+            Ok(server) => Some(server),
+            Err(e) => {
+                warn!("Status socket unavailable: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
     // Create channel for pairing requests
     let (pairing_tx, mut pairing_rx) = tokio::sync::mpsc::channel::<PairingRequest>(8);
+    // Answers to pairing requests, from prompts that run alongside the main loop
+    let (answer_tx, mut answer_rx) =
+        tokio::sync::mpsc::unbounded_channel::<(PairingRequest, ui::ConfirmationResult)>();
 
     // Create event processor
     let processor = if let Some(store) = voice_command_store.clone() {
         EventProcessor::with_voice_commands(injector, store, state.clone())
     } else {
         EventProcessor::new(injector)
+    }
+    .with_timeouts(
+        Duration::from_millis(config.input.look_ahead_ms),
+        Duration::from_millis(config.input.stale_timeout_ms),
+    )
+    .with_look_ahead(!config.input.disable_look_ahead)
+    .with_shell_commands(config.shell.clone())
+    .with_apps(config.apps.clone())
+    .with_command_cooldown(Duration::from_millis(config.input.command_cooldown_ms))
+    .with_rate_limit(config.input.max_chars_per_sec)
+    .with_duplicate_text_window(Duration::from_millis(config.input.duplicate_text_window_ms))
+    .with_date_formats(&config.input.date_format, &config.input.time_format)
+    .with_duplicate_line_keys(&config.input.duplicate_line_keys)
+    .with_focus_check(
+        events::FocusCheck::from_config(&config.input.focus_check),
+        Arc::new(input::ActiveWindow),
+    )
+    .with_notifier(ui::create_notifier(config.notifications.enabled));
+    if config.shell.enabled {
+        warn!("Shell commands enabled: {:?}", config.shell.allowed.keys().collect::<Vec<_>>());
+    }
+    let processor = if config.webhook.url.trim().is_empty() {
+        processor
+    } else {
+        let timeout = Duration::from_millis(config.webhook.timeout_ms);
+        match webhook::Webhook::new(&config.webhook.url, timeout) {
+            Ok(webhook) => {
+                info!("Reporting text and commands to {}", config.webhook.url.trim());
+                processor.with_webhook(webhook)
+            }
+            Err(e) => {
+                warn!("Webhook disabled: {:#}", e);
+                processor
+            }
+        }
     };
 
-    // Start system tray
-    let (mut action_rx, tray_handle) = ui::run_tray(state.clone())?;
+    // Start system tray, it redraws itself when the state changes
+    let (action_tx, mut action_rx) = tokio::sync::mpsc::unbounded_channel();
+    if !headless {
+        ui::run_tray(state.clone(), action_tx.clone())?;
+    }
+
+    // Global hotkey toggles input the same way as the tray menu
+    let hotkey = config.input.toggle_hotkey.trim();
+    if !hotkey.is_empty() {
+        match input::parse_hotkey(hotkey) {
+            Ok(combo) => {
+                let hotkey_tx = action_tx.clone();
+                if let Err(e) = input::spawn_hotkey_listener(combo, move || {
+                    let _ = hotkey_tx.send(ui::TrayAction::ToggleInput);
+                }) {
+                    warn!("Global hotkey unavailable: {}", e);
+                }
+            }
+            Err(e) => warn!("Ignoring toggle_hotkey: {}", e),
+        }
+    }
+
+    // Desktop notifications for connection events
+    let notifier: Arc<dyn ui::Notifier> =
+        Arc::from(ui::create_notifier(config.notifications.enabled));
     
-    info!("Ready. System tray active.");
+    if headless {
+        info!("Ready.");
+    } else {
+        info!("Ready. System tray active.");
+    }
 
     // Handle BLE GATT events
     let state_gatt = state.clone();
     let mut gatt_event_rx_state = gatt_event_rx;
-    let tray_handle_gatt = tray_handle.clone();
+    let live_config_gatt = live_config.clone();
+    let flush_interval_ms = config.input.flush_interval_ms;
+    let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel::<()>();
     
-    tokio::spawn(async move {
+    let gatt_task = tokio::spawn(async move {
         let mut processor_gatt = processor;
         
         // Periodic flush interval for look-ahead and stale word handling
-        let mut flush_interval = tokio::time::interval(tokio::time::Duration::from_millis(50));
+        let mut flush_interval = tokio::time::interval(Duration::from_millis(flush_interval_ms));
         
         loop {
             tokio::select! {
                 Some(event) = gatt_event_rx_state.recv() => {
                     // Sync input_enabled state before processing each event
                     processor_gatt.set_input_enabled(state_gatt.is_input_enabled());
+                    if let Err(e) = processor_gatt.set_paused(state_gatt.is_paused()).await {
+                        error!("Error replaying paused events: {}", e);
+                    }
+                    processor_gatt.set_typing_delay(live_config_gatt.read().input.typing_delay_ms);
+
+                    // BlueZ may report the same connection more than once
+                    if processor_gatt.is_duplicate_connect(&event) {
+                        debug!("Ignoring duplicate connect");
+                        continue;
+                    }
+
+                    // Notify the user about connection changes, unless the
+                    // pairing request gets its own prompt
+                    let prompted = matches!(event, bluetooth::ConnectionEvent::PairRequested { .. })
+                        && live_config_gatt.read().notifications.prompts_with_notification();
+                    if !prompted {
+                        // Showing a notification waits on D-Bus, keep it off
+                        // the async threads
+                        let notifier = notifier.clone();
+                        let event = event.clone();
+                        tokio::task::spawn_blocking(move || {
+                            ui::notify_event(notifier.as_ref(), &event)
+                        });
+                    }
                     
                     // Update state
                     match &event {
-                        bluetooth::ConnectionEvent::Connected { device_name } => {
+                        bluetooth::ConnectionEvent::Connected { device_name, .. } => {
                             info!("BLE device connected: {}", device_name);
                             state_gatt.set_connected(device_name.clone());
-                            tray_handle_gatt.update(|_| {});
                         }
                         bluetooth::ConnectionEvent::Disconnected => {
                             info!("BLE device disconnected");
                             state_gatt.set_disconnected();
-                            tray_handle_gatt.update(|_| {});
+                        }
+                        bluetooth::ConnectionEvent::Battery(level) => {
+                            debug!("Phone battery at {}%", level);
+                            state_gatt.set_battery(Some(*level));
                         }
                         bluetooth::ConnectionEvent::Error(e) => {
                             error!("BLE error: {}", e);
-                            state_gatt.set_error();
-                            tray_handle_gatt.update(|_| {});
+                            state_gatt.set_error(e.clone());
                         }
                         bluetooth::ConnectionEvent::TextReceived(text) => {
-                            debug!("BLE text received: {}", text);
+                            debug!("BLE text received: {}", logging::redacted(text));
                             state_gatt.set_last_text(text.clone());
                         }
-                        bluetooth::ConnectionEvent::WordReceived { word, seq, session } => {
-                            debug!("BLE word received: '{}' seq={:?} session={}", word, seq, session);
+                        bluetooth::ConnectionEvent::WordReceived { word, seq, session, eou } => {
+                            debug!("BLE word received: '{}' seq={:?} session={} eou={}", logging::redacted(word), seq, session, eou);
                             // Word processing is handled by event processor
                         }
-                        bluetooth::ConnectionEvent::PairRequested { device_id, device_name } => {
+                        bluetooth::ConnectionEvent::PairRequested { device_id, device_name, public_key, scanned } => {
                             info!("📱 BLE pairing requested by: {}", device_id);
                             info!("📤 Forwarding to main loop for confirmation dialog...");
                             // Send to main loop for confirmation dialog handling
                             let _ = pairing_tx.send(PairingRequest { 
                                 device_id: device_id.clone(),
                                 device_name: device_name.clone(),
+                                public_key: public_key.clone(),
+                                scanned: *scanned,
                             }).await;
                             info!("✅ Pairing request forwarded to main loop");
                         }
@@ -184,7 +390,19 @@ async fn main() -> Result<()> {
                         error!("Error processing BLE event: {}", e);
                     }
                 }
+                _ = &mut shutdown_rx => {
+                    // Type the word held back for look-ahead before exiting
+                    if let Err(e) = processor_gatt.shutdown().await {
+                        error!("Error flushing words on shutdown: {}", e);
+                    }
+                    break;
+                }
                 _ = flush_interval.tick() => {
+                    // Resume promptly even when no new event arrives
+                    if let Err(e) = processor_gatt.set_paused(state_gatt.is_paused()).await {
+                        error!("Error replaying paused events: {}", e);
+                    }
+
                     // Periodic flush of pending/stale words
                     if let Err(e) = processor_gatt.process_periodic_flush().await {
                         error!("Error during periodic flush: {}", e);
@@ -217,19 +435,24 @@ async fn main() -> Result<()> {
                         let enabled = !state.is_input_enabled();
                         state.set_input_enabled(enabled);
                         info!("Input {}", if enabled { "enabled" } else { "disabled" });
-                        tray_handle.update(|_| {});
+                    }
+                    ui::TrayAction::TogglePause => {
+                        let paused = !state.is_paused();
+                        state.set_paused(paused);
+                        info!("Processing {}", if paused { "paused" } else { "resumed" });
                     }
                     ui::TrayAction::ManageCommands => {
                         info!("Manage Commands window requested");
                         // Window will be opened and events handled in the GTK main context
                         // The manage_commands window has its own event handling via periodic refresh
-                        if let Some(store) = voice_command_store.clone() {
-                            let mut event_rx = ui::show_manage_commands_window(&gtk_app, store.clone(), state.clone());
+                        if let (Some(store), Some(gtk_app)) = (voice_command_store.clone(), gtk_app.as_ref()) {
+                            let mut event_rx = ui::show_manage_commands_window(gtk_app, store.clone(), state.clone());
                             
                             // Handle events from the manage commands window using GTK's event loop
                             let state_cmds = state.clone();
                             let store_cmds = store.clone();
                             let gtk_app_cmds = gtk_app.clone();
+                            let live_config_cmds = live_config.clone();
                             
                             // Use glib::timeout to poll the channel from the GTK thread
                             glib::timeout_add_local(std::time::Duration::from_millis(50), move || {
@@ -240,7 +463,8 @@ async fn main() -> Result<()> {
                                                 info!("Starting recording for command: {}", command);
                                                 state_cmds.start_recording(command.clone());
                                                 // Show recording dialog immediately (we're on GTK thread)
-                                                ui::show_recording_dialog(&gtk_app_cmds, &command, state_cmds.clone());
+                                                let recording_timeout_secs = live_config_cmds.read().input.recording_timeout_secs;
+                                                ui::show_recording_dialog(&gtk_app_cmds, &command, state_cmds.clone(), recording_timeout_secs);
                                             }
                                             ui::ManageCommandsEvent::CancelRecording => {
                                                 info!("Recording cancelled");
@@ -252,6 +476,18 @@ async fn main() -> Result<()> {
                                                     error!("Failed to revert command: {}", e);
                                                 }
                                             }
+                                            ui::ManageCommandsEvent::SetPhrase(command, phrase) => {
+                                                info!("Setting phrase '{}' for command '{}'", phrase, command);
+                                                if let Err(e) = store_cmds.set_phrase(&command, &phrase) {
+                                                    error!("Failed to set phrase: {}", e);
+                                                }
+                                            }
+                                            ui::ManageCommandsEvent::AddCommand { name, keys, phrase } => {
+                                                info!("Adding command '{}' ({}) with phrase '{}'", name, keys, phrase);
+                                                if let Err(e) = store_cmds.add_custom_command(&name, &keys, &phrase) {
+                                                    error!("Failed to add command: {}", e);
+                                                }
+                                            }
                                         }
                                         glib::ControlFlow::Continue
                                     }
@@ -267,8 +503,48 @@ async fn main() -> Result<()> {
                                 }
                             });
                         } else {
-                            warn!("Voice command store not available");
+                            warn!("Voice command store or GTK not available");
+                        }
+                    }
+                    ui::TrayAction::PairWithQr => {
+                        info!("QR code pairing requested");
+                        if let Some(gtk_app) = gtk_app.as_ref() {
+                            let payload = gatt_server.lock().await.start_qr_pairing().await;
+                            match ui::show_qr_pairing_window(gtk_app, &payload) {
+                                Ok(closed) => {
+                                    // The QR code is only valid while shown
+                                    let gatt_server = gatt_server.clone();
+                                    tokio::spawn(async move {
+                                        let _ = closed.await;
+                                        gatt_server.lock().await.cancel_qr_pairing(&payload).await;
+                                    });
+                                }
+                                Err(e) => {
+                                    error!("Failed to show pairing QR code: {}", e);
+                                    gatt_server.lock().await.cancel_qr_pairing(&payload).await;
+                                }
+                            }
+                        } else {
+                            warn!("GTK not available");
+                        }
+                    }
+                    ui::TrayAction::ToggleDiscoverable => {
+                        let mut server = gatt_server.lock().await;
+                        if server.is_advertising() {
+                            server.stop_advertising();
+                        } else if let Err(e) = server.start_advertising().await {
+                            error!("Failed to start advertising: {}", e);
+                        }
+                        state.set_discoverable(server.is_advertising());
+                    }
+                    ui::TrayAction::RestartBluetooth => {
+                        info!("Bluetooth restart requested");
+                        let mut server = gatt_server.lock().await;
+                        if let Err(e) = server.restart().await {
+                            error!("Failed to restart the GATT server: {}", e);
+                            state.set_error(format!("Bluetooth restart failed: {}", e));
                         }
+                        state.set_discoverable(server.is_advertising());
                     }
                     ui::TrayAction::Quit => {
                         info!("Quit requested");
@@ -277,47 +553,89 @@ async fn main() -> Result<()> {
                 }
             }
             Some(request) = pairing_rx.recv() => {
-                let display_name = request.device_name.unwrap_or_else(|| request.device_id.clone());
+                let display_name = request.device_name.clone().unwrap_or_else(|| request.device_id.clone());
                 info!("🔔 Received pairing request in main loop for: {}", display_name);
-                info!("🪟 Showing confirmation dialog...");
-                
-                // Show confirmation dialog
-                let mut confirm_rx = ui::show_confirmation_dialog(&gtk_app, &display_name);
-                info!("✅ Confirmation dialog shown, waiting for user response...");
-                
-                // Process GTK events until dialog closes
-                let result = loop {
-                    while gtk4::glib::MainContext::default().pending() {
-                        gtk4::glib::MainContext::default().iteration(false);
+
+                let known = paired_devices
+                    .as_ref()
+                    .map(|store| store.is_known(&request.device_id))
+                    .unwrap_or(false);
+                let decision = pairing::decide_pairing(headless, live_config.read().bluetooth.auto_accept, known, request.scanned);
+
+                let result = match (decision, gtk_app.as_ref()) {
+                    (PairingDecision::Prompt, Some(gtk_app)) => {
+                        let dialog_timeout = live_config.read().bluetooth.dialog_timeout();
+                        let confirm_rx = if live_config.read().notifications.prompts_with_notification() {
+                            info!("🔔 Asking for approval with a notification...");
+                            ui::ask_pairing(
+                                Arc::new(ui::DesktopNotifier),
+                                &display_name,
+                                &request.device_id,
+                                &request.public_key,
+                                dialog_timeout,
+                            )
+                        } else {
+                            info!("🪟 Showing confirmation dialog...");
+                            ui::show_confirmation_dialog(
+                                gtk_app,
+                                &display_name,
+                                &request.device_id,
+                                &request.public_key,
+                                dialog_timeout,
+                            )
+                        };
+                        info!("✅ Confirmation shown, waiting for user response...");
+
+                        // Wait for the answer alongside the main loop, which
+                        // keeps GTK, the tray and Ctrl+C going meanwhile
+                        let answer_tx = answer_tx.clone();
+                        tokio::spawn(async move {
+                            let result = wait_for_answer(confirm_rx, dialog_timeout).await;
+                            let _ = answer_tx.send((request, result));
+                        });
+                        continue;
                     }
-                    
-                    match confirm_rx.try_recv() {
-                        Ok(result) => break result,
-                        Err(tokio::sync::oneshot::error::TryRecvError::Empty) => {
-                            tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
-                        }
-                        Err(tokio::sync::oneshot::error::TryRecvError::Closed) => {
-                            break ui::ConfirmationResult::Rejected;
-                        }
+                    (PairingDecision::Approve, _) if request.scanned => {
+                        info!("Accepting pairing from {}, it scanned the QR code", display_name);
+                        ui::ConfirmationResult::Approved
+                    }
+                    (PairingDecision::Approve, _) => {
+                        info!("Auto-accepting pairing from known device {}", display_name);
+                        ui::ConfirmationResult::Approved
+                    }
+                    (PairingDecision::Reject, _) | (PairingDecision::Prompt, None) => {
+                        warn!("Rejecting pairing from {}: not a known device and no dialog available", display_name);
+                        ui::ConfirmationResult::Rejected
                     }
                 };
-                
-                // Handle result
+                let _ = answer_tx.send((request, result));
+            }
+            Some((request, result)) = answer_rx.recv() => {
                 info!("👤 User response received: {:?}", if matches!(result, ui::ConfirmationResult::Approved) { "APPROVED" } else { "REJECTED" });
                 let server = gatt_server.lock().await;
                 match result {
                     ui::ConfirmationResult::Approved => {
                         info!("✅ User approved pairing, completing ECDH exchange...");
-                        if let Err(e) = server.complete_pairing().await {
+                        if let Err(e) = server.complete_pairing(&request.device_id, &request.public_key).await {
                             error!("❌ Pairing failed: {}", e);
                         } else {
                             info!("🎉 Pairing completed successfully!");
-                            tray_handle.update(|_| {});
+                            state.set_negotiated_mtu(server.negotiated_mtu().await);
+                            if let Some(store) = &paired_devices {
+                                if let Err(e) = store.add(&request.device_id, request.device_name.as_deref()) {
+                                    warn!("Failed to remember paired device: {}", e);
+                                }
+                            }
+                            let last_device = paired_devices.as_ref().and_then(|store| store.last_device());
+                            state.set_reconnect_device(pairing::expected_reconnect(
+                                live_config.read().bluetooth.auto_accept,
+                                last_device.as_ref(),
+                            ));
                         }
                     }
                     ui::ConfirmationResult::Rejected => {
                         info!("❌ User rejected pairing, sending rejection...");
-                        if let Err(e) = server.reject_pairing("User rejected").await {
+                        if let Err(e) = server.reject_pairing(&request.device_id, &request.public_key, "User rejected").await {
                             error!("❌ Failed to send rejection: {}", e);
                         } else {
                             info!("✅ Rejection sent to Android");
@@ -336,6 +654,140 @@ async fn main() -> Result<()> {
         }
     }
 
+    // Shut down in order: finish typing, then stop BLE. Paired devices and
+    // voice commands are saved as they change, so there is nothing else to write.
+    info!("Shutting down...");
+    let _ = shutdown_tx.send(());
+    if let Err(e) = gatt_task.await {
+        error!("BLE event task failed: {}", e);
+    }
+    gatt_server.lock().await.shutdown().await;
+
     info!("Speech2Prompt Desktop stopped");
     Ok(())
 }
+
+/// Create the GATT server and start advertising.
+async fn start_gatt_server(
+    event_tx: tokio::sync::mpsc::Sender<bluetooth::ConnectionEvent>,
+    bluetooth_config: &config::BluetoothConfig,
+) -> Result<GattServer> {
+    let mut server = GattServer::new(event_tx, bluetooth_config.power_on_attempts).await?;
+    server.set_idle_timeout(bluetooth_config.idle_timeout()).await;
+    server.set_name(&bluetooth_config.device_name).await?;
+    server.start().await?;
+    Ok(server)
+}
+
+/// Report an unplugged adapter and rebuild the GATT server once one is back.
+async fn recover_unplugged_adapter(
+    mut adapter_events: tokio::sync::mpsc::Receiver<bluetooth::AdapterEvent>,
+    mut watch: bluetooth::AdapterWatch,
+    gatt_server: Arc<Mutex<GattServer>>,
+    event_tx: tokio::sync::mpsc::Sender<bluetooth::ConnectionEvent>,
+    bluetooth_config: config::BluetoothConfig,
+    state: Arc<AppState>,
+) {
+    while let Some(event) = adapter_events.recv().await {
+        match watch.handle(event) {
+            Some(bluetooth::Recovery::ReportLost) => {
+                let error = "Bluetooth adapter removed, waiting for it to come back";
+                let _ = event_tx
+                    .send(bluetooth::ConnectionEvent::Error(error.to_string()))
+                    .await;
+            }
+            Some(bluetooth::Recovery::Rebuild) => {
+                match start_gatt_server(event_tx.clone(), &bluetooth_config).await {
+                    Ok(server) => {
+                        let mut current = gatt_server.lock().await;
+                        current.shutdown().await;
+                        *current = server;
+                        info!("BLE GATT server restarted on adapter {}", current.adapter_name());
+                        state.set_discoverable(true);
+                        let _ = event_tx.send(bluetooth::ConnectionEvent::Disconnected).await;
+                    }
+                    Err(e) => {
+                        error!("Failed to restart the GATT server: {}", e);
+                        watch.rebuild_failed();
+                        let _ = event_tx
+                            .send(bluetooth::ConnectionEvent::Error(format!(
+                                "Bluetooth restart failed: {}",
+                                e
+                            )))
+                            .await;
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+/// Wait for the answer to a pairing prompt.
+///
+/// Doesn't rely on the dialog or notification server to give up after
+/// `timeout`. A prompt closed without an answer rejects the request.
+async fn wait_for_answer(
+    confirm_rx: tokio::sync::oneshot::Receiver<ui::ConfirmationResult>,
+    timeout: Option<Duration>,
+) -> ui::ConfirmationResult {
+    let answer = async { confirm_rx.await.unwrap_or(ui::ConfirmationResult::Rejected) };
+    let Some(timeout) = timeout else {
+        return answer.await;
+    };
+    match tokio::time::timeout(timeout, answer).await {
+        Ok(result) => result,
+        Err(_) => {
+            warn!(
+                "No answer to the pairing request within {:?}, rejecting",
+                timeout
+            );
+            ui::ConfirmationResult::Rejected
+        }
+    }
+}
+
+/// Forward whether text or a command is being injected to the phone.
+async fn report_busy(state: Arc<AppState>, gatt_server: Arc<Mutex<GattServer>>) {
+    use tokio::sync::broadcast::error::RecvError;
+
+    let mut changes = state.subscribe();
+    loop {
+        match changes.recv().await {
+            Ok(StateChange::Busy) | Err(RecvError::Lagged(_)) => {
+                gatt_server.lock().await.set_busy(state.is_busy()).await;
+            }
+            Ok(_) => {}
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Export or restore paired devices as asked on the command line.
+fn backup_paired_devices(cli: &cli::Cli, data_dir: &Path) -> Result<()> {
+    let store = PairedDeviceStore::new(data_dir)?;
+    let passphrase = read_passphrase(cli.export_devices.is_some())?;
+
+    if let Some(path) = &cli.export_devices {
+        store.export_encrypted(path, &passphrase)?;
+        println!("Exported paired devices to {}", path.display());
+    } else if let Some(path) = &cli.import_devices {
+        let added = store.import_encrypted(path, &passphrase)?;
+        println!("Restored {} paired devices", added);
+    }
+    Ok(())
+}
+
+/// Backup passphrase from the environment, or prompted for without echo.
+///
+/// A new passphrase (for an export) has to be typed twice.
+fn read_passphrase(confirm: bool) -> Result<String> {
+    if let Ok(passphrase) = std::env::var(BACKUP_PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    let passphrase = rpassword::prompt_password("Backup passphrase: ")?;
+    if confirm && rpassword::prompt_password("Repeat passphrase: ")? != passphrase {
+        anyhow::bail!("Passphrases do not match");
+    }
+    Ok(passphrase)
+}
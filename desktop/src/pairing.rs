@@ -0,0 +1,207 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pairing approval policy.
+//!
+//! Decides whether a pairing request is approved, rejected, or needs the
+//! confirmation dialog. Kept free of GTK so headless mode can use it.
+
+use crate::storage::PairedDevice;
+
+/// Longest device name shown, in characters.
+const MAX_DEVICE_NAME_CHARS: usize = 64;
+
+/// How to handle a pairing request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PairingDecision {
+    /// Complete pairing without asking.
+    Approve,
+    /// Reject pairing without asking.
+    Reject,
+    /// Ask the user with the confirmation dialog.
+    Prompt,
+}
+
+/// Decide how to handle a pairing request.
+///
+/// Phones that scanned the pairing QR code are approved, seeing the screen
+/// is the confirmation. Known devices are approved silently when
+/// `auto_accept` is on. Everything else needs the confirmation dialog, which
+/// headless mode doesn't have, so there it is rejected.
+pub fn decide_pairing(
+    headless: bool,
+    auto_accept: bool,
+    known: bool,
+    scanned: bool,
+) -> PairingDecision {
+    if scanned || (auto_accept && known) {
+        PairingDecision::Approve
+    } else if headless {
+        PairingDecision::Reject
+    } else {
+        PairingDecision::Prompt
+    }
+}
+
+/// Name of the device expected to reconnect on its own after startup.
+///
+/// The phone is the BLE central, so it reconnects by itself. With
+/// `auto_accept` its pairing request is then approved without asking and the
+/// session comes back without user interaction. Without it, the user is
+/// asked as usual and no reconnect is expected.
+pub fn expected_reconnect(auto_accept: bool, last_device: Option<&PairedDevice>) -> Option<String> {
+    if !auto_accept {
+        return None;
+    }
+    last_device.map(|d| d.device_name.clone().unwrap_or_else(|| d.device_id.clone()))
+}
+
+/// Make a phone-supplied device name safe to show and log.
+///
+/// The phone picks the name freely. Control characters, including line
+/// breaks and bidirectional overrides that could make it read like
+/// something else, are dropped, and overly long names are cut short.
+/// Returns `None` if nothing is left.
+pub fn sanitize_device_name(name: &str) -> Option<String> {
+    let cleaned: String = name
+        .chars()
+        .filter(|c| !c.is_control() && !is_bidi_control(*c))
+        .collect();
+    let cleaned = cleaned.trim();
+    if cleaned.is_empty() {
+        return None;
+    }
+
+    if cleaned.chars().count() > MAX_DEVICE_NAME_CHARS {
+        let truncated: String = cleaned.chars().take(MAX_DEVICE_NAME_CHARS - 1).collect();
+        Some(format!("{}…", truncated.trim_end()))
+    } else {
+        Some(cleaned.to_string())
+    }
+}
+
+/// Whether `c` changes the direction of the text around it.
+fn is_bidi_control(c: char) -> bool {
+    matches!(c, '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn device(name: Option<&str>) -> PairedDevice {
+        PairedDevice {
+            device_id: "android-123".to_string(),
+            device_name: name.map(str::to_string),
+            paired_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_known_device_with_auto_accept_skips_dialog() {
+        assert_eq!(decide_pairing(false, true, true, false), PairingDecision::Approve);
+        assert_eq!(decide_pairing(true, true, true, false), PairingDecision::Approve);
+    }
+
+    #[test]
+    fn test_unknown_device_prompts() {
+        assert_eq!(decide_pairing(false, true, false, false), PairingDecision::Prompt);
+        assert_eq!(decide_pairing(false, false, false, false), PairingDecision::Prompt);
+    }
+
+    #[test]
+    fn test_known_device_without_auto_accept_prompts() {
+        assert_eq!(decide_pairing(false, false, true, false), PairingDecision::Prompt);
+    }
+
+    #[test]
+    fn test_scanned_qr_code_is_approved() {
+        for auto_accept in [false, true] {
+            for known in [false, true] {
+                assert_eq!(
+                    decide_pairing(false, auto_accept, known, true),
+                    PairingDecision::Approve
+                );
+            }
+        }
+        assert_eq!(decide_pairing(true, false, false, true), PairingDecision::Approve);
+    }
+
+    #[test]
+    fn test_headless_never_needs_a_dialog() {
+        for auto_accept in [false, true] {
+            for known in [false, true] {
+                assert_ne!(
+                    decide_pairing(true, auto_accept, known, false),
+                    PairingDecision::Prompt
+                );
+            }
+        }
+        assert_eq!(decide_pairing(true, true, false, false), PairingDecision::Reject);
+        assert_eq!(decide_pairing(true, false, true, false), PairingDecision::Reject);
+    }
+
+    #[test]
+    fn test_last_device_reconnects_with_auto_accept() {
+        let last = device(Some("Pixel 8"));
+        assert_eq!(
+            expected_reconnect(true, Some(&last)).as_deref(),
+            Some("Pixel 8")
+        );
+
+        let unnamed = device(None);
+        assert_eq!(
+            expected_reconnect(true, Some(&unnamed)).as_deref(),
+            Some("android-123")
+        );
+    }
+
+    #[test]
+    fn test_no_reconnect_without_auto_accept_or_device() {
+        assert_eq!(
+            expected_reconnect(false, Some(&device(Some("Pixel 8")))),
+            None
+        );
+        assert_eq!(expected_reconnect(true, None), None);
+    }
+
+    #[test]
+    fn test_sanitize_device_name_strips_control_chars() {
+        assert_eq!(
+            sanitize_device_name("Pixel\n8\u{7}").as_deref(),
+            Some("Pixel8")
+        );
+        assert_eq!(
+            sanitize_device_name("\u{202E}lexiP").as_deref(),
+            Some("lexiP")
+        );
+        assert_eq!(
+            sanitize_device_name("  Jana's Pixel  ").as_deref(),
+            Some("Jana's Pixel")
+        );
+        assert_eq!(sanitize_device_name("\r\n\t "), None);
+    }
+
+    #[test]
+    fn test_sanitize_device_name_truncates() {
+        let name = "ž".repeat(100);
+        let sanitized = sanitize_device_name(&name).unwrap();
+        assert_eq!(sanitized.chars().count(), MAX_DEVICE_NAME_CHARS);
+        assert!(sanitized.ends_with('…'));
+
+        let name = "a".repeat(MAX_DEVICE_NAME_CHARS);
+        assert_eq!(sanitize_device_name(&name), Some(name));
+    }
+}
@@ -0,0 +1,174 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! QR code pairing payload.
+//!
+//! The QR code shown on the desktop carries a one-time ECDH public key and
+//! the desktop's device id. A phone that scanned it echoes the key in its
+//! pairing request, which proves the user saw the screen, so no
+//! confirmation is needed.
+
+use anyhow::{anyhow, bail, Context, Result};
+use qrcode::{Color, QrCode};
+
+/// URI prefix of the payload.
+const PAIR_URI: &str = "speech2prompt://pair?";
+
+/// Size of an X25519 public key in bytes.
+const KEY_SIZE: usize = 32;
+
+/// Light modules around the code, as the QR spec asks for.
+const QUIET_ZONE: usize = 4;
+
+/// What the phone needs to start a verified pairing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QrPayload {
+    /// Desktop device id, as sent in `PAIR_ACK`.
+    pub device_id: String,
+    /// One-time desktop X25519 public key.
+    pub public_key: [u8; KEY_SIZE],
+}
+
+impl QrPayload {
+    /// Encode as `speech2prompt://pair?id=<device id>&key=<hex key>`.
+    pub fn encode(&self) -> String {
+        let key: String = self
+            .public_key
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        format!("{}id={}&key={}", PAIR_URI, self.device_id, key)
+    }
+
+    /// Parse a scanned payload.
+    pub fn decode(text: &str) -> Result<Self> {
+        let query = text
+            .trim()
+            .strip_prefix(PAIR_URI)
+            .ok_or_else(|| anyhow!("Not a Speech2Prompt pairing code"))?;
+
+        let mut device_id = None;
+        let mut public_key = None;
+        for pair in query.split('&') {
+            match pair.split_once('=') {
+                Some(("id", id)) if !id.is_empty() => device_id = Some(id.to_string()),
+                Some(("key", key)) => public_key = Some(decode_key(key)?),
+                // Unknown fields are left for newer versions
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            device_id: device_id.ok_or_else(|| anyhow!("Pairing code has no device id"))?,
+            public_key: public_key.ok_or_else(|| anyhow!("Pairing code has no key"))?,
+        })
+    }
+}
+
+/// Decode a hex public key.
+fn decode_key(hex: &str) -> Result<[u8; KEY_SIZE]> {
+    if hex.len() != KEY_SIZE * 2 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        bail!("Invalid key: expected {} hex digits", KEY_SIZE * 2);
+    }
+    let mut key = [0u8; KEY_SIZE];
+    for (byte, digits) in key.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        let digits = std::str::from_utf8(digits).context("Invalid key")?;
+        *byte = u8::from_str_radix(digits, 16).context("Invalid key")?;
+    }
+    Ok(key)
+}
+
+/// QR code rendered as an RGB image.
+pub struct QrImage {
+    /// Width and height in pixels.
+    pub size: usize,
+    /// Rows of RGB pixels, `size * 3` bytes each.
+    pub pixels: Vec<u8>,
+}
+
+/// Render `payload` with `scale` pixels per module, black on white.
+pub fn render(payload: &QrPayload, scale: usize) -> Result<QrImage> {
+    let code = QrCode::new(payload.encode()).context("Failed to create QR code")?;
+    let width = code.width();
+    let colors = code.to_colors();
+
+    let size = (width + 2 * QUIET_ZONE) * scale;
+    let mut pixels = vec![0xff; size * size * 3];
+    for (i, color) in colors.iter().enumerate() {
+        if *color != Color::Dark {
+            continue;
+        }
+        let (row, col) = (i / width + QUIET_ZONE, i % width + QUIET_ZONE);
+        for y in row * scale..(row + 1) * scale {
+            let start = (y * size + col * scale) * 3;
+            pixels[start..start + scale * 3].fill(0);
+        }
+    }
+    Ok(QrImage { size, pixels })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload() -> QrPayload {
+        let mut public_key = [0u8; KEY_SIZE];
+        for (i, byte) in public_key.iter_mut().enumerate() {
+            *byte = (i * 8) as u8;
+        }
+        QrPayload {
+            device_id: "linux-001122334455".to_string(),
+            public_key,
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let payload = payload();
+        let encoded = payload.encode();
+        assert!(encoded.starts_with("speech2prompt://pair?id=linux-001122334455&key=0008101820"));
+        assert_eq!(QrPayload::decode(&encoded).unwrap(), payload);
+    }
+
+    #[test]
+    fn test_decode_ignores_unknown_fields() {
+        let encoded = format!("{}&v=2\n", payload().encode());
+        assert_eq!(QrPayload::decode(&encoded).unwrap(), payload());
+    }
+
+    #[test]
+    fn test_decode_rejects_bad_payloads() {
+        assert!(QrPayload::decode("https://example.com").is_err());
+        assert!(QrPayload::decode("speech2prompt://pair?id=linux-1").is_err());
+        assert!(
+            QrPayload::decode(&format!("speech2prompt://pair?key={}", "00".repeat(32))).is_err()
+        );
+        assert!(QrPayload::decode("speech2prompt://pair?id=linux-1&key=0011").is_err());
+        assert!(QrPayload::decode(&format!(
+            "speech2prompt://pair?id=linux-1&key={}",
+            "zz".repeat(32)
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let image = render(&payload(), 4).unwrap();
+        assert_eq!(image.pixels.len(), image.size * image.size * 3);
+        // Quiet zone is white, the finder pattern in the corner black
+        assert_eq!(image.pixels[0], 0xff);
+        let corner = (QUIET_ZONE * 4 * image.size + QUIET_ZONE * 4) * 3;
+        assert_eq!(image.pixels[corner], 0);
+    }
+}
@@ -0,0 +1,249 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rhai scripts for user-defined commands.
+//!
+//! A command mapping can name a script instead of a key combination. The
+//! script sees the spoken phrase as `phrase` and returns what to do:
+//!
+//! ```rhai
+//! text("Hello " + phrase)                 // type text
+//! keys("ctrl+s")                          // press a key combination
+//! [text("done"), keys("enter")]           // several actions in order
+//! "plain string"                          // same as text(...)
+//! ```
+//!
+//! Scripts are sandboxed: the engine has no file, network or process
+//! access, output from `print` and `debug` is discarded, and runaway
+//! scripts are stopped by operation and size limits.
+//!
+//! Needs the `scripting` feature; without it running a script fails.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use crate::input::KeyCombo;
+
+/// An action returned by a script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptAction {
+    /// Type text at the cursor.
+    Text(String),
+    /// Press a key combination.
+    Keys(KeyCombo),
+}
+
+#[cfg(feature = "scripting")]
+mod engine {
+    use anyhow::{anyhow, bail, Result};
+    use rhai::module_resolvers::DummyModuleResolver;
+    use rhai::{Array, Dynamic, Engine, EvalAltResult, Scope};
+
+    use super::ScriptAction;
+    use crate::input::KeyCombo;
+
+    /// Operations a script may run before it is stopped.
+    const MAX_OPERATIONS: u64 = 100_000;
+
+    /// Sandboxed Rhai engine.
+    pub struct ScriptEngine {
+        engine: Engine,
+    }
+
+    impl ScriptEngine {
+        /// Create an engine with the action functions registered.
+        pub fn new() -> Self {
+            let mut engine = Engine::new();
+
+            engine.set_max_operations(MAX_OPERATIONS);
+            engine.set_max_call_levels(32);
+            engine.set_max_expr_depths(64, 32);
+            engine.set_max_string_size(10_000);
+            engine.set_max_array_size(1_000);
+            engine.set_max_map_size(1_000);
+            engine.disable_symbol("eval");
+            // The default resolver lets `import` load any file
+            engine.set_module_resolver(DummyModuleResolver::new());
+            engine.on_print(|_| {});
+            engine.on_debug(|_, _, _| {});
+
+            engine.register_type_with_name::<ScriptAction>("Action");
+            engine.register_fn("text", |text: &str| ScriptAction::Text(text.to_string()));
+            engine.register_fn(
+                "keys",
+                |keys: &str| -> Result<ScriptAction, Box<EvalAltResult>> {
+                    KeyCombo::parse(keys)
+                        .map(ScriptAction::Keys)
+                        .ok_or_else(|| format!("Unknown key combination '{}'", keys).into())
+                },
+            );
+
+            Self { engine }
+        }
+
+        /// Run a script for the spoken `phrase` and collect its actions.
+        pub fn run(&self, source: &str, phrase: &str) -> Result<Vec<ScriptAction>> {
+            let mut scope = Scope::new();
+            scope.push_constant("phrase", phrase.to_string());
+
+            let value = self
+                .engine
+                .eval_with_scope::<Dynamic>(&mut scope, source)
+                .map_err(|e| anyhow!("Script failed: {}", e))?;
+
+            let mut actions = Vec::new();
+            collect_actions(value, &mut actions)?;
+            Ok(actions)
+        }
+    }
+
+    /// Turn a script's return value into actions.
+    fn collect_actions(value: Dynamic, actions: &mut Vec<ScriptAction>) -> Result<()> {
+        if value.is_unit() {
+            return Ok(());
+        }
+        if value.is::<ScriptAction>() {
+            actions.push(value.cast::<ScriptAction>());
+            return Ok(());
+        }
+        if value.is_string() {
+            let text = value.into_string().map_err(|e| anyhow!(e))?;
+            actions.push(ScriptAction::Text(text));
+            return Ok(());
+        }
+        if value.is_array() {
+            for item in value.cast::<Array>() {
+                collect_actions(item, actions)?;
+            }
+            return Ok(());
+        }
+        bail!(
+            "Script returned a {}, expected an action",
+            value.type_name()
+        )
+    }
+}
+
+#[cfg(not(feature = "scripting"))]
+mod engine {
+    use anyhow::{bail, Result};
+
+    use super::ScriptAction;
+
+    /// Stand-in used when scripting isn't compiled in.
+    pub struct ScriptEngine;
+
+    impl ScriptEngine {
+        pub fn new() -> Self {
+            Self
+        }
+
+        pub fn run(&self, _source: &str, _phrase: &str) -> Result<Vec<ScriptAction>> {
+            bail!("Script commands need the `scripting` feature")
+        }
+    }
+}
+
+pub use engine::ScriptEngine;
+
+impl Default for ScriptEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Read a script file and run it for the spoken `phrase`.
+pub fn run_file(engine: &ScriptEngine, path: &Path, phrase: &str) -> Result<Vec<ScriptAction>> {
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read script {:?}", path))?;
+    engine.run(&source, phrase)
+}
+
+#[cfg(all(test, feature = "scripting"))]
+mod tests {
+    use super::*;
+    use crate::input::{Key, Modifier};
+
+    #[test]
+    fn test_script_returns_text_action() {
+        let engine = ScriptEngine::new();
+        let actions = engine.run(r#"text("Hello " + phrase)"#, "world").unwrap();
+        assert_eq!(actions, vec![ScriptAction::Text("Hello world".to_string())]);
+    }
+
+    #[test]
+    fn test_script_returns_several_actions() {
+        let engine = ScriptEngine::new();
+        let actions = engine
+            .run(r#"["signed", keys("ctrl+s"), ()]"#, "save")
+            .unwrap();
+        assert_eq!(
+            actions,
+            vec![
+                ScriptAction::Text("signed".to_string()),
+                ScriptAction::Keys(KeyCombo {
+                    modifiers: vec![Modifier::Ctrl],
+                    key: Key::S,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_script_errors_are_reported() {
+        let engine = ScriptEngine::new();
+        assert!(engine.run(r#"keys("ctrl+nope")"#, "").is_err());
+        assert!(engine.run("42", "").is_err());
+        assert!(engine.run("let x = ;", "").is_err());
+    }
+
+    #[test]
+    fn test_runaway_script_is_stopped() {
+        let engine = ScriptEngine::new();
+        assert!(engine.run("loop {}", "").is_err());
+    }
+
+    #[test]
+    fn test_import_is_not_allowed() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("secret.rhai"),
+            r#"export const value = "x";"#,
+        )
+        .unwrap();
+        let source = format!(
+            r#"import "{}" as secret; secret::value"#,
+            temp_dir.path().join("secret").display()
+        );
+
+        let engine = ScriptEngine::new();
+        assert!(engine.run(&source, "").is_err());
+    }
+
+    #[test]
+    fn test_phrase_is_read_only() {
+        let engine = ScriptEngine::new();
+        assert!(engine.run(r#"phrase = "x"; phrase"#, "hi").is_err());
+    }
+}
+
+#[cfg(all(test, not(feature = "scripting")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripts_need_the_feature() {
+        assert!(ScriptEngine::new().run(r#"text("hi")"#, "").is_err());
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Unix socket listener shared by the control and status sockets.
+
+use anyhow::{Context, Result};
+use std::future::Future;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Running socket listener.
+///
+/// Stops listening and removes the socket file when dropped.
+pub struct SocketServer {
+    path: PathBuf,
+    task: JoinHandle<()>,
+}
+
+impl SocketServer {
+    /// Listen on `path` and spawn `handle_client` for every connection.
+    ///
+    /// A stale socket file from an earlier run is replaced, and the socket
+    /// is only accessible to the current user. `name` is used in log
+    /// messages.
+    pub fn bind<F, Fut>(path: &Path, name: &'static str, handle_client: F) -> Result<Self>
+    where
+        F: Fn(UnixStream) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        if path.exists() {
+            std::fs::remove_file(path)
+                .with_context(|| format!("Failed to remove stale socket {:?}", path))?;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let listener =
+            UnixListener::bind(path).with_context(|| format!("Failed to bind {:?}", path))?;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        info!("{} socket listening on {:?}", name, path);
+
+        let task = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(handle_client(stream));
+                    }
+                    Err(e) => {
+                        warn!("{} socket accept failed: {}", name, e);
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            task,
+        })
+    }
+}
+
+impl Drop for SocketServer {
+    fn drop(&mut self) {
+        self.task.abort();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_bind_replaces_stale_socket() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("run").join("test.sock");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "stale").unwrap();
+
+        let server = SocketServer::bind(&path, "Test", |mut stream: UnixStream| async move {
+            let _ = stream.write_all(b"hi").await;
+        })
+        .unwrap();
+
+        let mut reply = String::new();
+        let mut stream = UnixStream::connect(&path).await.unwrap();
+        stream.read_to_string(&mut reply).await.unwrap();
+        assert_eq!(reply, "hi");
+
+        drop(server);
+        assert!(!path.exists());
+    }
+}
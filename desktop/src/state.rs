@@ -15,7 +15,13 @@
 //! Application state management.
 
 use parking_lot::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// Buffered changes per subscriber before it lags.
+const CHANGE_CAPACITY: usize = 16;
 
 /// Connection status.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -38,6 +44,23 @@ impl ConnectionStatus {
     }
 }
 
+/// What changed in [`AppState`], sent to subscribers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateChange {
+    /// Connection status, device, MTU, battery or expected reconnect.
+    Connection,
+    /// Input injection was enabled or disabled.
+    InputEnabled,
+    /// Processing was paused or resumed.
+    Paused,
+    /// Recording mode started or stopped.
+    Recording,
+    /// BLE advertising was started or stopped.
+    Discoverable,
+    /// Injecting text or a command started or finished.
+    Busy,
+}
+
 /// Shared application state.
 #[derive(Debug)]
 pub struct AppState {
@@ -47,14 +70,54 @@ pub struct AppState {
     /// Whether input injection is enabled.
     pub input_enabled: RwLock<bool>,
 
+    /// Whether processing is paused. Unlike disabled input, text received
+    /// while paused is kept and typed on resume.
+    pub paused: RwLock<bool>,
+
+    /// Whether the desktop advertises itself to phones.
+    pub discoverable: RwLock<bool>,
+
+    /// Whether text or a command is being injected.
+    pub busy: RwLock<bool>,
+
     /// Connected device name.
     pub connected_device: RwLock<Option<String>>,
 
+    /// Device expected to reconnect while disconnected.
+    pub reconnect_device: RwLock<Option<String>>,
+
+    /// When the current connection was established.
+    pub connected_since: RwLock<Option<Instant>>,
+
+    /// ATT MTU negotiated with the connected device.
+    pub negotiated_mtu: RwLock<Option<usize>>,
+
+    /// Battery level of the connected device in percent, if it reports one.
+    pub battery: RwLock<Option<u8>>,
+
+    /// What failed when the status last turned to error (for tooltip).
+    pub last_error: RwLock<Option<String>>,
+
     /// Last received text (for tooltip).
     pub last_text: RwLock<Option<String>>,
 
     /// Command being recorded (if in recording mode).
     pub recording_command: RwLock<Option<String>>,
+
+    /// Words typed since startup.
+    words_typed: AtomicU64,
+
+    /// Voice commands executed since startup.
+    commands_executed: AtomicU64,
+
+    /// Bytes of text injected since startup.
+    bytes_injected: AtomicU64,
+
+    /// When the app started.
+    started_at: Instant,
+
+    /// Notifies subscribers of changes.
+    changes: broadcast::Sender<StateChange>,
 }
 
 impl Default for AppState {
@@ -62,9 +125,22 @@ impl Default for AppState {
         Self {
             connection_status: RwLock::new(ConnectionStatus::Disconnected),
             input_enabled: RwLock::new(true),
+            paused: RwLock::new(false),
+            discoverable: RwLock::new(true),
+            busy: RwLock::new(false),
             connected_device: RwLock::new(None),
+            reconnect_device: RwLock::new(None),
+            connected_since: RwLock::new(None),
+            negotiated_mtu: RwLock::new(None),
+            battery: RwLock::new(None),
+            last_error: RwLock::new(None),
             last_text: RwLock::new(None),
             recording_command: RwLock::new(None),
+            words_typed: AtomicU64::new(0),
+            commands_executed: AtomicU64::new(0),
+            bytes_injected: AtomicU64::new(0),
+            started_at: Instant::now(),
+            changes: broadcast::channel(CHANGE_CAPACITY).0,
         }
     }
 }
@@ -74,36 +150,129 @@ impl AppState {
         Arc::new(Self::default())
     }
 
+    /// Get notified of changes, so UI can refresh without polling.
+    ///
+    /// Statistics and the last text aren't reported.
+    pub fn subscribe(&self) -> broadcast::Receiver<StateChange> {
+        self.changes.subscribe()
+    }
+
+    fn notify(&self, change: StateChange) {
+        // No subscribers is fine
+        let _ = self.changes.send(change);
+    }
+
     pub fn set_connected(&self, device_name: String) {
         *self.connection_status.write() = ConnectionStatus::Connected;
         *self.connected_device.write() = Some(device_name);
+        *self.connected_since.write() = Some(Instant::now());
+        self.notify(StateChange::Connection);
     }
 
     pub fn set_disconnected(&self) {
         *self.connection_status.write() = ConnectionStatus::Disconnected;
         *self.connected_device.write() = None;
+        *self.connected_since.write() = None;
+        *self.negotiated_mtu.write() = None;
+        *self.battery.write() = None;
+        self.notify(StateChange::Connection);
     }
 
-    pub fn set_error(&self) {
+    pub fn set_error(&self, error: impl Into<String>) {
+        *self.last_error.write() = Some(error.into());
         *self.connection_status.write() = ConnectionStatus::Error;
+        self.notify(StateChange::Connection);
     }
 
     pub fn set_input_enabled(&self, enabled: bool) {
         *self.input_enabled.write() = enabled;
+        self.notify(StateChange::InputEnabled);
     }
 
     pub fn is_input_enabled(&self) -> bool {
         *self.input_enabled.read()
     }
 
+    pub fn set_paused(&self, paused: bool) {
+        *self.paused.write() = paused;
+        self.notify(StateChange::Paused);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.read()
+    }
+
+    pub fn set_discoverable(&self, discoverable: bool) {
+        *self.discoverable.write() = discoverable;
+        self.notify(StateChange::Discoverable);
+    }
+
+    pub fn is_discoverable(&self) -> bool {
+        *self.discoverable.read()
+    }
+
+    /// Note whether text or a command is being injected.
+    pub fn set_busy(&self, busy: bool) {
+        let was_busy = std::mem::replace(&mut *self.busy.write(), busy);
+        if was_busy != busy {
+            self.notify(StateChange::Busy);
+        }
+    }
+
+    pub fn is_busy(&self) -> bool {
+        *self.busy.read()
+    }
+
     pub fn get_status(&self) -> ConnectionStatus {
         *self.connection_status.read()
     }
 
+    pub fn get_last_error(&self) -> Option<String> {
+        self.last_error.read().clone()
+    }
+
     pub fn get_device_name(&self) -> Option<String> {
         self.connected_device.read().clone()
     }
 
+    /// Set the device expected to reconnect while disconnected.
+    pub fn set_reconnect_device(&self, device_name: Option<String>) {
+        *self.reconnect_device.write() = device_name;
+        self.notify(StateChange::Connection);
+    }
+
+    pub fn get_reconnect_device(&self) -> Option<String> {
+        self.reconnect_device.read().clone()
+    }
+
+    /// Get how long the current connection has been up.
+    pub fn get_connected_duration(&self) -> Option<Duration> {
+        self.connected_since.read().map(|since| since.elapsed())
+    }
+
+    pub fn set_negotiated_mtu(&self, mtu: usize) {
+        *self.negotiated_mtu.write() = Some(mtu);
+        self.notify(StateChange::Connection);
+    }
+
+    pub fn get_negotiated_mtu(&self) -> Option<usize> {
+        *self.negotiated_mtu.read()
+    }
+
+    /// Note the battery level the connected device reported.
+    ///
+    /// Heartbeats repeat the level, so only a change is reported.
+    pub fn set_battery(&self, level: Option<u8>) {
+        let previous = std::mem::replace(&mut *self.battery.write(), level);
+        if previous != level {
+            self.notify(StateChange::Connection);
+        }
+    }
+
+    pub fn get_battery(&self) -> Option<u8> {
+        *self.battery.read()
+    }
+
     pub fn set_last_text(&self, text: String) {
         *self.last_text.write() = Some(text);
     }
@@ -111,11 +280,30 @@ impl AppState {
     /// Start recording mode for a command.
     pub fn start_recording(&self, command: String) {
         *self.recording_command.write() = Some(command);
+        self.notify(StateChange::Recording);
     }
 
     /// Stop recording mode and return the command that was being recorded.
     pub fn stop_recording(&self) -> Option<String> {
-        self.recording_command.write().take()
+        let command = self.recording_command.write().take();
+        if command.is_some() {
+            self.notify(StateChange::Recording);
+        }
+        command
+    }
+
+    /// Stop recording mode with a captured phrase.
+    ///
+    /// `save` gets the command being recorded and runs before anyone sees
+    /// recording stop, so the phrase is saved once even if several arrive
+    /// at the same time. `None` if not recording.
+    pub fn finish_recording<T>(&self, save: impl FnOnce(&str) -> T) -> Option<T> {
+        let mut recording = self.recording_command.write();
+        let command = recording.take()?;
+        let result = save(&command);
+        drop(recording);
+        self.notify(StateChange::Recording);
+        Some(result)
     }
 
     /// Check if we're in recording mode.
@@ -127,4 +315,162 @@ impl AppState {
     pub fn get_recording_command(&self) -> Option<String> {
         self.recording_command.read().clone()
     }
+
+    /// Count text that was typed into the active window.
+    pub fn record_typed(&self, text: &str) {
+        let words = text.split_whitespace().count() as u64;
+        self.words_typed.fetch_add(words, Ordering::Relaxed);
+        self.bytes_injected
+            .fetch_add(text.len() as u64, Ordering::Relaxed);
+    }
+
+    /// Count an executed voice command.
+    pub fn record_command(&self) {
+        self.commands_executed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get the number of words typed since startup.
+    pub fn words_typed(&self) -> u64 {
+        self.words_typed.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of voice commands executed since startup.
+    pub fn commands_executed(&self) -> u64 {
+        self.commands_executed.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of bytes of text injected since startup.
+    pub fn bytes_injected(&self) -> u64 {
+        self.bytes_injected.load(Ordering::Relaxed)
+    }
+
+    /// Get how long the app has been running.
+    pub fn get_uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statistics_start_at_zero() {
+        let state = AppState::new();
+        assert_eq!(state.words_typed(), 0);
+        assert_eq!(state.commands_executed(), 0);
+        assert_eq!(state.bytes_injected(), 0);
+    }
+
+    #[test]
+    fn test_setters_notify_subscribers() {
+        let state = AppState::new();
+        let mut changes = state.subscribe();
+
+        state.set_connected("Pixel 8".to_string());
+        state.set_input_enabled(false);
+        state.set_paused(true);
+        state.set_discoverable(false);
+        state.set_busy(true);
+        state.start_recording("ENTER".to_string());
+        state.stop_recording();
+        state.set_disconnected();
+
+        let received: Vec<_> = std::iter::from_fn(|| changes.try_recv().ok()).collect();
+        assert_eq!(
+            received,
+            vec![
+                StateChange::Connection,
+                StateChange::InputEnabled,
+                StateChange::Paused,
+                StateChange::Discoverable,
+                StateChange::Busy,
+                StateChange::Recording,
+                StateChange::Recording,
+                StateChange::Connection,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_battery() {
+        let state = AppState::new();
+        let mut changes = state.subscribe();
+        assert_eq!(state.get_battery(), None);
+
+        state.set_connected("Pixel 8".to_string());
+        state.set_battery(Some(80));
+        state.set_battery(Some(80));
+        assert_eq!(state.get_battery(), Some(80));
+
+        // Gone with the connection
+        state.set_disconnected();
+        assert_eq!(state.get_battery(), None);
+
+        let received: Vec<_> = std::iter::from_fn(|| changes.try_recv().ok()).collect();
+        assert_eq!(received, vec![StateChange::Connection; 3]);
+    }
+
+    #[test]
+    fn test_finish_recording_once() {
+        let state = AppState::new();
+        let mut changes = state.subscribe();
+        assert_eq!(state.finish_recording(|_| ()), None);
+
+        state.start_recording("ENTER".to_string());
+        let saved = state.finish_recording(|command| {
+            // Still recording while the phrase is saved
+            assert!(state.recording_command.is_locked());
+            command.to_string()
+        });
+        assert_eq!(saved.as_deref(), Some("ENTER"));
+        assert!(!state.is_recording());
+        assert_eq!(state.finish_recording(|_| ()), None);
+
+        let received: Vec<_> = std::iter::from_fn(|| changes.try_recv().ok()).collect();
+        assert_eq!(received, vec![StateChange::Recording; 2]);
+    }
+
+    #[test]
+    fn test_unchanged_state_is_not_reported() {
+        let state = AppState::new();
+        let mut changes = state.subscribe();
+
+        // Not recording, so stopping changes nothing
+        assert_eq!(state.stop_recording(), None);
+        state.set_busy(false);
+        // Statistics are read on demand
+        state.record_typed("hello world");
+        state.record_command();
+        assert!(changes.try_recv().is_err());
+
+        // Setters work without subscribers
+        drop(changes);
+        state.set_error("Adapter not found");
+        assert_eq!(state.get_status(), ConnectionStatus::Error);
+        assert_eq!(state.get_last_error().as_deref(), Some("Adapter not found"));
+    }
+
+    #[test]
+    fn test_negotiated_mtu_cleared_on_disconnect() {
+        let state = AppState::new();
+        state.set_connected("Pixel 8".to_string());
+        state.set_negotiated_mtu(185);
+        assert_eq!(state.get_negotiated_mtu(), Some(185));
+
+        state.set_disconnected();
+        assert_eq!(state.get_negotiated_mtu(), None);
+    }
+
+    #[test]
+    fn test_record_typed_counts_words_and_bytes() {
+        let state = AppState::new();
+        state.record_typed("hello ");
+        state.record_typed("grüße, world");
+        assert_eq!(state.words_typed(), 3);
+        assert_eq!(state.bytes_injected(), 6 + "grüße, world".len() as u64);
+
+        state.record_command();
+        assert_eq!(state.commands_executed(), 1);
+    }
 }
@@ -0,0 +1,137 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Read-only status socket.
+//!
+//! Status bars and scripts can query the app without D-Bus: every client
+//! that connects gets one line of JSON and the connection is closed.
+//!
+//! ```text
+//! {"connected_device":"Pixel 8","input_enabled":true,"last_text_len":12,"mtu":185,"uptime_secs":3600}
+//! ```
+//!
+//! `connected_device` and `mtu` are `null` while no phone is connected. The
+//! socket is only accessible to the current user.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::net::UnixStream;
+use tracing::{debug, warn};
+
+use crate::socket::SocketServer;
+use crate::state::AppState;
+
+/// Socket file name.
+const SOCKET_NAME: &str = "speech2prompt-status.sock";
+
+/// Default socket path, next to the control socket.
+pub fn default_socket_path(data_dir: &Path) -> PathBuf {
+    dirs::runtime_dir()
+        .unwrap_or_else(|| data_dir.to_path_buf())
+        .join(SOCKET_NAME)
+}
+
+/// Snapshot of the app state sent to clients.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Status {
+    /// Name of the connected phone.
+    pub connected_device: Option<String>,
+    /// Whether received text is typed.
+    pub input_enabled: bool,
+    /// Characters in the last received text.
+    pub last_text_len: usize,
+    /// ATT MTU negotiated with the connected phone.
+    pub mtu: Option<usize>,
+    /// Seconds since the app started.
+    pub uptime_secs: u64,
+}
+
+impl Status {
+    /// Take a snapshot of `state`.
+    pub fn from_state(state: &AppState) -> Self {
+        Self {
+            connected_device: state.get_device_name(),
+            input_enabled: state.is_input_enabled(),
+            last_text_len: state
+                .last_text
+                .read()
+                .as_ref()
+                .map_or(0, |text| text.chars().count()),
+            mtu: state.get_negotiated_mtu(),
+            uptime_secs: state.get_uptime().as_secs(),
+        }
+    }
+}
+
+/// Running status socket.
+///
+/// Stops listening and removes the socket file when dropped.
+pub struct StatusServer {
+    _server: SocketServer,
+}
+
+impl StatusServer {
+    /// Listen on `path` and report the status of `state`.
+    ///
+    /// A stale socket file from an earlier run is replaced.
+    pub fn bind(path: &Path, state: Arc<AppState>) -> Result<Self> {
+        let server = SocketServer::bind(path, "Status", move |stream| {
+            send_status(stream, Status::from_state(&state))
+        })?;
+        Ok(Self { _server: server })
+    }
+}
+
+/// Write `status` as one JSON line and close the connection.
+async fn send_status(mut stream: UnixStream, status: Status) {
+    let mut line = match serde_json::to_string(&status) {
+        Ok(line) => line,
+        Err(e) => {
+            warn!("Failed to serialize status: {}", e);
+            return;
+        }
+    };
+    line.push('\n');
+    if let Err(e) = stream.write_all(line.as_bytes()).await {
+        debug!("Status client went away: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_from_state() {
+        let state = AppState::new();
+        let status = Status::from_state(&state);
+        assert_eq!(status.connected_device, None);
+        assert!(status.input_enabled);
+        assert_eq!(status.last_text_len, 0);
+        assert_eq!(status.mtu, None);
+
+        state.set_connected("Pixel".to_string());
+        state.set_negotiated_mtu(185);
+        state.set_input_enabled(false);
+        state.set_last_text("grüße".to_string());
+        let status = Status::from_state(&state);
+        assert_eq!(status.connected_device.as_deref(), Some("Pixel"));
+        assert!(!status.input_enabled);
+        assert_eq!(status.last_text_len, 5);
+        assert_eq!(status.mtu, Some(185));
+    }
+}
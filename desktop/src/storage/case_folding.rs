@@ -0,0 +1,86 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Language-aware lowercasing for phrase matching.
+//!
+//! `str::to_lowercase` follows the Unicode defaults, which are wrong for
+//! Turkish and Azerbaijani: there "I" is the capital of the dotless "ı" and
+//! "İ" the capital of "i".
+
+/// How letters are lowercased before phrases are compared.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CaseFolding {
+    /// Unicode default lowercasing.
+    #[default]
+    Default,
+    /// Turkish and Azerbaijani: "I" → "ı", "İ" → "i".
+    Turkic,
+}
+
+impl CaseFolding {
+    /// Case folding for a locale such as "tr", "tr_TR.UTF-8" or "az-AZ".
+    ///
+    /// Empty and unknown locales get the Unicode default.
+    pub fn for_locale(locale: &str) -> Self {
+        let language = locale
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "tr" | "az" => Self::Turkic,
+            _ => Self::Default,
+        }
+    }
+
+    /// Lowercase `text`.
+    pub fn lowercase(self, text: &str) -> String {
+        match self {
+            Self::Default => text.to_lowercase(),
+            Self::Turkic => text
+                .chars()
+                .flat_map(|c| -> Box<dyn Iterator<Item = char>> {
+                    match c {
+                        'I' => Box::new(std::iter::once('ı')),
+                        'İ' => Box::new(std::iter::once('i')),
+                        c => Box::new(c.to_lowercase()),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_locale() {
+        assert_eq!(CaseFolding::for_locale("tr"), CaseFolding::Turkic);
+        assert_eq!(CaseFolding::for_locale("tr_TR.UTF-8"), CaseFolding::Turkic);
+        assert_eq!(CaseFolding::for_locale("AZ-az"), CaseFolding::Turkic);
+        assert_eq!(CaseFolding::for_locale("en_US"), CaseFolding::Default);
+        assert_eq!(CaseFolding::for_locale(""), CaseFolding::Default);
+    }
+
+    #[test]
+    fn test_turkish_i() {
+        // Naive lowercasing turns the dotless capital into a dotted "i"
+        assert_eq!(CaseFolding::Default.lowercase("IŞIK"), "işik");
+        assert_eq!(CaseFolding::Turkic.lowercase("IŞIK"), "ışık");
+        assert_eq!(CaseFolding::Turkic.lowercase("İSTANBUL"), "istanbul");
+        assert_eq!(CaseFolding::Turkic.lowercase("Çay"), "çay");
+    }
+}
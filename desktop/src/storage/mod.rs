@@ -14,6 +14,11 @@
 
 //! Storage module for voice commands and secure storage.
 
+mod case_folding;
+mod paired_devices;
+mod phonetic;
 mod voice_commands;
 
+pub use case_folding::CaseFolding;
+pub use paired_devices::{PairedDevice, PairedDeviceStore};
 pub use voice_commands::{CommandInfo, VoiceCommandStore};
@@ -0,0 +1,325 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Paired device storage.
+//!
+//! Remembers which phones the user approved so reconnections can skip the
+//! confirmation dialog when `auto_accept` is enabled, and which one paired
+//! last. The list can be exported to a passphrase-encrypted backup and
+//! restored on a new machine.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+use speech2prompt_protocol::crypto::{decrypt_with_passphrase, encrypt_with_passphrase};
+
+/// A device the user approved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairedDevice {
+    /// Android device ID.
+    pub device_id: String,
+    /// Device name, if the phone sent one.
+    pub device_name: Option<String>,
+    /// When the device was first paired.
+    pub paired_at: DateTime<Utc>,
+}
+
+/// Paired devices file format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PairedDevicesFile {
+    /// File format version.
+    version: u32,
+    /// Approved devices.
+    devices: Vec<PairedDevice>,
+    /// Device ID of the device that paired last.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_device_id: Option<String>,
+}
+
+/// Encrypted backup file format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BackupFile {
+    /// Backup format version.
+    version: u32,
+    /// `PairedDevicesFile` JSON, encrypted with the passphrase.
+    data: String,
+}
+
+/// Store of approved devices, persisted to `paired_devices.json`.
+pub struct PairedDeviceStore {
+    /// Path to the paired_devices.json file.
+    path: PathBuf,
+    /// Devices indexed by device ID.
+    devices: RwLock<HashMap<String, PairedDevice>>,
+    /// Device ID of the device that paired last.
+    last_device_id: RwLock<Option<String>>,
+}
+
+impl PairedDeviceStore {
+    /// Open the store in `data_dir`, loading existing devices.
+    pub fn new(data_dir: &Path) -> Result<Self> {
+        let path = data_dir.join("paired_devices.json");
+
+        let (devices, last_device_id) = if path.exists() {
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {:?}", path))?;
+            let file: PairedDevicesFile = serde_json::from_str(&content)
+                .with_context(|| "Failed to parse paired_devices.json")?;
+            let devices = file
+                .devices
+                .into_iter()
+                .map(|d| (d.device_id.clone(), d))
+                .collect();
+            (devices, file.last_device_id)
+        } else {
+            debug!("Paired devices file doesn't exist, starting empty");
+            (HashMap::new(), None)
+        };
+
+        info!("Loaded {} paired devices", devices.len());
+        Ok(Self {
+            path,
+            devices: RwLock::new(devices),
+            last_device_id: RwLock::new(last_device_id),
+        })
+    }
+
+    /// Check whether a device was paired before.
+    pub fn is_known(&self, device_id: &str) -> bool {
+        self.devices.read().contains_key(device_id)
+    }
+
+    /// Get the device that paired last.
+    pub fn last_device(&self) -> Option<PairedDevice> {
+        let last_device_id = self.last_device_id.read();
+        let device_id = last_device_id.as_ref()?;
+        self.devices.read().get(device_id).cloned()
+    }
+
+    /// Remember a device after it paired successfully.
+    ///
+    /// Updates the name of an already known device and makes it the last
+    /// paired device.
+    pub fn add(&self, device_id: &str, device_name: Option<&str>) -> Result<()> {
+        {
+            let mut guard = self.devices.write();
+            guard
+                .entry(device_id.to_string())
+                .and_modify(|d| d.device_name = device_name.map(str::to_string))
+                .or_insert_with(|| PairedDevice {
+                    device_id: device_id.to_string(),
+                    device_name: device_name.map(str::to_string),
+                    paired_at: Utc::now(),
+                });
+        }
+        *self.last_device_id.write() = Some(device_id.to_string());
+        self.save()
+    }
+
+    /// Write all devices to `path`, encrypted with `passphrase`.
+    pub fn export_encrypted(&self, path: &Path, passphrase: &str) -> Result<()> {
+        if passphrase.is_empty() {
+            bail!("Backup passphrase must not be empty");
+        }
+
+        let file = self.to_file();
+        let backup = BackupFile {
+            version: 1,
+            data: encrypt_with_passphrase(&serde_json::to_string(&file)?, passphrase)?,
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&backup)?)
+            .with_context(|| format!("Failed to write {:?}", path))?;
+
+        info!("Exported {} paired devices to {:?}", file.devices.len(), path);
+        Ok(())
+    }
+
+    /// Add the devices of a backup made by [`Self::export_encrypted`].
+    ///
+    /// Devices already known are kept as they are. The backup's last device
+    /// becomes the last device only if there is none yet. Returns the number
+    /// of devices added.
+    pub fn import_encrypted(&self, path: &Path, passphrase: &str) -> Result<usize> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {:?}", path))?;
+        let backup: BackupFile =
+            serde_json::from_str(&content).context("Not a paired devices backup")?;
+        if backup.version != 1 {
+            bail!("Unsupported backup version {}", backup.version);
+        }
+
+        let json = decrypt_with_passphrase(&backup.data, passphrase)
+            .context("Wrong passphrase or damaged backup")?;
+        let file: PairedDevicesFile =
+            serde_json::from_str(&json).context("Failed to parse backup contents")?;
+
+        let mut added = 0;
+        {
+            let mut guard = self.devices.write();
+            for device in file.devices {
+                if let Entry::Vacant(entry) = guard.entry(device.device_id.clone()) {
+                    entry.insert(device);
+                    added += 1;
+                }
+            }
+        }
+        {
+            let mut last_device_id = self.last_device_id.write();
+            if last_device_id.is_none() {
+                *last_device_id = file.last_device_id;
+            }
+        }
+        self.save()?;
+
+        info!("Imported {} paired devices from {:?}", added, path);
+        Ok(added)
+    }
+
+    /// Current devices in file format, sorted by device ID.
+    fn to_file(&self) -> PairedDevicesFile {
+        let mut devices: Vec<PairedDevice> = self.devices.read().values().cloned().collect();
+        devices.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+
+        PairedDevicesFile {
+            version: 1,
+            devices,
+            last_device_id: self.last_device_id.read().clone(),
+        }
+    }
+
+    /// Save devices to file.
+    fn save(&self) -> Result<()> {
+        let file = self.to_file();
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&file)?)?;
+
+        debug!("Saved {} paired devices", file.devices.len());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_add_and_reload() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = PairedDeviceStore::new(temp_dir.path())?;
+        assert!(!store.is_known("android-123"));
+
+        store.add("android-123", Some("Pixel 8"))?;
+        assert!(store.is_known("android-123"));
+        assert!(!store.is_known("android-456"));
+
+        let reloaded = PairedDeviceStore::new(temp_dir.path())?;
+        assert!(reloaded.is_known("android-123"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_last_device_is_persisted() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = PairedDeviceStore::new(temp_dir.path())?;
+        assert!(store.last_device().is_none());
+
+        store.add("android-123", Some("Pixel 8"))?;
+        store.add("android-456", None)?;
+        assert_eq!(store.last_device().unwrap().device_id, "android-456");
+
+        store.add("android-123", Some("Pixel 8"))?;
+        let reloaded = PairedDeviceStore::new(temp_dir.path())?;
+        let last = reloaded.last_device().unwrap();
+        assert_eq!(last.device_id, "android-123");
+        assert_eq!(last.device_name.as_deref(), Some("Pixel 8"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypted_backup_round_trip() -> Result<()> {
+        let old_machine = TempDir::new()?;
+        let store = PairedDeviceStore::new(old_machine.path())?;
+        store.add("android-123", Some("Pixel 8"))?;
+        store.add("android-456", None)?;
+
+        let backup = old_machine.path().join("devices.backup");
+        store.export_encrypted(&backup, "correct horse")?;
+        let content = std::fs::read_to_string(&backup)?;
+        assert!(!content.contains("android-123"));
+
+        let new_machine = TempDir::new()?;
+        let restored = PairedDeviceStore::new(new_machine.path())?;
+        restored.add("android-789", None)?;
+        assert_eq!(restored.import_encrypted(&backup, "correct horse")?, 2);
+        assert!(restored.is_known("android-123"));
+        assert!(restored.is_known("android-456"));
+        assert!(restored.is_known("android-789"));
+        // The device paired here stays the last one
+        assert_eq!(restored.last_device().unwrap().device_id, "android-789");
+
+        // Persisted, and importing again adds nothing
+        let reloaded = PairedDeviceStore::new(new_machine.path())?;
+        assert!(reloaded.is_known("android-123"));
+        assert_eq!(reloaded.import_encrypted(&backup, "correct horse")?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_backup_with_wrong_passphrase_fails() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = PairedDeviceStore::new(temp_dir.path())?;
+        store.add("android-123", Some("Pixel 8"))?;
+        let backup = temp_dir.path().join("devices.backup");
+        assert!(store.export_encrypted(&backup, "").is_err());
+        store.export_encrypted(&backup, "correct horse")?;
+
+        let new_machine = TempDir::new()?;
+        let restored = PairedDeviceStore::new(new_machine.path())?;
+        let err = restored
+            .import_encrypted(&backup, "wrong horse")
+            .unwrap_err();
+        assert!(err.to_string().contains("Wrong passphrase"));
+        assert!(!restored.is_known("android-123"));
+        assert!(restored.last_device().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_without_last_device_loads() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        std::fs::write(
+            temp_dir.path().join("paired_devices.json"),
+            r#"{"version": 1, "devices": []}"#,
+        )?;
+
+        let store = PairedDeviceStore::new(temp_dir.path())?;
+        assert!(store.last_device().is_none());
+
+        Ok(())
+    }
+}
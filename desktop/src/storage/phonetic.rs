@@ -0,0 +1,183 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Phonetic keys for matching phrases by sound.
+//!
+//! Speech recognizers often pick the wrong one of two words that sound
+//! alike ("right" and "write"). Words with the same [`metaphone`] key sound
+//! alike, so comparing keys lets such a word still match its command.
+
+/// Metaphone key of a word, e.g. "RT" for both "right" and "write".
+///
+/// Follows the original Metaphone rules. Letters outside ASCII are kept as
+/// they are, anything that isn't a letter is ignored.
+pub fn metaphone(word: &str) -> String {
+    let mut letters: Vec<char> = Vec::new();
+    for c in word
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .flat_map(char::to_uppercase)
+    {
+        // Doubled letters sound like one, except for C ("accent")
+        if letters.last() != Some(&c) || c == 'C' {
+            letters.push(c);
+        }
+    }
+
+    // Silent first letters
+    match (letters.first(), letters.get(1)) {
+        (Some('K' | 'G' | 'P'), Some('N')) | (Some('A'), Some('E')) | (Some('W'), Some('R')) => {
+            letters.remove(0);
+        }
+        (Some('W'), Some('H')) => {
+            letters.remove(1);
+        }
+        _ => {}
+    }
+
+    let at = |i: usize| letters.get(i).copied();
+    let mut key = String::new();
+    for (i, &c) in letters.iter().enumerate() {
+        let prev = i.checked_sub(1).and_then(at);
+        let next = at(i + 1);
+        let after_next = at(i + 2);
+
+        match c {
+            'A' | 'E' | 'I' | 'O' | 'U' => {
+                if i == 0 {
+                    key.push(c);
+                }
+            }
+            // "lamb"
+            'B' if prev == Some('M') && next.is_none() => {}
+            'C' if next == Some('H') && prev == Some('S') => key.push('K'),
+            'C' if next == Some('H') || (next == Some('I') && after_next == Some('A')) => {
+                key.push('X')
+            }
+            'C' if matches!(next, Some('I' | 'E' | 'Y')) => {
+                // "science"
+                if prev != Some('S') {
+                    key.push('S');
+                }
+            }
+            'C' => key.push('K'),
+            'D' if next == Some('G') && matches!(after_next, Some('E' | 'I' | 'Y')) => {
+                key.push('J')
+            }
+            'D' => key.push('T'),
+            // "right", "sign", "signed"
+            'G' if next == Some('H') && after_next.is_some() && !is_vowel(after_next) => {}
+            'G' if next == Some('N')
+                && (after_next.is_none()
+                    || (after_next == Some('E')
+                        && at(i + 3) == Some('D')
+                        && at(i + 4).is_none())) => {}
+            // "edge", the D already sounds like J
+            'G' if prev == Some('D') && matches!(next, Some('E' | 'I' | 'Y')) => {}
+            'G' if matches!(next, Some('I' | 'E' | 'Y')) => key.push('J'),
+            'G' => key.push('K'),
+            'H' => {
+                if is_vowel(next) && !matches!(prev, Some('C' | 'G' | 'P' | 'S' | 'T')) {
+                    key.push('H');
+                }
+            }
+            'K' if prev == Some('C') => {}
+            'P' if next == Some('H') => key.push('F'),
+            'Q' => key.push('K'),
+            'S' if next == Some('H')
+                || (next == Some('I') && matches!(after_next, Some('O' | 'A'))) =>
+            {
+                key.push('X')
+            }
+            'T' if next == Some('I') && matches!(after_next, Some('O' | 'A')) => key.push('X'),
+            'T' if next == Some('H') => key.push('0'),
+            // "match"
+            'T' if next == Some('C') && after_next == Some('H') => {}
+            'V' => key.push('F'),
+            'W' | 'Y' => {
+                if is_vowel(next) {
+                    key.push(c);
+                }
+            }
+            'X' if i == 0 => key.push('S'),
+            'X' => key.push_str("KS"),
+            'Z' => key.push('S'),
+            c => key.push(c),
+        }
+    }
+    key
+}
+
+/// Metaphone keys of each word of a phrase, separated by single spaces.
+///
+/// `None` if a word has no key (e.g. a number), as it can't be compared.
+pub fn phrase_key(phrase: &str) -> Option<String> {
+    let keys: Vec<String> = phrase.split_whitespace().map(metaphone).collect();
+    if keys.is_empty() || keys.iter().any(String::is_empty) {
+        return None;
+    }
+    Some(keys.join(" "))
+}
+
+fn is_vowel(c: Option<char>) -> bool {
+    matches!(c, Some('A' | 'E' | 'I' | 'O' | 'U'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_homophones_share_a_key() {
+        for (a, b) in [
+            ("right", "write"),
+            ("new", "knew"),
+            ("pause", "paws"),
+            ("night", "knight"),
+            ("sea", "see"),
+            ("phone", "fone"),
+        ] {
+            assert_eq!(metaphone(a), metaphone(b), "{} / {}", a, b);
+        }
+        assert_eq!(metaphone("right"), "RT");
+    }
+
+    #[test]
+    fn test_different_words_differ() {
+        assert_ne!(metaphone("enter"), metaphone("copy"));
+        assert_ne!(metaphone("paste"), metaphone("pause"));
+        assert_ne!(metaphone("cut"), metaphone("copy"));
+    }
+
+    #[test]
+    fn test_rules() {
+        assert_eq!(metaphone("Thumb"), "0M");
+        assert_eq!(metaphone("church"), "XRX");
+        assert_eq!(metaphone("school"), "SKL");
+        assert_eq!(metaphone("science"), "SNS");
+        assert_eq!(metaphone("edge"), "EJ");
+        assert_eq!(metaphone("nation"), "NXN");
+        assert_eq!(metaphone("xylophone"), "SLFN");
+        assert_eq!(metaphone("what"), "WT");
+        assert_eq!(metaphone("enter!"), "ENTR");
+    }
+
+    #[test]
+    fn test_phrase_key() {
+        assert_eq!(phrase_key("write click"), phrase_key("right click"));
+        assert_eq!(phrase_key("  new   line "), Some("N LN".to_string()));
+        assert_eq!(phrase_key("select 2"), None);
+        assert_eq!(phrase_key(""), None);
+    }
+}
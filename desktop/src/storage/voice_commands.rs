@@ -1,5 +1,16 @@
 // Copyright 2024 Speech2Prompt Contributors
-// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
 
 //! Voice command mapping storage.
 //!
@@ -15,38 +26,119 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 use tracing::{debug, error, info, warn};
+use unicode_normalization::UnicodeNormalization;
+
+use super::phonetic;
+use super::CaseFolding;
+use crate::input::KeyCombo;
+use crate::logging::redacted;
 
 /// Normalize a phrase to at most 2 words.
 /// If more than 2 words, take the last 2.
-/// Trims whitespace and converts to lowercase.
-fn normalize_phrase(phrase: &str) -> String {
+/// Trims whitespace, converts to lowercase by the rules of `folding` and to
+/// Unicode NFC, so accented letters compare equal however they were composed.
+fn normalize_phrase(phrase: &str, folding: CaseFolding) -> String {
+    let phrase: String = phrase.nfc().collect();
     let words: Vec<&str> = phrase.split_whitespace().collect();
     match words.len() {
         0 => String::new(),
-        1 => words[0].to_lowercase(),
-        2 => format!("{} {}", words[0].to_lowercase(), words[1].to_lowercase()),
+        1 => folding.lowercase(words[0]),
+        2 => format!(
+            "{} {}",
+            folding.lowercase(words[0]),
+            folding.lowercase(words[1])
+        ),
         _ => {
             // Take last 2 words
             let last_two = &words[words.len() - 2..];
             format!(
                 "{} {}",
-                last_two[0].to_lowercase(),
-                last_two[1].to_lowercase()
+                folding.lowercase(last_two[0]),
+                folding.lowercase(last_two[1])
             )
         }
     }
 }
 
+/// Fold a phrase for comparison: trimmed, NFC and lowercase.
+fn fold_phrase(phrase: &str, folding: CaseFolding) -> String {
+    folding.lowercase(&phrase.trim().nfc().collect::<String>())
+}
+
+/// Validate a user-supplied phrase and return its normalized form.
+///
+/// Applies the same rules as stored phrases: at most 2 words (the last 2 are
+/// kept), lowercase by the rules of `folding`, and not empty.
+pub fn validate_phrase(phrase: &str, folding: CaseFolding) -> Result<String> {
+    let normalized = normalize_phrase(phrase, folding);
+    if normalized.is_empty() {
+        anyhow::bail!("Custom phrase cannot be empty");
+    }
+    Ok(normalized)
+}
+
+/// Turn a user-supplied command name into a command code.
+///
+/// "Save file" becomes "SAVE_FILE". Only letters, digits and underscores
+/// are allowed.
+fn command_code(name: &str) -> Result<String> {
+    let code = name
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("_")
+        .to_uppercase();
+    if code.is_empty() {
+        anyhow::bail!("Command name cannot be empty");
+    }
+    if !code.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        anyhow::bail!(
+            "Command name '{}' may only contain letters, digits and spaces",
+            name.trim()
+        );
+    }
+    Ok(code)
+}
+
 /// Default phrases for built-in commands (case-insensitive matching).
+///
+/// The one table of built-in phrases: the store and the matcher both read
+/// it through [`VoiceCommandStore::default_phrases`], which applies the
+/// overrides from the config.
 pub const DEFAULT_PHRASES: &[(&str, &str)] = &[
     ("ENTER", "enter"),
+    ("NEW_PARAGRAPH", "new paragraph"),
     ("SELECT_ALL", "select all"),
+    ("CLEAR_FIELD", "clear field"),
+    ("SELECT_WORD", "select word"),
+    ("SELECT_LINE", "select line"),
     ("COPY", "copy"),
     ("PASTE", "paste"),
     ("CUT", "cut"),
+    ("INDENT", "indent"),
+    ("DEDENT", "dedent"),
+    ("CAPS_LOCK", "caps lock"),
+    ("DUPLICATE_LINE", "duplicate line"),
+    ("CLICK", "click"),
+    ("RIGHT_CLICK", "right click"),
+    ("DOUBLE_CLICK", "double click"),
+    ("SCROLL_UP", "scroll up"),
+    ("SCROLL_DOWN", "scroll down"),
+    ("INSERT_DATE", "insert date"),
+    ("INSERT_TIME", "insert time"),
     ("CANCEL", "cancel"),
+    ("CANCEL_TYPING", "cancel typing"),
+    ("PLAY", "play"),
+    ("PAUSE", "pause"),
+    ("NEXT_TRACK", "next track"),
+    ("PREVIOUS_TRACK", "previous track"),
 ];
 
+/// Further default phrases for built-in commands.
+///
+/// Like the default phrase, an alias stops matching once the command gets a
+/// custom phrase.
+pub const DEFAULT_ALIASES: &[(&str, &str)] = &[("DEDENT", "outdent"), ("ENTER", "new line")];
+
 /// A single voice command mapping.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VoiceCommandMapping {
@@ -56,6 +148,18 @@ pub struct VoiceCommandMapping {
     pub command: String,
     /// When this mapping was created.
     pub created_at: DateTime<Utc>,
+    /// Key combination for user-defined commands (e.g. "ctrl+shift+s").
+    /// Not set for built-in commands.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keys: Option<String>,
+    /// Script file in the `scripts` directory run for user-defined commands
+    /// (e.g. "date.rhai"). Needs the `scripting` feature.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+    /// Name of a shell command in the `[shell]` allowlist of config.toml run
+    /// for user-defined commands (e.g. "build").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shell: Option<String>,
 }
 
 impl VoiceCommandMapping {
@@ -65,6 +169,9 @@ impl VoiceCommandMapping {
             phrase: phrase.into(),
             command: command.into(),
             created_at: Utc::now(),
+            keys: None,
+            script: None,
+            shell: None,
         }
     }
 }
@@ -99,6 +206,8 @@ pub struct CommandInfo {
     /// The default phrase for this command.
     #[allow(dead_code)]
     pub default_phrase: String,
+    /// Key combination for user-defined commands, `None` for built-ins.
+    pub keys: Option<String>,
 }
 
 /// Voice command store with file watching.
@@ -109,6 +218,12 @@ pub struct VoiceCommandStore {
     mappings: Arc<RwLock<HashMap<String, VoiceCommandMapping>>>,
     /// File watcher (kept alive).
     _watcher: Option<RecommendedWatcher>,
+    /// Also match phrases that sound like a command's phrase.
+    phonetic: bool,
+    /// How phrases are lowercased, shared with the file watcher.
+    case_folding: Arc<RwLock<CaseFolding>>,
+    /// Default phrases replaced by the config, by command code.
+    default_overrides: HashMap<String, String>,
 }
 
 impl VoiceCommandStore {
@@ -121,6 +236,9 @@ impl VoiceCommandStore {
             config_path,
             mappings: Arc::new(RwLock::new(HashMap::new())),
             _watcher: None,
+            phonetic: false,
+            case_folding: Arc::new(RwLock::new(CaseFolding::Default)),
+            default_overrides: HashMap::new(),
         };
 
         // Load existing mappings
@@ -133,6 +251,7 @@ impl VoiceCommandStore {
     pub fn new_with_watcher(config_dir: &Path) -> Result<Self> {
         let config_path = config_dir.join("voice_commands.json");
         let mappings = Arc::new(RwLock::new(HashMap::new()));
+        let case_folding = Arc::new(RwLock::new(CaseFolding::Default));
 
         // Ensure config directory exists
         std::fs::create_dir_all(config_dir)?;
@@ -140,6 +259,7 @@ impl VoiceCommandStore {
         // Set up file watcher
         let config_path_watch = config_path.clone();
         let mappings_watch = mappings.clone();
+        let case_folding_watch = case_folding.clone();
 
         let mut watcher = RecommendedWatcher::new(
             move |res: Result<Event, notify::Error>| {
@@ -148,7 +268,10 @@ impl VoiceCommandStore {
                         if event.kind.is_modify() || event.kind.is_create() {
                             debug!("Voice commands file changed, reloading...");
                             // Reload in the watcher thread
-                            if let Ok(new_mappings) = Self::load_from_file(&config_path_watch) {
+                            let folding = *case_folding_watch.read();
+                            if let Ok(new_mappings) =
+                                Self::load_from_file(&config_path_watch, folding)
+                            {
                                 let mut guard = mappings_watch.write();
                                 *guard = new_mappings;
                                 info!("Voice commands reloaded: {} custom mappings", guard.len());
@@ -171,6 +294,9 @@ impl VoiceCommandStore {
             config_path,
             mappings,
             _watcher: Some(watcher),
+            phonetic: false,
+            case_folding,
+            default_overrides: HashMap::new(),
         };
 
         // Load existing mappings
@@ -179,9 +305,76 @@ impl VoiceCommandStore {
         Ok(store)
     }
 
+    /// Also match phrases that only sound like a command's phrase, e.g.
+    /// "write click" for "right click".
+    ///
+    /// Phrases that match exactly still win.
+    pub fn with_phonetic_matching(mut self, enabled: bool) -> Self {
+        self.phonetic = enabled;
+        self
+    }
+
+    /// Lowercase phrases by the rules of a language, e.g. Turkish, when
+    /// storing and comparing them.
+    pub fn with_case_folding(mut self, case_folding: CaseFolding) -> Self {
+        *self.case_folding.write() = case_folding;
+        // Phrases loaded so far were lowercased by the default rules
+        if let Err(e) = self.load() {
+            warn!("Failed to reload voice commands: {:#}", e);
+        }
+        self
+    }
+
+    /// How phrases are lowercased for comparison.
+    pub fn case_folding(&self) -> CaseFolding {
+        *self.case_folding.read()
+    }
+
+    /// Replace the default phrases of built-in commands, by command code.
+    ///
+    /// Phrases follow the same rules as [`set_phrase`](Self::set_phrase).
+    /// Unknown commands and empty phrases are ignored with a warning.
+    pub fn with_default_phrases<'a>(
+        mut self,
+        overrides: impl IntoIterator<Item = (&'a String, &'a String)>,
+    ) -> Self {
+        for (command, phrase) in overrides {
+            let command_upper = command.to_uppercase();
+            if !DEFAULT_PHRASES.iter().any(|(cmd, _)| *cmd == command_upper) {
+                warn!("Ignoring default phrase for unknown command '{}'", command);
+                continue;
+            }
+            match validate_phrase(phrase, self.case_folding()) {
+                Ok(phrase) => {
+                    self.default_overrides.insert(command_upper, phrase);
+                }
+                Err(e) => warn!("Ignoring default phrase for '{}': {}", command, e),
+            }
+        }
+        self
+    }
+
+    /// Default phrase of each built-in command, with the overrides from the
+    /// config applied.
+    pub fn default_phrases(&self) -> impl Iterator<Item = (&'static str, &str)> + '_ {
+        DEFAULT_PHRASES.iter().map(|(cmd, phrase)| {
+            let phrase = self
+                .default_overrides
+                .get(*cmd)
+                .map_or(*phrase, String::as_str);
+            (*cmd, phrase)
+        })
+    }
+
+    /// Default phrases and aliases of built-in commands, as matched.
+    fn default_matches(&self) -> impl Iterator<Item = (&'static str, &str)> + '_ {
+        self.default_phrases()
+            .chain(DEFAULT_ALIASES.iter().copied())
+    }
+
     /// Load mappings from file.
     pub fn load(&mut self) -> Result<()> {
-        let new_mappings = Self::load_from_file(&self.config_path)?;
+        let new_mappings = Self::load_from_file(&self.config_path, self.case_folding())?;
         let mut guard = self.mappings.write();
         *guard = new_mappings;
         info!("Loaded {} custom voice command mappings", guard.len());
@@ -189,7 +382,10 @@ impl VoiceCommandStore {
     }
 
     /// Load mappings from a specific file path.
-    fn load_from_file(path: &Path) -> Result<HashMap<String, VoiceCommandMapping>> {
+    fn load_from_file(
+        path: &Path,
+        folding: CaseFolding,
+    ) -> Result<HashMap<String, VoiceCommandMapping>> {
         if !path.exists() {
             debug!("Voice commands file doesn't exist, using defaults");
             return Ok(HashMap::new());
@@ -217,7 +413,7 @@ impl VoiceCommandStore {
             }
 
             let word_count = phrase_trimmed.split_whitespace().count();
-            let normalized = normalize_phrase(phrase_trimmed);
+            let normalized = normalize_phrase(phrase_trimmed, folding);
 
             if word_count > 2 {
                 warn!(
@@ -265,8 +461,7 @@ impl VoiceCommandStore {
         }
 
         // Return default phrase
-        DEFAULT_PHRASES
-            .iter()
+        self.default_phrases()
             .find(|(cmd, _)| *cmd == command_upper)
             .map(|(_, phrase)| phrase.to_string())
             .unwrap_or_else(|| command.to_lowercase())
@@ -279,20 +474,48 @@ impl VoiceCommandStore {
         self.mappings.read().contains_key(&command_upper)
     }
 
+    /// Check that `phrase` can be the phrase of `command`, without setting
+    /// it.
+    ///
+    /// Returns the normalized phrase, or why [`set_phrase`](Self::set_phrase)
+    /// would fail.
+    pub fn check_phrase(&self, command: &str, phrase: &str) -> Result<String> {
+        let guard = self.mappings.read();
+        self.check_phrase_locked(&guard, &command.to_uppercase(), phrase)
+    }
+
+    /// Normalize `phrase` for `command_upper`, rejecting empty phrases and
+    /// phrases another command already uses.
+    fn check_phrase_locked(
+        &self,
+        guard: &HashMap<String, VoiceCommandMapping>,
+        command_upper: &str,
+        phrase: &str,
+    ) -> Result<String> {
+        let final_phrase = validate_phrase(phrase, self.case_folding())?;
+        if let Some(existing) = self.match_phrase_locked(guard, &final_phrase) {
+            if existing != command_upper {
+                anyhow::bail!(
+                    "Phrase '{}' is already used by '{}'",
+                    final_phrase,
+                    existing
+                );
+            }
+        }
+        Ok(final_phrase)
+    }
+
     /// Set a custom phrase for a command.
     ///
     /// Custom phrases support 1 or 2 words for reliable matching.
     /// If more than 2 words are provided, the last 2 words are used.
+    /// Phrases of other commands are rejected.
     pub fn set_phrase(&self, command: &str, phrase: &str) -> Result<()> {
         let command_upper = command.to_uppercase();
+        let mut guard = self.mappings.write();
 
-        // Normalize to at most 2 words
-        let final_phrase = normalize_phrase(phrase);
-
-        // Validate: must not be empty
-        if final_phrase.is_empty() {
-            anyhow::bail!("Custom phrase cannot be empty");
-        }
+        // Normalize to at most 2 words, rejecting empty and used phrases
+        let final_phrase = self.check_phrase_locked(&guard, &command_upper, phrase)?;
 
         // Log warning if more than 2 words were provided
         let word_count = phrase.split_whitespace().count();
@@ -305,17 +528,121 @@ impl VoiceCommandStore {
             );
         }
 
-        let mapping = VoiceCommandMapping::new(final_phrase, command_upper.clone());
+        let mut mapping = VoiceCommandMapping::new(final_phrase, command_upper.clone());
 
-        {
-            let mut guard = self.mappings.write();
-            guard.insert(command_upper, mapping);
+        // Keep the key combination, script or shell command of user-defined
+        // commands
+        if let Some(existing) = guard.get(&command_upper) {
+            mapping.keys = existing.keys.clone();
+            mapping.script = existing.script.clone();
+            mapping.shell = existing.shell.clone();
         }
+        guard.insert(command_upper, mapping);
+        drop(guard);
 
         self.save()
     }
 
+    /// Check that a user-defined command can be added, without adding it.
+    ///
+    /// Returns the normalized phrase, or why
+    /// [`add_custom_command`](Self::add_custom_command) would fail.
+    pub fn check_custom_command(&self, name: &str, keys: &str, phrase: &str) -> Result<String> {
+        let guard = self.mappings.read();
+        let (_, _, final_phrase) = self.check_custom_command_locked(&guard, name, keys, phrase)?;
+        Ok(final_phrase)
+    }
+
+    /// Command code, key combination and normalized phrase of a new
+    /// user-defined command, rejecting clashes with existing commands.
+    fn check_custom_command_locked(
+        &self,
+        guard: &HashMap<String, VoiceCommandMapping>,
+        name: &str,
+        keys: &str,
+        phrase: &str,
+    ) -> Result<(String, KeyCombo, String)> {
+        let code = command_code(name)?;
+        let combo = KeyCombo::parse(keys)
+            .with_context(|| format!("Unknown key combination '{}'", keys.trim()))?;
+
+        if DEFAULT_PHRASES.iter().any(|(cmd, _)| *cmd == code) {
+            anyhow::bail!("'{}' is a built-in command", code);
+        }
+        if guard.contains_key(&code) {
+            anyhow::bail!("Command '{}' already exists", code);
+        }
+        let final_phrase = self.check_phrase_locked(guard, &code, phrase)?;
+        Ok((code, combo, final_phrase))
+    }
+
+    /// Add a user-defined command that presses a key combination.
+    ///
+    /// The name is turned into a command code ("save file" -> "SAVE_FILE"),
+    /// `keys` is parsed as a combination like "ctrl+shift+s" and the phrase
+    /// follows the same rules as [`set_phrase`](Self::set_phrase).
+    /// Returns the new command code.
+    pub fn add_custom_command(&self, name: &str, keys: &str, phrase: &str) -> Result<String> {
+        let mut guard = self.mappings.write();
+        let (code, combo, final_phrase) =
+            self.check_custom_command_locked(&guard, name, keys, phrase)?;
+
+        let mut mapping = VoiceCommandMapping::new(final_phrase, code.clone());
+        mapping.keys = Some(combo.to_string());
+        guard.insert(code.clone(), mapping);
+        drop(guard);
+
+        self.save()?;
+        Ok(code)
+    }
+
+    /// Get the key combination of a user-defined command.
+    pub fn get_key_combo(&self, command: &str) -> Option<KeyCombo> {
+        let command_upper = command.to_uppercase();
+        let guard = self.mappings.read();
+        let keys = guard.get(&command_upper)?.keys.as_deref()?;
+
+        let combo = KeyCombo::parse(keys);
+        if combo.is_none() {
+            warn!("Invalid keys '{}' for command '{}'", keys, command_upper);
+        }
+        combo
+    }
+
+    /// Get the phrase and script path of a script command.
+    ///
+    /// Script names must be plain file names inside the `scripts` directory
+    /// next to voice_commands.json.
+    pub fn get_script(&self, command: &str) -> Option<(String, PathBuf)> {
+        let command_upper = command.to_uppercase();
+        let guard = self.mappings.read();
+        let mapping = guard.get(&command_upper)?;
+        let script = mapping.script.as_deref()?;
+
+        let is_plain_name = Path::new(script).file_name() == Some(script.as_ref());
+        if !is_plain_name || script.starts_with('.') {
+            warn!(
+                "Invalid script '{}' for command '{}'",
+                script, command_upper
+            );
+            return None;
+        }
+
+        let scripts_dir = self.config_path.parent()?.join("scripts");
+        Some((mapping.phrase.clone(), scripts_dir.join(script)))
+    }
+
+    /// Get the allowlist name of a shell command.
+    ///
+    /// Only names the command; what runs is looked up in config.toml.
+    pub fn get_shell(&self, command: &str) -> Option<String> {
+        let guard = self.mappings.read();
+        guard.get(&command.to_uppercase())?.shell.clone()
+    }
+
     /// Remove custom phrase (revert to default).
+    ///
+    /// For user-defined commands this deletes the command.
     pub fn revert_to_default(&self, command: &str) -> Result<()> {
         let command_upper = command.to_uppercase();
 
@@ -331,10 +658,10 @@ impl VoiceCommandStore {
     pub fn get_all_commands(&self) -> Vec<CommandInfo> {
         let guard = self.mappings.read();
 
-        DEFAULT_PHRASES
-            .iter()
+        let mut commands: Vec<CommandInfo> = self
+            .default_phrases()
             .map(|(cmd, default_phrase)| {
-                let custom = guard.get(*cmd);
+                let custom = guard.get(cmd);
                 CommandInfo {
                     command: cmd.to_string(),
                     phrase: custom
@@ -342,28 +669,108 @@ impl VoiceCommandStore {
                         .unwrap_or_else(|| default_phrase.to_string()),
                     is_custom: custom.is_some(),
                     default_phrase: default_phrase.to_string(),
+                    keys: None,
                 }
             })
-            .collect()
+            .collect();
+
+        // User-defined commands, sorted by code for a stable order
+        let mut user_defined: Vec<CommandInfo> = guard
+            .values()
+            .filter_map(|m| {
+                m.keys.as_ref().map(|keys| CommandInfo {
+                    command: m.command.to_uppercase(),
+                    phrase: m.phrase.clone(),
+                    is_custom: true,
+                    default_phrase: String::new(),
+                    keys: Some(keys.clone()),
+                })
+            })
+            .collect();
+        user_defined.sort_by(|a, b| a.command.cmp(&b.command));
+        commands.extend(user_defined);
+
+        commands
     }
 
     /// Match a spoken phrase to a command code.
     /// Returns the command code if found.
     pub fn match_phrase(&self, spoken: &str) -> Option<String> {
-        let spoken_lower = spoken.trim().to_lowercase();
         let guard = self.mappings.read();
+        let exact = self.match_phrase_locked(&guard, spoken);
+        if exact.is_some() || !self.phonetic {
+            return exact;
+        }
+        self.match_phrase_phonetic(&guard, spoken)
+    }
+
+    /// Match a spoken phrase by sound against already-locked mappings.
+    fn match_phrase_phonetic(
+        &self,
+        guard: &HashMap<String, VoiceCommandMapping>,
+        spoken: &str,
+    ) -> Option<String> {
+        let spoken_key = phonetic::phrase_key(spoken)?;
+        let sounds_like = |phrase: &str| phonetic::phrase_key(phrase).as_ref() == Some(&spoken_key);
+
+        // The map's order changes between runs, so pick by command code
+        let mut matches: Vec<&String> = guard
+            .iter()
+            .filter(|(_, mapping)| sounds_like(&mapping.phrase))
+            .map(|(cmd, _)| cmd)
+            .collect();
+        matches.sort();
+        if let Some(&cmd) = matches.first() {
+            if matches.len() > 1 {
+                warn!(
+                    "'{}' sounds like the phrases of {:?}, using {}",
+                    redacted(spoken.trim()),
+                    matches,
+                    cmd
+                );
+            }
+            debug!(
+                "'{}' sounds like '{}'",
+                redacted(spoken.trim()),
+                guard[cmd].phrase
+            );
+            return Some(cmd.clone());
+        }
+
+        for (cmd, default_phrase) in self.default_matches() {
+            if !guard.contains_key(cmd) && sounds_like(default_phrase) {
+                debug!(
+                    "'{}' sounds like '{}'",
+                    redacted(spoken.trim()),
+                    default_phrase
+                );
+                return Some(cmd.to_string());
+            }
+        }
+
+        None
+    }
+
+    /// Match a spoken phrase against already-locked mappings.
+    fn match_phrase_locked(
+        &self,
+        guard: &HashMap<String, VoiceCommandMapping>,
+        spoken: &str,
+    ) -> Option<String> {
+        let spoken_lower = fold_phrase(spoken, self.case_folding());
 
         // Check custom mappings first
         for (cmd, mapping) in guard.iter() {
-            // Trim the stored phrase as well to handle phrases recorded with trailing whitespace
-            if mapping.phrase.trim().to_lowercase() == spoken_lower {
+            // Fold the stored phrase as well to handle phrases recorded with
+            // trailing whitespace or edited by hand
+            if fold_phrase(&mapping.phrase, self.case_folding()) == spoken_lower {
                 return Some(cmd.clone());
             }
         }
 
         // Check default phrases (only for commands without custom mappings)
-        for (cmd, default_phrase) in DEFAULT_PHRASES {
-            if !guard.contains_key(*cmd) && *default_phrase == spoken_lower {
+        for (cmd, default_phrase) in self.default_matches() {
+            if !guard.contains_key(cmd) && default_phrase == spoken_lower {
                 return Some(cmd.to_string());
             }
         }
@@ -371,24 +778,47 @@ impl VoiceCommandStore {
         None
     }
 
-    /// Check if a word could be the first word of a custom 2-word command.
+    /// Check if a word could be the first word of a 2-word command.
     /// Used for look-ahead buffering in command matching.
+    ///
+    /// With phonetic matching, words sounding like the first word of any
+    /// 2-word phrase count too.
     pub fn could_start_two_word_command(&self, word: &str) -> bool {
-        let word_lower = word.to_lowercase();
+        let word_lower = fold_phrase(word, self.case_folding());
         let guard = self.mappings.read();
 
-        // Check custom mappings for 2-word phrases
-        for mapping in guard.values() {
-            let phrase = mapping.phrase.trim().to_lowercase();
-            if phrase.contains(' ') {
-                let first_word = phrase.split_whitespace().next().unwrap_or("");
-                if first_word == word_lower {
-                    return true;
+        let custom = guard.values().map(|m| m.phrase.as_str());
+        let defaults = self
+            .default_matches()
+            .filter(|(cmd, _)| !guard.contains_key(*cmd))
+            .map(|(_, phrase)| phrase);
+        let first_words: Vec<&str> = custom
+            .chain(defaults)
+            .filter_map(|phrase| {
+                let mut words = phrase.split_whitespace();
+                match (words.next(), words.next()) {
+                    (Some(first), Some(_)) => Some(first),
+                    _ => None,
                 }
-            }
+            })
+            .collect();
+
+        if first_words
+            .iter()
+            .any(|first| fold_phrase(first, self.case_folding()) == word_lower)
+        {
+            return true;
         }
 
-        false
+        if !self.phonetic {
+            return false;
+        }
+        let Some(word_key) = phonetic::phrase_key(word) else {
+            return false;
+        };
+        first_words
+            .iter()
+            .any(|first| phonetic::phrase_key(first).as_ref() == Some(&word_key))
     }
 
     /// Get the config file path.
@@ -404,30 +834,59 @@ mod tests {
 
     #[test]
     fn test_normalize_phrase_empty() {
-        assert_eq!(normalize_phrase(""), "");
-        assert_eq!(normalize_phrase("   "), "");
+        assert_eq!(normalize_phrase("", CaseFolding::Default), "");
+        assert_eq!(normalize_phrase("   ", CaseFolding::Default), "");
     }
 
     #[test]
     fn test_normalize_phrase_single_word() {
-        assert_eq!(normalize_phrase("hello"), "hello");
-        assert_eq!(normalize_phrase("HELLO"), "hello");
-        assert_eq!(normalize_phrase("  hello  "), "hello");
+        assert_eq!(normalize_phrase("hello", CaseFolding::Default), "hello");
+        assert_eq!(normalize_phrase("HELLO", CaseFolding::Default), "hello");
+        assert_eq!(normalize_phrase("  hello  ", CaseFolding::Default), "hello");
     }
 
     #[test]
     fn test_normalize_phrase_two_words() {
-        assert_eq!(normalize_phrase("hello world"), "hello world");
-        assert_eq!(normalize_phrase("HELLO WORLD"), "hello world");
-        assert_eq!(normalize_phrase("  hello   world  "), "hello world");
+        assert_eq!(
+            normalize_phrase("hello world", CaseFolding::Default),
+            "hello world"
+        );
+        assert_eq!(
+            normalize_phrase("HELLO WORLD", CaseFolding::Default),
+            "hello world"
+        );
+        assert_eq!(
+            normalize_phrase("  hello   world  ", CaseFolding::Default),
+            "hello world"
+        );
     }
 
     #[test]
     fn test_normalize_phrase_more_than_two_words() {
         // Takes last 2 words
-        assert_eq!(normalize_phrase("one two three"), "two three");
-        assert_eq!(normalize_phrase("a b c d"), "c d");
-        assert_eq!(normalize_phrase("please do enter"), "do enter");
+        assert_eq!(
+            normalize_phrase("one two three", CaseFolding::Default),
+            "two three"
+        );
+        assert_eq!(normalize_phrase("a b c d", CaseFolding::Default), "c d");
+        assert_eq!(
+            normalize_phrase("please do enter", CaseFolding::Default),
+            "do enter"
+        );
+    }
+
+    #[test]
+    fn test_validate_phrase() {
+        assert_eq!(
+            validate_phrase("Do Enter", CaseFolding::Default).unwrap(),
+            "do enter"
+        );
+        assert_eq!(
+            validate_phrase("please do enter", CaseFolding::Default).unwrap(),
+            "do enter"
+        );
+        assert!(validate_phrase("", CaseFolding::Default).is_err());
+        assert!(validate_phrase("   ", CaseFolding::Default).is_err());
     }
 
     #[test]
@@ -464,6 +923,23 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_set_phrase_rejects_phrase_of_other_command() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = VoiceCommandStore::new(temp_dir.path())?;
+
+        assert!(store.check_phrase("ENTER", "Copy").is_err());
+        assert!(store.set_phrase("ENTER", "copy").is_err());
+        assert_eq!(store.get_phrase("ENTER"), "enter");
+        assert_eq!(store.match_phrase("copy"), Some("COPY".to_string()));
+
+        // Setting a command's own phrase is fine
+        assert_eq!(store.check_phrase("COPY", "Copy")?, "copy");
+        store.set_phrase("COPY", "copy")?;
+
+        Ok(())
+    }
+
     #[test]
     fn test_could_start_two_word_command_no_matches() -> Result<()> {
         let temp_dir = TempDir::new()?;
@@ -520,6 +996,118 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_add_custom_command() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = VoiceCommandStore::new(temp_dir.path())?;
+
+        let code = store.add_custom_command("Save file", "Ctrl+S", "Save It")?;
+        assert_eq!(code, "SAVE_FILE");
+        assert_eq!(store.match_phrase("save it"), Some("SAVE_FILE".to_string()));
+        assert_eq!(
+            store.get_key_combo("SAVE_FILE").unwrap().to_string(),
+            "ctrl+s"
+        );
+
+        let info = store
+            .get_all_commands()
+            .into_iter()
+            .find(|c| c.command == "SAVE_FILE")
+            .unwrap();
+        assert_eq!(info.keys.as_deref(), Some("ctrl+s"));
+        assert!(info.is_custom);
+
+        // Changing the phrase keeps the key combination
+        store.set_phrase("SAVE_FILE", "store")?;
+        assert!(store.get_key_combo("SAVE_FILE").is_some());
+
+        // Survives a reload from disk
+        let reloaded = VoiceCommandStore::new(temp_dir.path())?;
+        assert_eq!(
+            reloaded.match_phrase("store"),
+            Some("SAVE_FILE".to_string())
+        );
+        assert!(reloaded.get_key_combo("SAVE_FILE").is_some());
+
+        // Reverting a user-defined command deletes it
+        store.revert_to_default("SAVE_FILE")?;
+        assert!(store.get_key_combo("SAVE_FILE").is_none());
+        assert!(store
+            .get_all_commands()
+            .iter()
+            .all(|c| c.command != "SAVE_FILE"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_custom_command_rejects_invalid_input() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = VoiceCommandStore::new(temp_dir.path())?;
+
+        assert!(store.add_custom_command("", "ctrl+s", "save").is_err());
+        assert!(store.add_custom_command("save!", "ctrl+s", "save").is_err());
+        assert!(store.add_custom_command("copy", "ctrl+c", "grab").is_err());
+        assert!(store
+            .add_custom_command("save", "ctrl+nope", "save")
+            .is_err());
+        assert!(store.add_custom_command("save", "ctrl+s", "").is_err());
+        // Phrase already used by a built-in command
+        assert!(store.add_custom_command("save", "ctrl+s", "paste").is_err());
+
+        store.add_custom_command("save", "ctrl+s", "save")?;
+        assert!(store.add_custom_command("save", "ctrl+s", "keep").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_script() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        std::fs::write(
+            temp_dir.path().join("voice_commands.json"),
+            r#"{"version": 1, "mappings": [
+                {"phrase": "today", "command": "DATE", "created_at": "2026-01-01T00:00:00Z", "script": "date.rhai"},
+                {"phrase": "escape", "command": "ESCAPE", "created_at": "2026-01-01T00:00:00Z", "script": "../escape.rhai"}
+            ]}"#,
+        )?;
+        let store = VoiceCommandStore::new(temp_dir.path())?;
+
+        let (phrase, path) = store.get_script("date").unwrap();
+        assert_eq!(phrase, "today");
+        assert_eq!(path, temp_dir.path().join("scripts").join("date.rhai"));
+
+        assert!(store.get_script("ESCAPE").is_none());
+        assert!(store.get_script("ENTER").is_none());
+
+        // Changing the phrase keeps the script
+        store.set_phrase("DATE", "what day")?;
+        assert_eq!(store.get_script("DATE").unwrap().0, "what day");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_shell() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        std::fs::write(
+            temp_dir.path().join("voice_commands.json"),
+            r#"{"version": 1, "mappings": [
+                {"phrase": "build it", "command": "BUILD", "created_at": "2026-01-01T00:00:00Z", "shell": "build"}
+            ]}"#,
+        )?;
+        let store = VoiceCommandStore::new(temp_dir.path())?;
+
+        assert_eq!(store.get_shell("build"), Some("build".to_string()));
+        assert_eq!(store.get_shell("ENTER"), None);
+
+        // Changing the phrase keeps the shell command
+        store.set_phrase("BUILD", "make it")?;
+        assert_eq!(store.get_shell("BUILD"), Some("build".to_string()));
+
+        Ok(())
+    }
+
     #[test]
     fn test_match_phrase_two_words() -> Result<()> {
         let temp_dir = TempDir::new()?;
@@ -534,4 +1122,97 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_phonetic_matching_is_opt_in() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        // Exact mode: a homophone is just text
+        let store = VoiceCommandStore::new(temp_dir.path())?;
+        assert_eq!(store.match_phrase("write click"), None);
+        assert_eq!(store.match_phrase("paws"), None);
+        assert!(!store.could_start_two_word_command("write"));
+
+        let store = VoiceCommandStore::new(temp_dir.path())?.with_phonetic_matching(true);
+        assert_eq!(
+            store.match_phrase("write click"),
+            Some("RIGHT_CLICK".to_string())
+        );
+        assert_eq!(store.match_phrase("paws"), Some("PAUSE".to_string()));
+        assert!(store.could_start_two_word_command("write"));
+        assert_eq!(store.match_phrase("hello"), None);
+
+        // Custom phrases too, and exact matches win
+        store.set_phrase("COPY", "grab knight")?;
+        assert_eq!(store.match_phrase("grab night"), Some("COPY".to_string()));
+        store.set_phrase("CUT", "grab night")?;
+        assert_eq!(store.match_phrase("grab night"), Some("CUT".to_string()));
+        assert_eq!(store.match_phrase("grab knight"), Some("COPY".to_string()));
+        // Sounding like both picks the same one every time
+        assert_eq!(store.match_phrase("grab nite"), Some("COPY".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_turkish_case_folding() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = VoiceCommandStore::new(temp_dir.path())?;
+        store.set_phrase("ENTER", "ışık")?;
+        store.set_phrase("COPY", "ılık su")?;
+
+        // Naive lowercasing turns "I" into a dotted "i"
+        assert_eq!(store.match_phrase("IŞIK"), None);
+        assert!(!store.could_start_two_word_command("ILIK"));
+
+        let store = VoiceCommandStore::new(temp_dir.path())?
+            .with_case_folding(CaseFolding::for_locale("tr_TR.UTF-8"));
+        assert_eq!(store.match_phrase("IŞIK"), Some("ENTER".to_string()));
+        assert_eq!(store.match_phrase("Işık"), Some("ENTER".to_string()));
+        assert_eq!(store.match_phrase("ILIK SU"), Some("COPY".to_string()));
+        assert!(store.could_start_two_word_command("ILIK"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_turkish_phrase_typed_in_uppercase() -> Result<()> {
+        let turkish = CaseFolding::for_locale("tr_TR.UTF-8");
+        assert_eq!(validate_phrase("IŞIK", turkish)?, "ışık");
+
+        let temp_dir = TempDir::new()?;
+        let store = VoiceCommandStore::new(temp_dir.path())?.with_case_folding(turkish);
+        store.set_phrase("ENTER", "IŞIK")?;
+        assert_eq!(store.get_phrase("ENTER"), "ışık");
+        assert_eq!(store.match_phrase("ışık"), Some("ENTER".to_string()));
+
+        // Phrases edited by hand are folded the same way when loaded
+        std::fs::write(
+            temp_dir.path().join("voice_commands.json"),
+            r#"{"version": 1, "mappings": [
+                {"phrase": "ILIK SU", "command": "COPY", "created_at": "2026-01-01T00:00:00Z"}
+            ]}"#,
+        )?;
+        let store = VoiceCommandStore::new(temp_dir.path())?.with_case_folding(turkish);
+        assert_eq!(store.get_phrase("COPY"), "ılık su");
+        assert_eq!(store.match_phrase("ılık su"), Some("COPY".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_alias() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = VoiceCommandStore::new(temp_dir.path())?;
+
+        assert_eq!(store.match_phrase("dedent"), Some("DEDENT".to_string()));
+        assert_eq!(store.match_phrase("Outdent"), Some("DEDENT".to_string()));
+
+        // A custom phrase replaces the alias too
+        store.set_phrase("DEDENT", "unindent")?;
+        assert_eq!(store.match_phrase("unindent"), Some("DEDENT".to_string()));
+        assert_eq!(store.match_phrase("outdent"), None);
+
+        Ok(())
+    }
 }
@@ -1,13 +1,26 @@
 // Copyright 2026 Daniel Pelikan
-// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
 
 //! Confirmation dialog for pairing approval.
 
 use gtk4::prelude::*;
 use gtk4::{Application, ApplicationWindow, Box as GtkBox, Button, Label, Orientation};
+use speech2prompt_protocol::crypto::ecdh;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::oneshot;
-use tracing::info;
+use tracing::{info, warn};
 
 /// Result of confirmation dialog.
 pub enum ConfirmationResult {
@@ -19,10 +32,17 @@ pub enum ConfirmationResult {
 
 /// Show connection confirmation dialog.
 ///
-/// Returns Approved if user clicks Yes, Rejected if user clicks No or closes the dialog.
+/// The device name is chosen by the phone, so the stable device id and a
+/// fingerprint of the phone's public key are shown as well.
+///
+/// Returns Approved if user clicks Yes, Rejected if user clicks No, closes
+/// the dialog, or doesn't answer within `timeout`.
 pub fn show_confirmation_dialog(
     app: &Application,
     device_name: &str,
+    device_id: &str,
+    public_key: &str,
+    timeout: Option<Duration>,
 ) -> oneshot::Receiver<ConfirmationResult> {
     info!(
         "🪟 Creating confirmation dialog for device: {}",
@@ -35,7 +55,7 @@ pub fn show_confirmation_dialog(
         .application(app)
         .title("Speech2Prompt - Pairing Request")
         .default_width(350)
-        .default_height(180)
+        .default_height(220)
         .modal(true)
         .resizable(false)
         .build();
@@ -62,6 +82,19 @@ pub fn show_confirmation_dialog(
     let question = Label::new(Some("wants to connect to this computer."));
     main_box.append(&question);
 
+    // Identity that the phone can't pick freely
+    let fingerprint = ecdh::fingerprint(public_key).unwrap_or_else(|e| {
+        warn!("No fingerprint for the pairing key: {}", e);
+        "unknown".to_string()
+    });
+    let identity = Label::new(Some(&format!(
+        "Device ID: {}\nKey fingerprint: {}",
+        device_id, fingerprint
+    )));
+    identity.add_css_class("monospace");
+    identity.set_selectable(true);
+    main_box.append(&identity);
+
     // Security note
     let note = Label::new(Some("Only approve if you initiated this connection."));
     note.add_css_class("dim-label");
@@ -118,19 +151,21 @@ pub fn show_confirmation_dialog(
         glib::Propagation::Proceed
     });
 
-    // Auto-close after 60 seconds (reject)
+    // Auto-close after the timeout (reject)
     // Only log timeout if we're actually timing out (tx not already taken)
-    let window_timeout = window.clone();
-    let tx_timeout = tx.clone();
-    glib::timeout_add_seconds_local_once(60, move || {
-        // Only log and act if the dialog wasn't already handled
-        if let Some(tx) = tx_timeout.lock().unwrap().take() {
-            info!("⏱️  Dialog timeout (60s) - auto-rejecting");
-            let _ = tx.send(ConfirmationResult::Rejected);
-            window_timeout.close();
-        }
-        // If tx was already taken, the dialog was handled - do nothing
-    });
+    if let Some(timeout) = timeout {
+        let window_timeout = window.clone();
+        let tx_timeout = tx.clone();
+        glib::timeout_add_local_once(timeout, move || {
+            // Only log and act if the dialog wasn't already handled
+            if let Some(tx) = tx_timeout.lock().unwrap().take() {
+                info!("⏱️  Dialog timeout ({}s) - auto-rejecting", timeout.as_secs());
+                let _ = tx.send(ConfirmationResult::Rejected);
+                window_timeout.close();
+            }
+            // If tx was already taken, the dialog was handled - do nothing
+        });
+    }
 
     info!("📺 Presenting dialog window to user...");
     window.present();
@@ -1,21 +1,35 @@
 // Copyright 2024 Speech2Prompt Contributors
-// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
 
 //! Manage Commands window for recording and configuring voice command phrases.
 
 use gtk4::prelude::*;
 use gtk4::{
-    Application, ApplicationWindow, Box as GtkBox, Button, Label, ListBox, ListBoxRow, Orientation,
-    ScrolledWindow, SelectionMode,
+    Application, ApplicationWindow, Box as GtkBox, Button, Entry, Label, ListBox, ListBoxRow,
+    Orientation, ScrolledWindow, SearchEntry, SelectionMode, Window,
 };
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::mpsc;
 use tracing::{error, info};
 
-use crate::state::AppState;
+use crate::commands::CombinedMatcher;
+use crate::input::KeyCombo;
+use crate::state::{AppState, StateChange};
 use crate::storage::{CommandInfo, VoiceCommandStore};
 
 /// Events from the manage commands window.
@@ -27,6 +41,17 @@ pub enum ManageCommandsEvent {
     CancelRecording,
     /// Revert command to default phrase.
     RevertToDefault(String),
+    /// Set a typed phrase for a command (command, normalized phrase).
+    SetPhrase(String, String),
+    /// Add a user-defined command that presses a key combination.
+    AddCommand {
+        /// Command name as typed (e.g. "save file").
+        name: String,
+        /// Key combination (e.g. "ctrl+s").
+        keys: String,
+        /// Normalized phrase.
+        phrase: String,
+    },
 }
 
 /// Create and show the Manage Commands window (non-modal).
@@ -57,11 +82,37 @@ pub fn show_manage_commands_window(
     main_box.append(&header);
 
     let subtitle = Label::new(Some(
-        "Click \"Record\" to set a custom phrase for a command.",
+        "Click \"Record\" to speak a custom phrase or \"Edit\" to type one.",
     ));
     subtitle.add_css_class("dim-label");
     main_box.append(&subtitle);
 
+    // Search: only show commands whose name or phrase matches
+    let search_entry = SearchEntry::new();
+    search_entry.set_placeholder_text(Some("Search commands"));
+    main_box.append(&search_entry);
+
+    // Test phrase: show what saying it would do, without typing anything
+    let test_entry = Entry::new();
+    test_entry.set_placeholder_text(Some("Test phrase, e.g. \"hello new line\""));
+    main_box.append(&test_entry);
+
+    let test_result = Label::new(None);
+    test_result.set_halign(gtk4::Align::Start);
+    test_result.set_wrap(true);
+    test_result.add_css_class("dim-label");
+    main_box.append(&test_result);
+
+    let matcher = CombinedMatcher::new(store.clone());
+    test_entry.connect_changed(move |entry| {
+        let phrase = entry.text();
+        if phrase.trim().is_empty() {
+            test_result.set_text("");
+        } else {
+            test_result.set_text(&matcher.explain(&phrase));
+        }
+    });
+
     // Command list in scrolled window
     let scrolled = ScrolledWindow::builder()
         .hexpand(true)
@@ -75,7 +126,21 @@ pub fn show_manage_commands_window(
     main_box.append(&scrolled);
 
     // Populate command list
-    populate_command_list(&list_box, &store, &state, &event_tx);
+    populate_command_list(&list_box, &store, &state, &event_tx, "");
+
+    let list_box_ref = list_box.clone();
+    let store_ref = store.clone();
+    let state_ref = state.clone();
+    let event_tx_ref = event_tx.clone();
+    search_entry.connect_search_changed(move |entry| {
+        populate_command_list(
+            &list_box_ref,
+            &store_ref,
+            &state_ref,
+            &event_tx_ref,
+            &entry.text(),
+        );
+    });
 
     // Legend and close button
     let footer_box = GtkBox::new(Orientation::Horizontal, 8);
@@ -86,6 +151,16 @@ pub fn show_manage_commands_window(
     legend.set_halign(gtk4::Align::Start);
     footer_box.append(&legend);
 
+    let add_button = Button::with_label("Add Command...");
+    let store_add = store.clone();
+    let tx_add = event_tx.clone();
+    add_button.connect_clicked(move |button| {
+        info!("Add Command button clicked");
+        let parent = button.root().and_downcast::<Window>();
+        show_add_command_dialog(parent.as_ref(), store_add.clone(), tx_add.clone());
+    });
+    footer_box.append(&add_button);
+
     let close_button = Button::with_label("Close");
     close_button.set_width_request(100);
     footer_box.append(&close_button);
@@ -99,18 +174,54 @@ pub fn show_manage_commands_window(
         window_close.close();
     });
 
-    // Refresh list periodically to show recording state changes
+    // Show recording starting and stopping right away
     let list_box_ref = list_box.clone();
     let store_ref = store.clone();
     let state_ref = state.clone();
     let event_tx_ref = event_tx.clone();
+    let search_ref = search_entry.clone();
+    let mut changes = state.subscribe();
+
+    glib::MainContext::default().spawn_local(async move {
+        loop {
+            match changes.recv().await {
+                Ok(StateChange::Recording) | Err(RecvError::Lagged(_)) => {}
+                Ok(_) => continue,
+                Err(RecvError::Closed) => break,
+            }
+            if list_box_ref.parent().is_none() {
+                // Window closed
+                break;
+            }
+            populate_command_list(
+                &list_box_ref,
+                &store_ref,
+                &state_ref,
+                &event_tx_ref,
+                &search_ref.text(),
+            );
+        }
+    });
+
+    // Phrase edits are saved by the main loop, refresh periodically for them
+    let list_box_ref = list_box.clone();
+    let store_ref = store.clone();
+    let state_ref = state.clone();
+    let event_tx_ref = event_tx.clone();
+    let search_ref = search_entry.clone();
 
     glib::timeout_add_local(Duration::from_millis(500), move || {
         if list_box_ref.parent().is_none() {
             // Window closed
             return glib::ControlFlow::Break;
         }
-        populate_command_list(&list_box_ref, &store_ref, &state_ref, &event_tx_ref);
+        populate_command_list(
+            &list_box_ref,
+            &store_ref,
+            &state_ref,
+            &event_tx_ref,
+            &search_ref.text(),
+        );
         glib::ControlFlow::Continue
     });
 
@@ -120,12 +231,13 @@ pub fn show_manage_commands_window(
     event_rx
 }
 
-/// Populate the command list.
+/// Populate the command list with the commands matching `filter`.
 fn populate_command_list(
     list_box: &ListBox,
-    store: &VoiceCommandStore,
+    store: &Arc<VoiceCommandStore>,
     state: &AppState,
     event_tx: &mpsc::UnboundedSender<ManageCommandsEvent>,
+    filter: &str,
 ) {
     // Remove existing rows
     while let Some(child) = list_box.first_child() {
@@ -135,16 +247,29 @@ fn populate_command_list(
     let commands = store.get_all_commands();
     let recording_command = state.get_recording_command();
 
-    for cmd_info in commands {
-        let row = create_command_row(&cmd_info, &recording_command, event_tx);
+    for cmd_info in commands
+        .iter()
+        .filter(|cmd_info| matches_filter(cmd_info, filter))
+    {
+        let row = create_command_row(cmd_info, &recording_command, store, event_tx);
         list_box.append(&row);
     }
 }
 
+/// Whether a command's name or phrase contains `filter`, ignoring case.
+///
+/// An empty filter matches every command.
+fn matches_filter(cmd_info: &CommandInfo, filter: &str) -> bool {
+    let filter = filter.trim().to_lowercase();
+    cmd_info.command.to_lowercase().contains(&filter)
+        || cmd_info.phrase.to_lowercase().contains(&filter)
+}
+
 /// Create a row for a command.
 fn create_command_row(
     cmd_info: &CommandInfo,
     recording_command: &Option<String>,
+    store: &Arc<VoiceCommandStore>,
     event_tx: &mpsc::UnboundedSender<ManageCommandsEvent>,
 ) -> ListBoxRow {
     let row = ListBoxRow::new();
@@ -165,7 +290,9 @@ fn create_command_row(
     hbox.append(&cmd_label);
 
     // Current phrase
-    let phrase_text = if cmd_info.is_custom {
+    let phrase_text = if let Some(keys) = &cmd_info.keys {
+        format!("\"{}\" ✱ → {}", cmd_info.phrase, keys)
+    } else if cmd_info.is_custom {
         format!("\"{}\" ✱", cmd_info.phrase)
     } else {
         format!("\"{}\" (default)", cmd_info.phrase)
@@ -218,8 +345,35 @@ fn create_command_row(
     });
     hbox.append(&record_button);
 
-    // Revert button
-    let revert_button = Button::with_label("Revert");
+    // Edit button (type a phrase instead of recording it)
+    let edit_button = Button::with_label("Edit");
+    edit_button.set_width_request(80);
+    edit_button.set_sensitive(recording_command.is_none());
+
+    let command_edit = cmd_info.command.clone();
+    let phrase_edit = cmd_info.phrase.clone();
+    let store_edit = store.clone();
+    let tx_edit = event_tx.clone();
+    edit_button.connect_clicked(move |button| {
+        info!("Edit button clicked for command: {}", command_edit);
+        let parent = button.root().and_downcast::<Window>();
+        show_phrase_entry_dialog(
+            parent.as_ref(),
+            &command_edit,
+            &phrase_edit,
+            store_edit.clone(),
+            tx_edit.clone(),
+        );
+    });
+    hbox.append(&edit_button);
+
+    // Revert button (deletes user-defined commands)
+    let revert_label = if cmd_info.keys.is_some() {
+        "Delete"
+    } else {
+        "Revert"
+    };
+    let revert_button = Button::with_label(revert_label);
     revert_button.set_width_request(80);
     revert_button.set_sensitive(cmd_info.is_custom && !is_recording_this);
 
@@ -234,11 +388,239 @@ fn create_command_row(
     row
 }
 
-/// Recording timeout duration in seconds.
-const RECORDING_TIMEOUT_SECS: u64 = 30;
+/// Validate a typed phrase and emit a `SetPhrase` event for it.
+///
+/// Returns an error message suitable for display if the phrase is invalid
+/// or already used by another command, so the dialog can stay open.
+fn submit_phrase(
+    store: &VoiceCommandStore,
+    event_tx: &mpsc::UnboundedSender<ManageCommandsEvent>,
+    command: &str,
+    phrase: &str,
+) -> Result<(), String> {
+    let phrase = store
+        .check_phrase(command, phrase)
+        .map_err(|e| e.to_string())?;
+    event_tx
+        .send(ManageCommandsEvent::SetPhrase(command.to_string(), phrase))
+        .map_err(|e| format!("Failed to send SetPhrase event: {}", e))
+}
+
+/// Validate a new command and emit an `AddCommand` event for it.
+///
+/// Returns an error message suitable for display if the input is invalid or
+/// clashes with an existing command, so the dialog can stay open.
+fn submit_new_command(
+    store: &VoiceCommandStore,
+    event_tx: &mpsc::UnboundedSender<ManageCommandsEvent>,
+    name: &str,
+    keys: &str,
+    phrase: &str,
+) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Command name cannot be empty".to_string());
+    }
+    let keys = KeyCombo::parse(keys)
+        .ok_or_else(|| format!("Unknown key combination '{}'", keys.trim()))?;
+    let phrase = store
+        .check_custom_command(name, &keys.to_string(), phrase)
+        .map_err(|e| e.to_string())?;
+    event_tx
+        .send(ManageCommandsEvent::AddCommand {
+            name: name.to_string(),
+            keys: keys.to_string(),
+            phrase,
+        })
+        .map_err(|e| format!("Failed to send AddCommand event: {}", e))
+}
+
+/// Show a dialog for adding a user-defined command.
+fn show_add_command_dialog(
+    parent: Option<&Window>,
+    store: Arc<VoiceCommandStore>,
+    event_tx: mpsc::UnboundedSender<ManageCommandsEvent>,
+) {
+    let dialog = Window::builder()
+        .title("Add Command")
+        .default_width(350)
+        .modal(true)
+        .resizable(false)
+        .build();
+    dialog.set_transient_for(parent);
+
+    let main_box = GtkBox::new(Orientation::Vertical, 12);
+    main_box.set_margin_top(24);
+    main_box.set_margin_bottom(24);
+    main_box.set_margin_start(24);
+    main_box.set_margin_end(24);
+
+    // Title
+    let title = Label::new(Some("New voice command"));
+    title.add_css_class("title-3");
+    main_box.append(&title);
+
+    // Entries
+    let name_entry = Entry::new();
+    name_entry.set_placeholder_text(Some("Name, e.g. save file"));
+    main_box.append(&name_entry);
+
+    let keys_entry = Entry::new();
+    keys_entry.set_placeholder_text(Some("Keys, e.g. ctrl+s"));
+    main_box.append(&keys_entry);
+
+    let phrase_entry = Entry::new();
+    phrase_entry.set_placeholder_text(Some("Phrase, one or two words"));
+    main_box.append(&phrase_entry);
 
-/// Show the recording dialog with 30-second timeout.
-pub fn show_recording_dialog(app: &Application, command: &str, state: Arc<AppState>) {
+    // Validation error
+    let error_label = Label::new(None);
+    error_label.add_css_class("error");
+    error_label.set_visible(false);
+    main_box.append(&error_label);
+
+    // Buttons
+    let button_box = GtkBox::new(Orientation::Horizontal, 12);
+    button_box.set_halign(gtk4::Align::End);
+    let cancel_button = Button::with_label("Cancel");
+    cancel_button.set_width_request(80);
+    let add_button = Button::with_label("Add");
+    add_button.add_css_class("suggested-action");
+    add_button.set_width_request(80);
+    button_box.append(&cancel_button);
+    button_box.append(&add_button);
+    main_box.append(&button_box);
+
+    dialog.set_child(Some(&main_box));
+
+    // Cancel handler
+    let dialog_cancel = dialog.clone();
+    cancel_button.connect_clicked(move |_| {
+        dialog_cancel.close();
+    });
+
+    // Add handler (button or Enter in the phrase entry)
+    let add = {
+        let dialog = dialog.clone();
+        let name_entry = name_entry.clone();
+        let keys_entry = keys_entry.clone();
+        let phrase_entry = phrase_entry.clone();
+        move || match submit_new_command(
+            &store,
+            &event_tx,
+            &name_entry.text(),
+            &keys_entry.text(),
+            &phrase_entry.text(),
+        ) {
+            Ok(()) => dialog.close(),
+            Err(message) => {
+                error_label.set_text(&message);
+                error_label.set_visible(true);
+            }
+        }
+    };
+    let add = Rc::new(add);
+    let add_click = add.clone();
+    add_button.connect_clicked(move |_| add_click());
+    phrase_entry.connect_activate(move |_| add());
+
+    dialog.present();
+    name_entry.grab_focus();
+}
+
+/// Show a small dialog for typing a phrase for a command.
+fn show_phrase_entry_dialog(
+    parent: Option<&Window>,
+    command: &str,
+    current_phrase: &str,
+    store: Arc<VoiceCommandStore>,
+    event_tx: mpsc::UnboundedSender<ManageCommandsEvent>,
+) {
+    let dialog = Window::builder()
+        .title("Edit Phrase")
+        .default_width(350)
+        .modal(true)
+        .resizable(false)
+        .build();
+    dialog.set_transient_for(parent);
+
+    let main_box = GtkBox::new(Orientation::Vertical, 12);
+    main_box.set_margin_top(24);
+    main_box.set_margin_bottom(24);
+    main_box.set_margin_start(24);
+    main_box.set_margin_end(24);
+
+    // Title
+    let title = Label::new(Some(&format!("Phrase for {}", command)));
+    title.add_css_class("title-3");
+    main_box.append(&title);
+
+    // Phrase entry
+    let entry = Entry::new();
+    entry.set_text(current_phrase);
+    entry.set_placeholder_text(Some("One or two words"));
+    main_box.append(&entry);
+
+    // Validation error
+    let error_label = Label::new(None);
+    error_label.add_css_class("error");
+    error_label.set_visible(false);
+    main_box.append(&error_label);
+
+    // Buttons
+    let button_box = GtkBox::new(Orientation::Horizontal, 12);
+    button_box.set_halign(gtk4::Align::End);
+    let cancel_button = Button::with_label("Cancel");
+    cancel_button.set_width_request(80);
+    let save_button = Button::with_label("Save");
+    save_button.add_css_class("suggested-action");
+    save_button.set_width_request(80);
+    button_box.append(&cancel_button);
+    button_box.append(&save_button);
+    main_box.append(&button_box);
+
+    dialog.set_child(Some(&main_box));
+
+    // Cancel handler
+    let dialog_cancel = dialog.clone();
+    cancel_button.connect_clicked(move |_| {
+        dialog_cancel.close();
+    });
+
+    // Save handler (button or Enter in the entry)
+    let save = {
+        let dialog = dialog.clone();
+        let entry = entry.clone();
+        let command = command.to_string();
+        move || match submit_phrase(&store, &event_tx, &command, &entry.text()) {
+            Ok(()) => dialog.close(),
+            Err(message) => {
+                error_label.set_text(&message);
+                error_label.set_visible(true);
+            }
+        }
+    };
+    let save = Rc::new(save);
+    let save_click = save.clone();
+    save_button.connect_clicked(move |_| save_click());
+    entry.connect_activate(move |_| save());
+
+    dialog.present();
+    entry.grab_focus();
+}
+
+/// Seconds left before the recording dialog times out.
+fn remaining_secs(timeout_secs: u64, elapsed: Duration) -> u64 {
+    timeout_secs.saturating_sub(elapsed.as_secs())
+}
+
+/// Show the recording dialog, closing it after `timeout_secs` seconds.
+pub fn show_recording_dialog(
+    app: &Application,
+    command: &str,
+    state: Arc<AppState>,
+    timeout_secs: u64,
+) {
     let dialog = ApplicationWindow::builder()
         .application(app)
         .title("Recording...")
@@ -270,7 +652,7 @@ pub fn show_recording_dialog(app: &Application, command: &str, state: Arc<AppSta
     // Countdown timer label
     let countdown_label = Label::new(Some(&format!(
         "🎤 Listening... ({}s remaining)",
-        RECORDING_TIMEOUT_SECS
+        timeout_secs
     )));
     countdown_label.add_css_class("title-4");
     main_box.append(&countdown_label);
@@ -317,8 +699,7 @@ pub fn show_recording_dialog(app: &Application, command: &str, state: Arc<AppSta
         }
 
         // Check timeout
-        let elapsed = start_time_ref.borrow().elapsed().as_secs();
-        let remaining = RECORDING_TIMEOUT_SECS.saturating_sub(elapsed);
+        let remaining = remaining_secs(timeout_secs, start_time_ref.borrow().elapsed());
 
         if remaining == 0 {
             // Timeout reached
@@ -337,3 +718,133 @@ pub fn show_recording_dialog(app: &Application, command: &str, state: Arc<AppSta
     dialog.present();
     info!("Recording dialog opened for command: {}", command);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_remaining_secs() {
+        assert_eq!(remaining_secs(30, Duration::ZERO), 30);
+        assert_eq!(remaining_secs(30, Duration::from_millis(1500)), 29);
+        assert_eq!(remaining_secs(60, Duration::from_secs(45)), 15);
+        assert_eq!(remaining_secs(30, Duration::from_secs(30)), 0);
+        assert_eq!(remaining_secs(30, Duration::from_secs(90)), 0);
+    }
+
+    fn command_info(command: &str, phrase: &str) -> CommandInfo {
+        CommandInfo {
+            command: command.to_string(),
+            phrase: phrase.to_string(),
+            is_custom: false,
+            default_phrase: phrase.to_string(),
+            keys: None,
+        }
+    }
+
+    #[test]
+    fn test_matches_filter() {
+        let enter = command_info("ENTER", "new line");
+        let save = command_info("SAVE_FILE", "save it");
+
+        // By name or phrase, ignoring case and surrounding spaces
+        assert!(matches_filter(&enter, "enter"));
+        assert!(matches_filter(&enter, " Line "));
+        assert!(matches_filter(&save, "save_"));
+        assert!(!matches_filter(&enter, "save"));
+        assert!(!matches_filter(&save, "line"));
+
+        // No filter shows everything
+        assert!(matches_filter(&enter, ""));
+        assert!(matches_filter(&save, "  "));
+    }
+
+    #[test]
+    fn test_submit_phrase_sets_store_phrase() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = VoiceCommandStore::new(temp_dir.path()).unwrap();
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+
+        submit_phrase(&store, &event_tx, "ENTER", "  Please Do   Enter ").unwrap();
+
+        // Apply the event the way the main loop does
+        match event_rx.try_recv().unwrap() {
+            ManageCommandsEvent::SetPhrase(command, phrase) => {
+                assert_eq!(command, "ENTER");
+                assert_eq!(phrase, "do enter");
+                store.set_phrase(&command, &phrase).unwrap();
+            }
+            other => panic!("Expected SetPhrase, got {:?}", other),
+        }
+
+        assert_eq!(store.get_phrase("ENTER"), "do enter");
+        assert!(store.has_custom_phrase("ENTER"));
+    }
+
+    #[test]
+    fn test_submit_empty_phrase_is_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = VoiceCommandStore::new(temp_dir.path()).unwrap();
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+
+        assert!(submit_phrase(&store, &event_tx, "ENTER", "   ").is_err());
+        assert!(event_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_submit_new_command_adds_to_store() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = VoiceCommandStore::new(temp_dir.path()).unwrap();
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+
+        submit_new_command(&store, &event_tx, " save file ", "Ctrl+S", "Save It").unwrap();
+
+        match event_rx.try_recv().unwrap() {
+            ManageCommandsEvent::AddCommand { name, keys, phrase } => {
+                assert_eq!(keys, "ctrl+s");
+                assert_eq!(phrase, "save it");
+                store.add_custom_command(&name, &keys, &phrase).unwrap();
+            }
+            other => panic!("Expected AddCommand, got {:?}", other),
+        }
+
+        assert_eq!(store.match_phrase("save it"), Some("SAVE_FILE".to_string()));
+    }
+
+    #[test]
+    fn test_submit_new_command_rejects_invalid_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = VoiceCommandStore::new(temp_dir.path()).unwrap();
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+
+        assert!(submit_new_command(&store, &event_tx, "save", "ctrl+", "save").is_err());
+        assert!(submit_new_command(&store, &event_tx, "", "ctrl+s", "save").is_err());
+        assert!(event_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_submit_clashing_input_is_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = VoiceCommandStore::new(temp_dir.path()).unwrap();
+        store
+            .add_custom_command("save file", "ctrl+s", "save it")
+            .unwrap();
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+
+        // Name of an existing command
+        let err =
+            submit_new_command(&store, &event_tx, "Save File", "ctrl+s", "store it").unwrap_err();
+        assert!(err.contains("already exists"), "{}", err);
+        // Phrase of another command
+        let err =
+            submit_new_command(&store, &event_tx, "save all", "ctrl+s", "Save It").unwrap_err();
+        assert!(err.contains("already used"), "{}", err);
+        let err = submit_phrase(&store, &event_tx, "ENTER", "save it").unwrap_err();
+        assert!(err.contains("already used"), "{}", err);
+        assert!(event_rx.try_recv().is_err());
+
+        // A command may keep its own phrase
+        submit_phrase(&store, &event_tx, "SAVE_FILE", "save it").unwrap();
+    }
+}
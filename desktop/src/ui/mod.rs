@@ -16,10 +16,18 @@
 
 mod confirmation_dialog;
 mod manage_commands;
+mod notifications;
+mod qr_pairing;
+mod theme;
 mod tray;
 
 pub use confirmation_dialog::{show_confirmation_dialog, ConfirmationResult};
 pub use manage_commands::{
     show_manage_commands_window, show_recording_dialog, ManageCommandsEvent,
 };
+pub use notifications::{
+    ask_pairing, create_notifier, notify_event, DesktopNotifier, Notification, Notifier,
+};
+pub use qr_pairing::show_qr_pairing_window;
+pub use theme::follow_system_theme;
 pub use tray::{run_tray, TrayAction};
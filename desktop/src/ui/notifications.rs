@@ -0,0 +1,390 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Desktop notifications for connection events.
+//!
+//! Useful when the tray icon is hidden: the user still learns when a phone
+//! connects, disconnects, or asks to pair. Pairing requests can also be
+//! answered from a notification instead of the confirmation dialog.
+
+use anyhow::{anyhow, Result};
+use speech2prompt_protocol::crypto::ecdh;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tracing::{debug, info, warn};
+
+use super::ConfirmationResult;
+use crate::bluetooth::ConnectionEvent;
+
+/// Application name shown by the notification server.
+const APP_NAME: &str = "Speech2Prompt";
+
+/// Icon used for all notifications.
+const ICON_NAME: &str = "speech2prompt";
+
+/// Action id of the approve button.
+const APPROVE_ACTION: &str = "approve";
+
+/// Action id of the reject button.
+const REJECT_ACTION: &str = "reject";
+
+/// Content of a desktop notification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    /// Short one-line summary.
+    pub summary: String,
+    /// Longer body text.
+    pub body: String,
+}
+
+impl Notification {
+    /// Build the notification for a connection event.
+    ///
+    /// Returns `None` for events that don't warrant a notification.
+    pub fn for_event(event: &ConnectionEvent) -> Option<Self> {
+        match event {
+            ConnectionEvent::Connected { device_name, .. } => Some(Self {
+                summary: "Phone connected".to_string(),
+                body: format!("Connected to {}", device_name),
+            }),
+            ConnectionEvent::Disconnected => Some(Self {
+                summary: "Phone disconnected".to_string(),
+                body: "Waiting for connection...".to_string(),
+            }),
+            ConnectionEvent::PairRequested {
+                device_id,
+                device_name,
+                public_key,
+                ..
+            } => Some(Self::pairing_request(
+                device_name.as_deref().unwrap_or(device_id),
+                device_id,
+                public_key,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Build the notification asking to approve a pairing request.
+    ///
+    /// Like the dialog, it shows the device id and key fingerprint, as the
+    /// phone picks its name freely.
+    pub fn pairing_request(device_name: &str, device_id: &str, public_key: &str) -> Self {
+        let fingerprint = ecdh::fingerprint(public_key).unwrap_or_else(|e| {
+            warn!("No fingerprint for the pairing key: {}", e);
+            "unknown".to_string()
+        });
+        Self {
+            summary: "Pairing request".to_string(),
+            body: format!(
+                "\"{}\" wants to connect to this computer.\nDevice ID: {}\nKey fingerprint: {}",
+                device_name, device_id, fingerprint
+            ),
+        }
+    }
+}
+
+/// Trait for notification backends.
+pub trait Notifier: Send + Sync {
+    /// Show a notification.
+    fn show(&self, notification: &Notification) -> Result<()>;
+
+    /// Show a notification with Approve and Reject buttons and wait for
+    /// the answer, expiring after `timeout` if given.
+    fn ask(
+        &self,
+        _notification: &Notification,
+        _timeout: Option<Duration>,
+    ) -> Result<ConfirmationResult> {
+        Err(anyhow!("Notifications can't ask for approval"))
+    }
+}
+
+/// Notifier using the freedesktop notification service (D-Bus).
+pub struct DesktopNotifier;
+
+impl Notifier for DesktopNotifier {
+    fn show(&self, notification: &Notification) -> Result<()> {
+        notify_rust::Notification::new()
+            .appname(APP_NAME)
+            .icon(ICON_NAME)
+            .summary(&notification.summary)
+            .body(&notification.body)
+            .show()
+            .map_err(|e| anyhow!("Failed to show notification: {}", e))?;
+        Ok(())
+    }
+
+    fn ask(
+        &self,
+        notification: &Notification,
+        timeout: Option<Duration>,
+    ) -> Result<ConfirmationResult> {
+        let timeout = match timeout {
+            Some(timeout) => notify_rust::Timeout::Milliseconds(
+                u32::try_from(timeout.as_millis()).unwrap_or(u32::MAX),
+            ),
+            None => notify_rust::Timeout::Never,
+        };
+        let handle = notify_rust::Notification::new()
+            .appname(APP_NAME)
+            .icon(ICON_NAME)
+            .summary(&notification.summary)
+            .body(&notification.body)
+            .action(APPROVE_ACTION, "Approve")
+            .action(REJECT_ACTION, "Reject")
+            .timeout(timeout)
+            .show()
+            .map_err(|e| anyhow!("Failed to show notification: {}", e))?;
+
+        // Dismissing or letting it time out rejects
+        let mut result = ConfirmationResult::Rejected;
+        handle.wait_for_action(|action| {
+            if action == APPROVE_ACTION {
+                result = ConfirmationResult::Approved;
+            }
+        });
+        Ok(result)
+    }
+}
+
+/// Notifier that discards notifications (used when disabled in config).
+pub struct DisabledNotifier;
+
+impl Notifier for DisabledNotifier {
+    fn show(&self, notification: &Notification) -> Result<()> {
+        debug!("Notifications disabled, skipping: {}", notification.summary);
+        Ok(())
+    }
+}
+
+/// Create the notifier selected by configuration.
+pub fn create_notifier(enabled: bool) -> Box<dyn Notifier> {
+    if enabled {
+        Box::new(DesktopNotifier)
+    } else {
+        Box::new(DisabledNotifier)
+    }
+}
+
+/// Show the notification for a connection event, if any.
+///
+/// Failures are logged and otherwise ignored.
+pub fn notify_event(notifier: &dyn Notifier, event: &ConnectionEvent) {
+    if let Some(notification) = Notification::for_event(event) {
+        if let Err(e) = notifier.show(&notification) {
+            warn!("{}", e);
+        }
+    }
+}
+
+/// Ask for a pairing approval with a notification instead of the dialog.
+///
+/// The answer arrives on the returned channel, like the dialog's. A
+/// notification that can't be shown rejects the request. The notification
+/// expires after `timeout`, but callers should not rely on the notification
+/// server for that.
+pub fn ask_pairing(
+    notifier: Arc<dyn Notifier>,
+    device_name: &str,
+    device_id: &str,
+    public_key: &str,
+    timeout: Option<Duration>,
+) -> oneshot::Receiver<ConfirmationResult> {
+    let (tx, rx) = oneshot::channel();
+    let notification = Notification::pairing_request(device_name, device_id, public_key);
+
+    // Waiting for the answer blocks
+    std::thread::spawn(move || {
+        let result = notifier.ask(&notification, timeout).unwrap_or_else(|e| {
+            warn!("{}", e);
+            ConfirmationResult::Rejected
+        });
+        info!(
+            "Pairing notification answered: {}",
+            if matches!(result, ConfirmationResult::Approved) {
+                "approved"
+            } else {
+                "rejected"
+            }
+        );
+        let _ = tx.send(result);
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Notifier that records notifications instead of showing them.
+    #[derive(Default)]
+    struct RecordingNotifier {
+        shown: Mutex<Vec<Notification>>,
+    }
+
+    impl Notifier for RecordingNotifier {
+        fn show(&self, notification: &Notification) -> Result<()> {
+            self.shown.lock().unwrap().push(notification.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_connected_notification() {
+        let notifier = RecordingNotifier::default();
+        notify_event(
+            &notifier,
+            &ConnectionEvent::Connected {
+                device_id: "android-1".to_string(),
+                device_name: "Pixel 8".to_string(),
+            },
+        );
+
+        let shown = notifier.shown.lock().unwrap();
+        assert_eq!(shown.len(), 1);
+        assert_eq!(shown[0].summary, "Phone connected");
+        assert_eq!(shown[0].body, "Connected to Pixel 8");
+    }
+
+    #[test]
+    fn test_disconnected_notification() {
+        let notifier = RecordingNotifier::default();
+        notify_event(&notifier, &ConnectionEvent::Disconnected);
+
+        let shown = notifier.shown.lock().unwrap();
+        assert_eq!(shown.len(), 1);
+        assert_eq!(shown[0].summary, "Phone disconnected");
+    }
+
+    #[test]
+    fn test_pairing_notification_uses_name_or_id() {
+        let notifier = RecordingNotifier::default();
+        notify_event(
+            &notifier,
+            &ConnectionEvent::PairRequested {
+                device_id: "android-123".to_string(),
+                device_name: Some("Pixel 8".to_string()),
+                public_key: String::new(),
+                scanned: false,
+            },
+        );
+        notify_event(
+            &notifier,
+            &ConnectionEvent::PairRequested {
+                device_id: "android-123".to_string(),
+                device_name: None,
+                public_key: String::new(),
+                scanned: false,
+            },
+        );
+
+        let shown = notifier.shown.lock().unwrap();
+        assert_eq!(shown.len(), 2);
+        assert_eq!(shown[0].summary, "Pairing request");
+        assert!(shown[0].body.contains("Pixel 8"));
+        assert!(shown[1].body.contains("android-123"));
+    }
+
+    #[test]
+    fn test_pairing_notification_shows_identity() {
+        let public_key = ecdh::EcdhKeypair::generate().public_key_base64();
+        let notification = Notification::pairing_request("Pixel 8", "android-123", &public_key);
+
+        let fingerprint = ecdh::fingerprint(&public_key).unwrap();
+        assert!(notification.body.contains("Device ID: android-123"));
+        assert!(notification
+            .body
+            .contains(&format!("Key fingerprint: {}", fingerprint)));
+
+        // A broken key doesn't hide the request
+        let notification = Notification::pairing_request("Pixel 8", "android-123", "%%%");
+        assert!(notification.body.contains("Key fingerprint: unknown"));
+    }
+
+    /// Notifier that answers pairing prompts with a fixed result.
+    struct AnsweringNotifier(bool);
+
+    impl Notifier for AnsweringNotifier {
+        fn show(&self, _notification: &Notification) -> Result<()> {
+            Ok(())
+        }
+
+        fn ask(
+            &self,
+            notification: &Notification,
+            timeout: Option<Duration>,
+        ) -> Result<ConfirmationResult> {
+            assert!(notification.body.contains("Pixel 8"));
+            assert_eq!(timeout, Some(Duration::from_secs(60)));
+            Ok(if self.0 {
+                ConfirmationResult::Approved
+            } else {
+                ConfirmationResult::Rejected
+            })
+        }
+    }
+
+    fn ask(notifier: Arc<dyn Notifier>) -> oneshot::Receiver<ConfirmationResult> {
+        ask_pairing(
+            notifier,
+            "Pixel 8",
+            "android-123",
+            "",
+            Some(Duration::from_secs(60)),
+        )
+    }
+
+    #[test]
+    fn test_ask_pairing_returns_answer() {
+        let approved = ask(Arc::new(AnsweringNotifier(true)));
+        assert!(matches!(
+            approved.blocking_recv(),
+            Ok(ConfirmationResult::Approved)
+        ));
+
+        let rejected = ask(Arc::new(AnsweringNotifier(false)));
+        assert!(matches!(
+            rejected.blocking_recv(),
+            Ok(ConfirmationResult::Rejected)
+        ));
+    }
+
+    #[test]
+    fn test_ask_pairing_rejects_without_buttons() {
+        // Backends that can't ask reject instead of leaving the phone waiting
+        let answer = ask(Arc::new(DisabledNotifier));
+        assert!(matches!(
+            answer.blocking_recv(),
+            Ok(ConfirmationResult::Rejected)
+        ));
+    }
+
+    #[test]
+    fn test_text_events_are_not_notified() {
+        let notifier = RecordingNotifier::default();
+        notify_event(
+            &notifier,
+            &ConnectionEvent::TextReceived("hello".to_string()),
+        );
+        notify_event(
+            &notifier,
+            &ConnectionEvent::CommandReceived("ENTER".to_string()),
+        );
+
+        assert!(notifier.shown.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Window showing the pairing QR code.
+
+use anyhow::Result;
+use gtk4::prelude::*;
+use gtk4::{
+    gdk, Application, ApplicationWindow, Box as GtkBox, Button, Label, Orientation, Picture,
+};
+use std::sync::Arc;
+use tokio::sync::oneshot;
+use tracing::info;
+
+use crate::qr::{self, QrPayload};
+
+/// Pixels per QR module.
+const MODULE_SCALE: usize = 8;
+
+/// How long the QR code stays valid, in seconds.
+const QR_TIMEOUT_SECS: u32 = 120;
+
+/// Show the QR code for `payload`.
+///
+/// The returned receiver fires when the window closes, by the user or after
+/// [`QR_TIMEOUT_SECS`]; the QR code should be invalidated then.
+pub fn show_qr_pairing_window(
+    app: &Application,
+    payload: &QrPayload,
+) -> Result<oneshot::Receiver<()>> {
+    let image = qr::render(payload, MODULE_SCALE)?;
+    let size = image.size as i32;
+    let texture = gdk::MemoryTexture::new(
+        size,
+        size,
+        gdk::MemoryFormat::R8g8b8,
+        &glib::Bytes::from_owned(image.pixels),
+        image.size * 3,
+    );
+
+    let (tx, rx) = oneshot::channel();
+    let tx = Arc::new(std::sync::Mutex::new(Some(tx)));
+
+    let window = ApplicationWindow::builder()
+        .application(app)
+        .title("Speech2Prompt - Pair with QR Code")
+        .resizable(false)
+        .build();
+
+    let main_box = GtkBox::new(Orientation::Vertical, 16);
+    main_box.set_margin_top(24);
+    main_box.set_margin_bottom(24);
+    main_box.set_margin_start(24);
+    main_box.set_margin_end(24);
+
+    let title = Label::new(Some("Pair with QR Code"));
+    title.add_css_class("title-2");
+    main_box.append(&title);
+
+    let picture = Picture::for_paintable(&texture);
+    picture.set_can_shrink(false);
+    main_box.append(&picture);
+
+    let hint = Label::new(Some(
+        "Scan this code with the Speech2Prompt app to pair without confirming.",
+    ));
+    hint.set_wrap(true);
+    main_box.append(&hint);
+
+    let note = Label::new(Some("The code works once and expires in 2 minutes."));
+    note.add_css_class("dim-label");
+    main_box.append(&note);
+
+    let close_button = Button::with_label("Close");
+    close_button.set_halign(gtk4::Align::Center);
+    main_box.append(&close_button);
+
+    window.set_child(Some(&main_box));
+
+    let window_close = window.clone();
+    close_button.connect_clicked(move |_| window_close.close());
+
+    let tx_close = tx.clone();
+    window.connect_close_request(move |_| {
+        info!("QR pairing window closed");
+        if let Some(tx) = tx_close.lock().unwrap().take() {
+            let _ = tx.send(());
+        }
+        glib::Propagation::Proceed
+    });
+
+    let window_timeout = window.clone();
+    glib::timeout_add_seconds_local_once(QR_TIMEOUT_SECS, move || {
+        if tx.lock().unwrap().is_some() {
+            info!("⏱️  QR code expired ({}s)", QR_TIMEOUT_SECS);
+            window_timeout.close();
+        }
+    });
+
+    window.present();
+    Ok(rx)
+}
@@ -0,0 +1,221 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Follow the desktop's dark/light theme preference.
+//!
+//! The preference is read from the XDG desktop portal (`color-scheme`) and
+//! applied through `gtk-application-prefer-dark-theme`, so all windows switch
+//! when the user changes the desktop theme.
+
+use gtk4::gio;
+use gtk4::glib;
+use gtk4::prelude::*;
+use std::cell::Cell;
+use std::rc::Rc;
+use tracing::{debug, info, warn};
+
+/// D-Bus name of the desktop portal.
+const PORTAL_BUS_NAME: &str = "org.freedesktop.portal.Desktop";
+/// Object path of the desktop portal.
+const PORTAL_OBJECT_PATH: &str = "/org/freedesktop/portal/desktop";
+/// Portal interface exposing desktop settings.
+const PORTAL_SETTINGS_INTERFACE: &str = "org.freedesktop.portal.Settings";
+/// Settings namespace holding the color scheme.
+const APPEARANCE_NAMESPACE: &str = "org.freedesktop.appearance";
+/// Settings key of the color scheme.
+const COLOR_SCHEME_KEY: &str = "color-scheme";
+
+/// Desktop color scheme preference as reported by the portal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorScheme {
+    /// No preference (or no portal available).
+    NoPreference,
+    /// The user prefers a dark theme.
+    PreferDark,
+    /// The user prefers a light theme.
+    PreferLight,
+}
+
+impl ColorScheme {
+    /// Convert the portal's `color-scheme` value (0, 1 or 2).
+    fn from_portal(value: u32) -> Self {
+        match value {
+            1 => ColorScheme::PreferDark,
+            2 => ColorScheme::PreferLight,
+            _ => ColorScheme::NoPreference,
+        }
+    }
+}
+
+/// Decide whether windows should use the dark variant.
+///
+/// An explicit portal preference wins. Without one, follow the GTK theme
+/// name so a "*-dark" theme keeps dark widgets.
+fn prefers_dark(scheme: ColorScheme, gtk_theme_name: Option<&str>) -> bool {
+    match scheme {
+        ColorScheme::PreferDark => true,
+        ColorScheme::PreferLight => false,
+        ColorScheme::NoPreference => gtk_theme_name
+            .map(|name| name.to_lowercase().ends_with("-dark"))
+            .unwrap_or(false),
+    }
+}
+
+/// Extract the color scheme from a portal value.
+///
+/// Older portals wrap the value in an extra variant, so unwrap until a
+/// plain `u32` is found.
+fn color_scheme_from_variant(value: &glib::Variant) -> ColorScheme {
+    let mut value = value.clone();
+    while let Some(inner) = value.as_variant() {
+        value = inner;
+    }
+    value
+        .get::<u32>()
+        .map(ColorScheme::from_portal)
+        .unwrap_or(ColorScheme::NoPreference)
+}
+
+/// Keeps the portal connection alive so theme changes are still received.
+pub struct ThemeWatcher {
+    _proxy: gio::DBusProxy,
+}
+
+/// Apply the current preference and follow changes at runtime.
+///
+/// Must be called after GTK is initialized. Returns `None` if the portal is
+/// unavailable; the GTK default theme is used then.
+pub fn follow_system_theme() -> Option<ThemeWatcher> {
+    let settings = gtk4::Settings::default()?;
+
+    let proxy = match gio::DBusProxy::for_bus_sync(
+        gio::BusType::Session,
+        gio::DBusProxyFlags::NONE,
+        None,
+        PORTAL_BUS_NAME,
+        PORTAL_OBJECT_PATH,
+        PORTAL_SETTINGS_INTERFACE,
+        gio::Cancellable::NONE,
+    ) {
+        Ok(proxy) => proxy,
+        Err(e) => {
+            warn!(
+                "Desktop portal unavailable, not following system theme: {}",
+                e
+            );
+            return None;
+        }
+    };
+
+    let scheme = match proxy.call_sync(
+        "Read",
+        Some(&(APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY).to_variant()),
+        gio::DBusCallFlags::NONE,
+        1000,
+        gio::Cancellable::NONE,
+    ) {
+        Ok(reply) => color_scheme_from_variant(&reply.child_value(0)),
+        Err(e) => {
+            debug!("Failed to read color scheme: {}", e);
+            ColorScheme::NoPreference
+        }
+    };
+    let scheme = Rc::new(Cell::new(scheme));
+    apply(&settings, scheme.get());
+
+    // Portal preference changed
+    let settings_portal = settings.clone();
+    let scheme_portal = scheme.clone();
+    proxy.connect_local("g-signal", false, move |values| {
+        let signal_name = values.get(2).and_then(|v| v.get::<String>().ok());
+        let parameters = values.get(3).and_then(|v| v.get::<glib::Variant>().ok());
+        if let (Some("SettingChanged"), Some(parameters)) = (signal_name.as_deref(), parameters) {
+            let namespace = parameters.child_value(0).get::<String>();
+            let key = parameters.child_value(1).get::<String>();
+            if namespace.as_deref() == Some(APPEARANCE_NAMESPACE)
+                && key.as_deref() == Some(COLOR_SCHEME_KEY)
+            {
+                scheme_portal.set(color_scheme_from_variant(&parameters.child_value(2)));
+                apply(&settings_portal, scheme_portal.get());
+            }
+        }
+        None
+    });
+
+    // GTK theme changed (matters when the portal has no preference)
+    settings.connect_gtk_theme_name_notify(move |settings| {
+        apply(settings, scheme.get());
+    });
+
+    Some(ThemeWatcher { _proxy: proxy })
+}
+
+/// Set `gtk-application-prefer-dark-theme` for the given preference.
+fn apply(settings: &gtk4::Settings, scheme: ColorScheme) {
+    let theme_name = settings.gtk_theme_name();
+    let dark = prefers_dark(scheme, theme_name.as_deref());
+    if settings.is_gtk_application_prefer_dark_theme() != dark {
+        info!(
+            "Using {} theme ({:?})",
+            if dark { "dark" } else { "light" },
+            scheme
+        );
+        settings.set_gtk_application_prefer_dark_theme(dark);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_scheme_from_portal() {
+        assert_eq!(ColorScheme::from_portal(0), ColorScheme::NoPreference);
+        assert_eq!(ColorScheme::from_portal(1), ColorScheme::PreferDark);
+        assert_eq!(ColorScheme::from_portal(2), ColorScheme::PreferLight);
+        assert_eq!(ColorScheme::from_portal(7), ColorScheme::NoPreference);
+    }
+
+    #[test]
+    fn test_portal_preference_wins() {
+        assert!(prefers_dark(ColorScheme::PreferDark, Some("Adwaita")));
+        assert!(!prefers_dark(
+            ColorScheme::PreferLight,
+            Some("Adwaita-dark")
+        ));
+    }
+
+    #[test]
+    fn test_no_preference_follows_theme_name() {
+        assert!(prefers_dark(
+            ColorScheme::NoPreference,
+            Some("Adwaita-dark")
+        ));
+        assert!(prefers_dark(ColorScheme::NoPreference, Some("Yaru-Dark")));
+        assert!(!prefers_dark(ColorScheme::NoPreference, Some("Adwaita")));
+        assert!(!prefers_dark(ColorScheme::NoPreference, None));
+    }
+
+    #[test]
+    fn test_color_scheme_from_nested_variant() {
+        let plain = 1u32.to_variant();
+        let nested = glib::Variant::from_variant(&2u32.to_variant());
+        assert_eq!(color_scheme_from_variant(&plain), ColorScheme::PreferDark);
+        assert_eq!(color_scheme_from_variant(&nested), ColorScheme::PreferLight);
+        assert_eq!(
+            color_scheme_from_variant(&"dark".to_variant()),
+            ColorScheme::NoPreference
+        );
+    }
+}
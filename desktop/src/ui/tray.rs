@@ -17,16 +17,25 @@
 use anyhow::Result;
 use ksni::{self, menu::StandardItem, Handle, MenuItem, Tray, TrayService};
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::mpsc;
 use tracing::info;
 
 use crate::state::{AppState, ConnectionStatus};
 
+/// Icon shown while connected but with input injection disabled.
+const INPUT_DISABLED_ICON: &str = "media-playback-pause";
+
 /// Actions that can be triggered from the tray menu.
 #[derive(Debug, Clone)]
 pub enum TrayAction {
     ToggleInput,
+    TogglePause,
     ManageCommands,
+    PairWithQr,
+    ToggleDiscoverable,
+    RestartBluetooth,
     Quit,
 }
 
@@ -45,7 +54,7 @@ impl Speech2PromptTray {
 impl Tray for Speech2PromptTray {
     fn icon_name(&self) -> String {
         let status = self.state.get_status();
-        status.icon_name().to_string()
+        tray_icon_name(status, self.state.is_input_enabled()).to_string()
     }
 
     fn title(&self) -> String {
@@ -59,18 +68,46 @@ impl Tray for Speech2PromptTray {
         let description = match status {
             ConnectionStatus::Connected => {
                 let device = self.state.get_device_name().unwrap_or_default();
-                let enabled = if self.state.is_input_enabled() {
+                let enabled = if self.state.is_paused() {
+                    "Paused"
+                } else if self.state.is_input_enabled() {
                     "Input enabled"
                 } else {
                     "Input disabled"
                 };
-                format!("Connected to {}\n{}", device, enabled)
+                let mut description = format!("Connected to {}", device);
+                if let Some(duration) = self.state.get_connected_duration() {
+                    description.push_str(&format!("\nConnected for {}", format_duration(duration)));
+                }
+                if let Some(mtu) = self.state.get_negotiated_mtu() {
+                    description.push_str(&format!("\nMTU: {} bytes", mtu));
+                }
+                if let Some(battery) = self.state.get_battery() {
+                    description.push_str(&format!("\nPhone battery: {}%", battery));
+                }
+                format!("{}\n{}", description, enabled)
             }
-            ConnectionStatus::Disconnected => "Waiting for connection...".to_string(),
+            ConnectionStatus::Disconnected => match self.state.get_reconnect_device() {
+                Some(device) => format!("Waiting for {} to reconnect...", device),
+                None => "Waiting for connection...".to_string(),
+            },
             ConnectionStatus::Connecting => "Connecting...".to_string(),
-            ConnectionStatus::Error => "Connection error".to_string(),
+            ConnectionStatus::Error => match self.state.get_last_error() {
+                Some(error) => format!("Error: {}", error),
+                None => "Connection error".to_string(),
+            },
         };
 
+        let description = format!(
+            "{}\n{}",
+            description,
+            format_statistics(
+                self.state.words_typed(),
+                self.state.commands_executed(),
+                self.state.bytes_injected()
+            )
+        );
+
         ksni::ToolTip {
             icon_name: String::new(),
             icon_pixmap: Vec::new(),
@@ -122,6 +159,21 @@ impl Tray for Speech2PromptTray {
             ..Default::default()
         }));
 
+        // Pause keeps received text and types it on resume
+        let pause_label = if self.state.is_paused() {
+            "▶ Resume"
+        } else {
+            "⏸ Pause"
+        };
+
+        items.push(MenuItem::Standard(StandardItem {
+            label: pause_label.to_string(),
+            activate: Box::new(|tray: &mut Self| {
+                let _ = tray.action_tx.send(TrayAction::TogglePause);
+            }),
+            ..Default::default()
+        }));
+
         items.push(MenuItem::Separator);
 
         // Manage Commands
@@ -133,6 +185,39 @@ impl Tray for Speech2PromptTray {
             ..Default::default()
         }));
 
+        // Pair with QR Code
+        items.push(MenuItem::Standard(StandardItem {
+            label: "Pair with QR Code...".to_string(),
+            activate: Box::new(|tray: &mut Self| {
+                let _ = tray.action_tx.send(TrayAction::PairWithQr);
+            }),
+            ..Default::default()
+        }));
+
+        // Phones that can't find the desktop anymore need it advertising
+        let discoverable_label = if self.state.is_discoverable() {
+            "✓ Discoverable"
+        } else {
+            "○ Hidden"
+        };
+
+        items.push(MenuItem::Standard(StandardItem {
+            label: discoverable_label.to_string(),
+            activate: Box::new(|tray: &mut Self| {
+                let _ = tray.action_tx.send(TrayAction::ToggleDiscoverable);
+            }),
+            ..Default::default()
+        }));
+
+        // Recover from an adapter reset without relaunching
+        items.push(MenuItem::Standard(StandardItem {
+            label: "Restart Bluetooth".to_string(),
+            activate: Box::new(|tray: &mut Self| {
+                let _ = tray.action_tx.send(TrayAction::RestartBluetooth);
+            }),
+            ..Default::default()
+        }));
+
         items.push(MenuItem::Separator);
 
         // Quit
@@ -156,15 +241,48 @@ impl Tray for Speech2PromptTray {
     }
 }
 
+/// Get the tray icon for a connection status and input state.
+///
+/// While connected, a disabled input is shown with a distinct icon so it
+/// can't be mistaken for an active session.
+fn tray_icon_name(status: ConnectionStatus, input_enabled: bool) -> &'static str {
+    match status {
+        ConnectionStatus::Connected if !input_enabled => INPUT_DISABLED_ICON,
+        _ => status.icon_name(),
+    }
+}
+
+/// Format a duration for display, e.g. "45s", "3m 12s" or "1h 05m".
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+
+    if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+/// Format dictation statistics for the tooltip.
+fn format_statistics(words: u64, commands: u64, bytes: u64) -> String {
+    format!(
+        "{} words, {} commands, {} bytes typed",
+        words, commands, bytes
+    )
+}
+
 /// Run the system tray service.
+///
+/// Menu actions are sent on `action_tx`, which other sources (like the
+/// global hotkey) may share.
 pub fn run_tray(
     state: Arc<AppState>,
-) -> Result<(
-    mpsc::UnboundedReceiver<TrayAction>,
-    Handle<Speech2PromptTray>,
-)> {
-    let (action_tx, action_rx) = mpsc::unbounded_channel();
-
+    action_tx: mpsc::UnboundedSender<TrayAction>,
+) -> Result<Handle<Speech2PromptTray>> {
+    let mut changes = state.subscribe();
     let tray = Speech2PromptTray::new(state, action_tx);
     let service = TrayService::new(tray);
     let handle = service.handle();
@@ -174,7 +292,158 @@ pub fn run_tray(
         let _ = service.run();
     });
 
+    // Redraw as soon as the state changes
+    let refresh = handle.clone();
+    std::thread::spawn(move || {
+        // Lagging behind still calls for a redraw
+        while !matches!(changes.blocking_recv(), Err(RecvError::Closed)) {
+            refresh.update(|_| {});
+        }
+    });
+
     info!("System tray started");
 
-    Ok((action_rx, handle))
+    Ok(handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tray_icon_name_input_enabled() {
+        assert_eq!(
+            tray_icon_name(ConnectionStatus::Connected, true),
+            "network-transmit-receive"
+        );
+        assert_eq!(
+            tray_icon_name(ConnectionStatus::Disconnected, true),
+            "network-offline"
+        );
+        assert_eq!(
+            tray_icon_name(ConnectionStatus::Connecting, true),
+            "network-idle"
+        );
+        assert_eq!(
+            tray_icon_name(ConnectionStatus::Error, true),
+            "network-error"
+        );
+    }
+
+    #[test]
+    fn test_tray_icon_name_input_disabled() {
+        // Only the connected state gets the distinct icon
+        assert_eq!(
+            tray_icon_name(ConnectionStatus::Connected, false),
+            INPUT_DISABLED_ICON
+        );
+        assert_eq!(
+            tray_icon_name(ConnectionStatus::Disconnected, false),
+            "network-offline"
+        );
+        assert_eq!(
+            tray_icon_name(ConnectionStatus::Connecting, false),
+            "network-idle"
+        );
+        assert_eq!(
+            tray_icon_name(ConnectionStatus::Error, false),
+            "network-error"
+        );
+    }
+
+    #[test]
+    fn test_format_duration_seconds() {
+        assert_eq!(format_duration(Duration::from_secs(0)), "0s");
+        assert_eq!(format_duration(Duration::from_secs(45)), "45s");
+        assert_eq!(format_duration(Duration::from_millis(59_999)), "59s");
+    }
+
+    #[test]
+    fn test_format_duration_minutes() {
+        assert_eq!(format_duration(Duration::from_secs(60)), "1m 0s");
+        assert_eq!(format_duration(Duration::from_secs(192)), "3m 12s");
+        assert_eq!(format_duration(Duration::from_secs(3599)), "59m 59s");
+    }
+
+    #[test]
+    fn test_format_duration_hours() {
+        assert_eq!(format_duration(Duration::from_secs(3600)), "1h 00m");
+        assert_eq!(format_duration(Duration::from_secs(3900)), "1h 05m");
+        assert_eq!(format_duration(Duration::from_secs(26 * 3600)), "26h 00m");
+    }
+
+    /// Activate the menu item labelled `label`.
+    fn activate(tray: &mut Speech2PromptTray, label: &str) {
+        let menu = tray.menu();
+        let item = menu
+            .iter()
+            .find_map(|item| match item {
+                MenuItem::Standard(item) if item.label == label => Some(item),
+                _ => None,
+            })
+            .unwrap_or_else(|| panic!("no menu item {:?}", label));
+        (item.activate)(tray);
+    }
+
+    #[test]
+    fn test_toggle_discoverable() {
+        let state = AppState::new();
+        let (action_tx, mut actions) = mpsc::unbounded_channel();
+        let mut tray = Speech2PromptTray::new(state.clone(), action_tx);
+
+        activate(&mut tray, "✓ Discoverable");
+        assert!(matches!(
+            actions.try_recv(),
+            Ok(TrayAction::ToggleDiscoverable)
+        ));
+
+        // The main loop stops advertising and records it
+        state.set_discoverable(false);
+        activate(&mut tray, "○ Hidden");
+        assert!(matches!(
+            actions.try_recv(),
+            Ok(TrayAction::ToggleDiscoverable)
+        ));
+    }
+
+    #[test]
+    fn test_tool_tip_shows_last_error() {
+        let state = AppState::new();
+        let (action_tx, _actions) = mpsc::unbounded_channel();
+        let tray = Speech2PromptTray::new(state.clone(), action_tx);
+
+        state.set_error("ydotool not running");
+        let description = tray.tool_tip().description;
+        assert!(
+            description.starts_with("Error: ydotool not running\n"),
+            "{}",
+            description
+        );
+    }
+
+    #[test]
+    fn test_tool_tip_shows_battery() {
+        let state = AppState::new();
+        let (action_tx, _actions) = mpsc::unbounded_channel();
+        let tray = Speech2PromptTray::new(state.clone(), action_tx);
+
+        state.set_connected("Pixel 8".to_string());
+        assert!(!tray.tool_tip().description.contains("battery"));
+
+        state.set_battery(Some(42));
+        let description = tray.tool_tip().description;
+        assert!(
+            description.contains("\nPhone battery: 42%\n"),
+            "{}",
+            description
+        );
+    }
+
+    #[test]
+    fn test_format_statistics() {
+        assert_eq!(
+            format_statistics(12, 3, 64),
+            "12 words, 3 commands, 64 bytes typed"
+        );
+    }
 }
@@ -0,0 +1,375 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Outbound webhook for processed text and commands.
+//!
+//! Each event is POSTed as JSON to the configured URL:
+//!
+//! ```json
+//! {"type": "text", "content": "hello ", "timestamp": "2026-01-01T12:00:00Z", "active_window": "Terminal"}
+//! ```
+//!
+//! Requests are fire-and-forget: they run on their own task with a timeout
+//! and failures are only logged, so a slow or unreachable endpoint never
+//! holds up injection.
+//!
+//! Events carry everything the user dictates, and requests are sent without
+//! TLS. The URL must therefore point to this machine (`localhost`, a
+//! `127.x.x.x` address or `[::1]`); put a local relay in front of remote or
+//! HTTPS endpoints.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::net::IpAddr;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tracing::{debug, warn};
+
+use crate::commands::VoiceCommand;
+use crate::input::active_window_title;
+
+/// A processed event, as sent to the webhook.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookEvent {
+    /// "text" or "command".
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// Typed text, or the command code or key combination.
+    pub content: String,
+    /// When the event was processed.
+    pub timestamp: DateTime<Utc>,
+    /// Title of the window the input went to, if known.
+    pub active_window: Option<String>,
+}
+
+impl WebhookEvent {
+    /// Event for typed text.
+    pub fn text(text: &str) -> Self {
+        Self::new("text", text.to_string())
+    }
+
+    /// Event for an executed command.
+    pub fn command(command: &VoiceCommand) -> Self {
+        let content = match command {
+            VoiceCommand::KeyCombo(combo) => combo.to_string(),
+            VoiceCommand::Script(code) | VoiceCommand::Shell(code) => code.clone(),
+            command => command.as_str().to_string(),
+        };
+        Self::new("command", content)
+    }
+
+    fn new(kind: &'static str, content: String) -> Self {
+        Self {
+            kind,
+            content,
+            timestamp: Utc::now(),
+            active_window: None,
+        }
+    }
+}
+
+/// Parts of an `http://` URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WebhookUrl {
+    /// Host name or IP address, without the brackets of IPv6 literals.
+    host: String,
+    port: u16,
+    path: String,
+}
+
+impl WebhookUrl {
+    fn parse(url: &str) -> Result<Self> {
+        let rest = url
+            .trim()
+            .strip_prefix("http://")
+            .with_context(|| format!("Webhook URL '{}' must start with http://", url.trim()))?;
+
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        // IPv6 literals are bracketed, as in "[::1]:8080"
+        let (host, port) = match authority.strip_prefix('[') {
+            Some(bracketed) => {
+                let (host, rest) = bracketed
+                    .split_once(']')
+                    .with_context(|| format!("Unclosed '[' in webhook URL '{}'", url.trim()))?;
+                let port = match rest {
+                    "" => None,
+                    rest => Some(rest.strip_prefix(':').with_context(|| {
+                        format!("Invalid port in webhook URL '{}'", url.trim())
+                    })?),
+                };
+                (host, port)
+            }
+            None => match authority.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            },
+        };
+        let port = match port {
+            Some(port) => port
+                .parse()
+                .with_context(|| format!("Invalid port in webhook URL '{}'", url.trim()))?,
+            None => 80,
+        };
+        if host.is_empty() {
+            bail!("Webhook URL '{}' has no host", url.trim());
+        }
+        if !is_loopback(host) {
+            bail!(
+                "Webhook URL '{}' must point to this machine (localhost, 127.0.0.1 or [::1])",
+                url.trim()
+            );
+        }
+
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+
+    /// Value of the `Host` header.
+    fn host_header(&self) -> String {
+        if self.host.contains(':') {
+            format!("[{}]:{}", self.host, self.port)
+        } else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+}
+
+/// Whether `host` names this machine.
+///
+/// Requests are not encrypted, so they must not leave it.
+fn is_loopback(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Webhook client.
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    url: WebhookUrl,
+    timeout: Duration,
+}
+
+impl Webhook {
+    /// Create a client for `url`, giving up on requests after `timeout`.
+    pub fn new(url: &str, timeout: Duration) -> Result<Self> {
+        Ok(Self {
+            url: WebhookUrl::parse(url)?,
+            timeout,
+        })
+    }
+
+    /// Send an event in the background.
+    ///
+    /// Must be called within a Tokio runtime. Returns immediately.
+    pub fn send(&self, mut event: WebhookEvent) {
+        let url = self.url.clone();
+        let timeout = self.timeout;
+
+        tokio::spawn(async move {
+            // Looking up the window talks to the display server, keep it off
+            // the async threads
+            event.active_window = tokio::task::spawn_blocking(active_window_title)
+                .await
+                .unwrap_or(None);
+
+            let body = match serde_json::to_vec(&event) {
+                Ok(body) => body,
+                Err(e) => {
+                    warn!("Failed to encode webhook event: {}", e);
+                    return;
+                }
+            };
+
+            match tokio::time::timeout(timeout, post(&url, &body)).await {
+                Ok(Ok(())) => debug!("Webhook delivered {} event", event.kind),
+                Ok(Err(e)) => warn!("Webhook failed: {:#}", e),
+                Err(_) => warn!("Webhook timed out after {:?}", timeout),
+            }
+        });
+    }
+}
+
+/// POST a JSON body and check for a 2xx status.
+async fn post(url: &WebhookUrl, body: &[u8]) -> Result<()> {
+    let mut stream = TcpStream::connect((url.host.as_str(), url.port))
+        .await
+        .with_context(|| format!("Failed to connect to {}:{}", url.host, url.port))?;
+
+    let head = format!(
+        "POST {} HTTP/1.1\r\n\
+         Host: {}\r\n\
+         User-Agent: speech2prompt-desktop/{}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n",
+        url.path,
+        url.host_header(),
+        env!("CARGO_PKG_VERSION"),
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body).await?;
+
+    let mut status_line = String::new();
+    BufReader::new(stream).read_line(&mut status_line).await?;
+    let status = status_line.split_whitespace().nth(1).unwrap_or("");
+    if !status.starts_with('2') {
+        bail!("Webhook answered '{}'", status_line.trim());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_parse_url() {
+        assert_eq!(
+            WebhookUrl::parse("http://localhost:8080/hooks/s2p").unwrap(),
+            WebhookUrl {
+                host: "localhost".to_string(),
+                port: 8080,
+                path: "/hooks/s2p".to_string(),
+            }
+        );
+        assert_eq!(
+            WebhookUrl::parse("http://127.0.0.1").unwrap(),
+            WebhookUrl {
+                host: "127.0.0.1".to_string(),
+                port: 80,
+                path: "/".to_string(),
+            }
+        );
+        assert!(WebhookUrl::parse("https://localhost").is_err());
+        assert!(WebhookUrl::parse("http://:80/").is_err());
+        assert!(WebhookUrl::parse("http://localhost:port/").is_err());
+    }
+
+    #[test]
+    fn test_parse_ipv6_url() {
+        let url = WebhookUrl::parse("http://[::1]:8080/hook").unwrap();
+        assert_eq!(
+            url,
+            WebhookUrl {
+                host: "::1".to_string(),
+                port: 8080,
+                path: "/hook".to_string(),
+            }
+        );
+        assert_eq!(url.host_header(), "[::1]:8080");
+        assert_eq!(WebhookUrl::parse("http://[::1]").unwrap().port, 80);
+        assert!(WebhookUrl::parse("http://[::1/").is_err());
+        assert!(WebhookUrl::parse("http://[::1]8080/").is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_remote_hosts() {
+        assert!(WebhookUrl::parse("http://example.com/hook").is_err());
+        assert!(WebhookUrl::parse("http://192.168.1.10:8080/").is_err());
+        assert!(WebhookUrl::parse("http://[2001:db8::1]/").is_err());
+        assert!(WebhookUrl::parse("http://LocalHost:8080/").is_ok());
+        assert!(WebhookUrl::parse("http://127.1.2.3/").is_ok());
+    }
+
+    #[test]
+    fn test_command_content() {
+        assert_eq!(WebhookEvent::command(&VoiceCommand::Enter).content, "ENTER");
+        let combo = crate::input::KeyCombo::parse("ctrl+s").unwrap();
+        assert_eq!(
+            WebhookEvent::command(&VoiceCommand::KeyCombo(combo)).content,
+            "ctrl+s"
+        );
+    }
+
+    /// Accept one request, answer 200 and return the request.
+    async fn mock_server() -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        let handle = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            loop {
+                let n = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let length: usize = head
+                        .lines()
+                        .find_map(|l| l.strip_prefix("Content-Length: "))
+                        .unwrap()
+                        .parse()
+                        .unwrap();
+                    if body.len() >= length {
+                        break;
+                    }
+                }
+            }
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn test_posts_event_json() {
+        let (url, server) = mock_server().await;
+        let webhook = Webhook::new(&url, Duration::from_secs(5)).unwrap();
+
+        webhook.send(WebhookEvent::text("hello "));
+        let request = server.await.unwrap();
+
+        let (head, body) = request.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("POST /hook HTTP/1.1\r\n"));
+        assert!(head.contains("Content-Type: application/json"));
+
+        let json: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(json["type"], "text");
+        assert_eq!(json["content"], "hello ");
+        assert!(json["timestamp"]
+            .as_str()
+            .unwrap()
+            .parse::<DateTime<Utc>>()
+            .is_ok());
+        assert!(json.as_object().unwrap().contains_key("active_window"));
+    }
+
+    #[tokio::test]
+    async fn test_send_does_not_wait_for_server() {
+        // Accepts connections but never answers
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let webhook = Webhook::new(&url, Duration::from_millis(100)).unwrap();
+
+        let start = std::time::Instant::now();
+        webhook.send(WebhookEvent::text("hello"));
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+}
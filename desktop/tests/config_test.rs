@@ -0,0 +1,26 @@
+//! Integration tests for where the config is loaded from.
+//!
+//! Setting the environment affects the whole process, so this runs apart
+//! from the unit tests.
+
+use speech2prompt_desktop::config::{Config, CONFIG_DIR_ENV, DATA_DIR_ENV};
+
+#[test]
+fn test_env_vars_redirect_load() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let data = temp_dir.path().join("data");
+    let config = temp_dir.path().join("config");
+    std::env::set_var(DATA_DIR_ENV, &data);
+    std::env::set_var(CONFIG_DIR_ENV, &config);
+
+    let path = Config::path();
+    let loaded = Config::load();
+    std::env::remove_var(DATA_DIR_ENV);
+    std::env::remove_var(CONFIG_DIR_ENV);
+
+    assert_eq!(path, config.join("config.toml"));
+    let loaded = loaded.unwrap();
+    assert_eq!(loaded.data_dir, data);
+    assert!(data.is_dir());
+    assert!(path.is_file());
+}
@@ -0,0 +1,99 @@
+//! Integration tests for the control socket.
+
+use anyhow::Result;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+use speech2prompt_desktop::control::ControlServer;
+use speech2prompt_desktop::events::EventProcessor;
+use speech2prompt_desktop::input::{InputInjector, Key, Modifier};
+
+/// Injector that records what it was asked to do.
+struct RecordingInjector(Arc<Mutex<Vec<String>>>);
+
+impl InputInjector for RecordingInjector {
+    fn backend_name(&self) -> &'static str {
+        "Recording"
+    }
+
+    fn type_text(&self, text: &str) -> Result<()> {
+        self.0.lock().push(text.to_string());
+        Ok(())
+    }
+
+    fn press_key(&self, key: Key) -> Result<()> {
+        self.0.lock().push(format!("<{:?}>", key));
+        Ok(())
+    }
+
+    fn key_combo(&self, modifiers: &[Modifier], key: Key) -> Result<()> {
+        self.0.lock().push(format!("<{:?}+{:?}>", modifiers, key));
+        Ok(())
+    }
+}
+
+/// Send request lines and collect one reply per line.
+async fn send(stream: &mut BufReader<UnixStream>, requests: &[&str]) -> Vec<String> {
+    let mut replies = Vec::new();
+    for request in requests {
+        stream
+            .get_mut()
+            .write_all(format!("{}\n", request).as_bytes())
+            .await
+            .unwrap();
+        let mut reply = String::new();
+        stream.read_line(&mut reply).await.unwrap();
+        replies.push(reply.trim_end().to_string());
+    }
+    replies
+}
+
+#[tokio::test]
+async fn test_socket_requests_reach_the_injector() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("control.sock");
+    let injected = Arc::new(Mutex::new(Vec::new()));
+
+    let (event_tx, mut event_rx) = tokio::sync::mpsc::channel(8);
+    let _server = ControlServer::bind(&path, event_tx).unwrap();
+
+    let mut stream = BufReader::new(UnixStream::connect(&path).await.unwrap());
+    let replies = send(
+        &mut stream,
+        &["InjectText hello world", "ExecuteCommand ENTER", "Bogus"],
+    )
+    .await;
+    assert_eq!(replies[..2], ["OK", "OK"]);
+    assert!(replies[2].starts_with("ERR "));
+
+    let mut processor = EventProcessor::new(Box::new(RecordingInjector(injected.clone())));
+    for _ in 0..2 {
+        let event = event_rx.recv().await.unwrap();
+        processor.process_event(event).await.unwrap();
+    }
+    processor.wait_for_injection().await;
+
+    assert_eq!(
+        *injected.lock(),
+        vec!["hello world".to_string(), "<Enter>".to_string()]
+    );
+}
+
+#[tokio::test]
+async fn test_socket_file_is_private_and_removed_on_drop() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("control.sock");
+    let (event_tx, _event_rx) = tokio::sync::mpsc::channel(8);
+
+    let server = ControlServer::bind(&path, event_tx).unwrap();
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+
+    drop(server);
+    assert!(!path.exists());
+}
@@ -0,0 +1,178 @@
+//! Integration tests driving the event processor end to end.
+
+use anyhow::Result;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+use speech2prompt_desktop::bluetooth::ConnectionEvent;
+use speech2prompt_desktop::events::EventProcessor;
+use speech2prompt_desktop::input::{InputInjector, Key, Modifier};
+use speech2prompt_desktop::state::{AppState, StateChange};
+use speech2prompt_desktop::storage::VoiceCommandStore;
+
+/// Injector that records what it was asked to do.
+struct RecordingInjector(Arc<Mutex<Vec<String>>>);
+
+impl InputInjector for RecordingInjector {
+    fn backend_name(&self) -> &'static str {
+        "Recording"
+    }
+
+    fn type_text(&self, text: &str) -> Result<()> {
+        self.0.lock().push(text.to_string());
+        Ok(())
+    }
+
+    fn press_key(&self, key: Key) -> Result<()> {
+        self.0.lock().push(format!("<{:?}>", key));
+        Ok(())
+    }
+
+    fn key_combo(&self, modifiers: &[Modifier], key: Key) -> Result<()> {
+        self.0.lock().push(format!("<{:?}+{:?}>", modifiers, key));
+        Ok(())
+    }
+
+    fn scroll(&self, delta: i32) -> Result<()> {
+        self.0.lock().push(format!("<scroll {}>", delta));
+        Ok(())
+    }
+}
+
+type Injected = Arc<Mutex<Vec<String>>>;
+
+fn processor(
+    store: Option<Arc<VoiceCommandStore>>,
+    state: Option<Arc<AppState>>,
+) -> (EventProcessor, Injected) {
+    let injected = Arc::new(Mutex::new(Vec::new()));
+    let processor =
+        EventProcessor::from_parts(Box::new(RecordingInjector(injected.clone())), store, state);
+    (processor, injected)
+}
+
+fn word(word: &str) -> ConnectionEvent {
+    ConnectionEvent::WordReceived {
+        word: word.to_string(),
+        seq: None,
+        session: "session1".to_string(),
+        eou: false,
+    }
+}
+
+#[tokio::test]
+async fn test_text_with_command_is_split_into_segments() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = Arc::new(VoiceCommandStore::new(temp_dir.path()).unwrap());
+    let (mut processor, injected) = processor(Some(store), None);
+
+    processor
+        .process_event(ConnectionEvent::TextReceived(
+            "hello enter world".to_string(),
+        ))
+        .await
+        .unwrap();
+    processor.wait_for_injection().await;
+
+    assert_eq!(
+        *injected.lock(),
+        vec![
+            "hello ".to_string(),
+            "<Enter>".to_string(),
+            " world".to_string()
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_words_and_commands_update_state() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = Arc::new(VoiceCommandStore::new(temp_dir.path()).unwrap());
+    let state = AppState::new();
+    let (mut processor, injected) = processor(Some(store), Some(state.clone()));
+
+    for w in ["hello", "select", "all", "copy"] {
+        processor.process_event(word(w)).await.unwrap();
+    }
+    processor.wait_for_injection().await;
+
+    assert_eq!(
+        *injected.lock(),
+        vec![
+            "hello ".to_string(),
+            "<[Ctrl]+A>".to_string(),
+            "<[Ctrl]+C>".to_string()
+        ]
+    );
+    assert_eq!(state.commands_executed(), 2);
+}
+
+#[tokio::test]
+async fn test_scroll_words_wait_for_direction() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = Arc::new(VoiceCommandStore::new(temp_dir.path()).unwrap());
+    let (mut processor, injected) = processor(Some(store), None);
+
+    // "scroll" is held back until the direction arrives
+    processor.process_event(word("scroll")).await.unwrap();
+    processor.wait_for_injection().await;
+    assert!(injected.lock().is_empty());
+
+    for w in ["down", "scroll", "up"] {
+        processor.process_event(word(w)).await.unwrap();
+    }
+    processor.wait_for_injection().await;
+
+    assert_eq!(
+        *injected.lock(),
+        vec!["<scroll 3>".to_string(), "<scroll -3>".to_string()]
+    );
+}
+
+#[tokio::test]
+async fn test_without_store_phrases_are_typed() {
+    let (mut processor, injected) = processor(None, None);
+
+    processor
+        .process_event(ConnectionEvent::TextReceived("enter".to_string()))
+        .await
+        .unwrap();
+    // Protocol commands still work
+    processor
+        .process_event(ConnectionEvent::CommandReceived("ENTER".to_string()))
+        .await
+        .unwrap();
+    processor.wait_for_injection().await;
+
+    assert_eq!(
+        *injected.lock(),
+        vec!["enter".to_string(), "<Enter>".to_string()]
+    );
+}
+
+#[tokio::test]
+async fn test_busy_while_injecting() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = Arc::new(VoiceCommandStore::new(temp_dir.path()).unwrap());
+    let state = AppState::new();
+    let mut changes = state.subscribe();
+    let (mut processor, _injected) = processor(Some(store), Some(state.clone()));
+
+    processor
+        .process_event(ConnectionEvent::TextReceived(
+            "hello enter world".to_string(),
+        ))
+        .await
+        .unwrap();
+    processor.wait_for_injection().await;
+
+    // Busy while typing (maybe more than once, the injector may catch up
+    // between segments), then idle again
+    let busy_changes = std::iter::from_fn(|| changes.try_recv().ok())
+        .filter(|change| *change == StateChange::Busy)
+        .count();
+    assert!(busy_changes >= 2);
+    assert_eq!(busy_changes % 2, 0);
+    assert!(!state.is_busy());
+}
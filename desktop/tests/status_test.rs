@@ -0,0 +1,44 @@
+//! Integration tests for the status socket.
+
+use tempfile::TempDir;
+use tokio::io::AsyncReadExt;
+use tokio::net::UnixStream;
+
+use speech2prompt_desktop::state::AppState;
+use speech2prompt_desktop::status::{Status, StatusServer};
+
+/// Connect and read everything the server sends.
+async fn query(path: &std::path::Path) -> String {
+    let mut stream = UnixStream::connect(path).await.unwrap();
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply).await.unwrap();
+    reply
+}
+
+#[tokio::test]
+async fn test_status_socket_reports_state() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("status.sock");
+    let state = AppState::new();
+    let server = StatusServer::bind(&path, state.clone()).unwrap();
+
+    let reply = query(&path).await;
+    assert!(reply.ends_with('\n'));
+    let status: Status = serde_json::from_str(&reply).unwrap();
+    assert_eq!(status.connected_device, None);
+    assert_eq!(status.mtu, None);
+
+    // Each connection gets a fresh snapshot
+    state.set_connected("Pixel 8".to_string());
+    state.set_negotiated_mtu(185);
+    state.set_last_text("hello world".to_string());
+    let json: serde_json::Value = serde_json::from_str(&query(&path).await).unwrap();
+    assert_eq!(json["connected_device"], "Pixel 8");
+    assert_eq!(json["input_enabled"], true);
+    assert_eq!(json["last_text_len"], 11);
+    assert_eq!(json["mtu"], 185);
+    assert!(json["uptime_secs"].is_u64());
+
+    drop(server);
+    assert!(!path.exists());
+}
@@ -0,0 +1,207 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tracking of sent messages awaiting an ACK.
+//!
+//! An ACK echoes the timestamp of the message it acknowledges, so pending
+//! messages are keyed by their timestamp.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::message::{Message, MessageType};
+
+/// What to do about a message whose ACK didn't arrive in time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AckTimeout<T> {
+    /// Send it again. It stays pending with a fresh deadline.
+    Resend(T),
+    /// Out of resends. It is no longer tracked.
+    GaveUp { timestamp: u64, item: T },
+}
+
+struct Pending<T> {
+    item: T,
+    deadline: Instant,
+    resends_left: u32,
+}
+
+/// Sent messages awaiting an ACK, with a deadline each.
+///
+/// Time is passed in by the caller so expiry doesn't depend on a timer.
+pub struct PendingAcks<T> {
+    timeout: Duration,
+    pending: HashMap<u64, Pending<T>>,
+}
+
+impl<T: Clone> PendingAcks<T> {
+    /// Create a table where each message gets `timeout` to be acknowledged.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Start waiting for the ACK of the message sent with `timestamp`.
+    ///
+    /// `resends` is how often it may be sent again before giving up.
+    pub fn track(&mut self, timestamp: u64, item: T, resends: u32, now: Instant) {
+        self.pending.insert(
+            timestamp,
+            Pending {
+                item,
+                deadline: now + self.timeout,
+                resends_left: resends,
+            },
+        );
+    }
+
+    /// Match an incoming ACK to its pending message.
+    ///
+    /// Returns the acknowledged item, or `None` for non-ACK messages and
+    /// ACKs of messages that aren't pending (late or duplicate ones).
+    pub fn acknowledge(&mut self, ack: &Message) -> Option<T> {
+        if ack.message_type != MessageType::Ack {
+            return None;
+        }
+        let timestamp = ack.payload.trim().parse().ok()?;
+        self.pending.remove(&timestamp).map(|pending| pending.item)
+    }
+
+    /// Collect messages whose deadline has passed by `now`.
+    pub fn expire(&mut self, now: Instant) -> Vec<AckTimeout<T>> {
+        let mut expired = Vec::new();
+        let mut gave_up = Vec::new();
+
+        for (&timestamp, pending) in self.pending.iter_mut() {
+            if pending.deadline > now {
+                continue;
+            }
+            if pending.resends_left > 0 {
+                pending.resends_left -= 1;
+                pending.deadline = now + self.timeout;
+                expired.push(AckTimeout::Resend(pending.item.clone()));
+            } else {
+                gave_up.push(timestamp);
+            }
+        }
+
+        for timestamp in gave_up {
+            if let Some(pending) = self.pending.remove(&timestamp) {
+                expired.push(AckTimeout::GaveUp {
+                    timestamp,
+                    item: pending.item,
+                });
+            }
+        }
+        expired
+    }
+
+    /// Number of messages awaiting an ACK.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Whether no message is awaiting an ACK.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Stop tracking everything, e.g. when the session ends.
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    #[test]
+    fn test_ack_correlates_by_timestamp() {
+        let now = Instant::now();
+        let mut acks = PendingAcks::new(TIMEOUT);
+        acks.track(1000, "first", 0, now);
+        acks.track(2000, "second", 0, now);
+
+        assert_eq!(acks.acknowledge(&Message::ack(2000)), Some("second"));
+        assert_eq!(acks.len(), 1);
+
+        // Late or duplicate ACK
+        assert_eq!(acks.acknowledge(&Message::ack(2000)), None);
+        // Unknown timestamp
+        assert_eq!(acks.acknowledge(&Message::ack(3000)), None);
+        // Not an ACK
+        let text = Message::new(MessageType::Text, "1000");
+        assert_eq!(acks.acknowledge(&text), None);
+
+        assert_eq!(acks.acknowledge(&Message::ack(1000)), Some("first"));
+        assert!(acks.is_empty());
+    }
+
+    #[test]
+    fn test_timeout_expiry() {
+        let now = Instant::now();
+        let mut acks = PendingAcks::new(TIMEOUT);
+        acks.track(1000, "pair ack", 0, now);
+
+        assert!(acks.expire(now + TIMEOUT - Duration::from_millis(1)).is_empty());
+        assert_eq!(
+            acks.expire(now + TIMEOUT),
+            vec![AckTimeout::GaveUp {
+                timestamp: 1000,
+                item: "pair ack"
+            }]
+        );
+        assert!(acks.is_empty());
+
+        // Expired entries are gone, a late ACK matches nothing
+        assert_eq!(acks.acknowledge(&Message::ack(1000)), None);
+    }
+
+    #[test]
+    fn test_resend_before_giving_up() {
+        let now = Instant::now();
+        let mut acks = PendingAcks::new(TIMEOUT);
+        acks.track(1000, "response", 1, now);
+
+        let first = now + TIMEOUT;
+        assert_eq!(acks.expire(first), vec![AckTimeout::Resend("response")]);
+        assert_eq!(acks.len(), 1);
+
+        // The resend gets a fresh deadline
+        assert!(acks.expire(first + TIMEOUT / 2).is_empty());
+        assert_eq!(
+            acks.expire(first + TIMEOUT),
+            vec![AckTimeout::GaveUp {
+                timestamp: 1000,
+                item: "response"
+            }]
+        );
+    }
+
+    #[test]
+    fn test_ack_after_resend() {
+        let now = Instant::now();
+        let mut acks = PendingAcks::new(TIMEOUT);
+        acks.track(1000, "response", 3, now);
+        acks.expire(now + TIMEOUT);
+
+        assert_eq!(acks.acknowledge(&Message::ack(1000)), Some("response"));
+        assert!(acks.expire(now + TIMEOUT * 10).is_empty());
+    }
+}
@@ -1,11 +1,23 @@
 // Copyright 2026 Daniel Pelikan
-// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
 
 //! ECDH key exchange using X25519.
 
 use anyhow::{anyhow, Result};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
 use x25519_dalek::{EphemeralSecret, PublicKey};
 
 /// X25519 public key size in bytes.
@@ -14,6 +26,9 @@ pub const PUBLIC_KEY_SIZE: usize = 32;
 /// X25519 shared secret size in bytes.
 pub const SHARED_SECRET_SIZE: usize = 32;
 
+/// Bytes of the key hash shown in a fingerprint.
+const FINGERPRINT_SIZE: usize = 8;
+
 /// ECDH keypair for key exchange.
 pub struct EcdhKeypair {
     secret: EphemeralSecret,
@@ -74,6 +89,22 @@ impl EcdhKeypair {
     }
 }
 
+/// Short fingerprint of a base64-encoded public key, for users to compare.
+///
+/// The first 8 bytes of the key's SHA-256 hash as hex in groups of four,
+/// e.g. "6668 7AAD F862 BD77".
+pub fn fingerprint(public_key_base64: &str) -> Result<String> {
+    let key = BASE64
+        .decode(public_key_base64)
+        .map_err(|e| anyhow!("Invalid base64 public key: {}", e))?;
+    let hash = Sha256::digest(&key);
+    let groups: Vec<String> = hash[..FINGERPRINT_SIZE]
+        .chunks(2)
+        .map(|pair| format!("{:02X}{:02X}", pair[0], pair[1]))
+        .collect();
+    Ok(groups.join(" "))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -106,4 +137,18 @@ mod tests {
         let decoded = BASE64.decode(&base64_key).unwrap();
         assert_eq!(decoded.len(), PUBLIC_KEY_SIZE);
     }
+
+    #[test]
+    fn test_fingerprint() {
+        let zero_key = BASE64.encode([0u8; PUBLIC_KEY_SIZE]);
+        assert_eq!(fingerprint(&zero_key).unwrap(), "6668 7AAD F862 BD77");
+
+        // Different keys, different fingerprints
+        let keypair = EcdhKeypair::generate();
+        let other = fingerprint(&keypair.public_key_base64()).unwrap();
+        assert_eq!(other.len(), 19);
+        assert_ne!(other, "6668 7AAD F862 BD77");
+
+        assert!(fingerprint("not base64!").is_err());
+    }
 }
@@ -30,6 +30,7 @@ const PBKDF2_ITERATIONS: u32 = 100_000;
 const SALT: &[u8] = b"speech2prompt_v1";
 const NONCE_SIZE: usize = 12;
 const KEY_SIZE: usize = 32;
+const PASSPHRASE_SALT_SIZE: usize = 16;
 
 /// Cryptographic context for a paired session.
 #[derive(Clone)]
@@ -113,6 +114,58 @@ pub fn derive_key_from_ecdh(
 /// Encrypt plaintext using AES-256-GCM.
 /// Returns base64(nonce || ciphertext || tag).
 pub fn encrypt(plaintext: &str, key: &[u8; KEY_SIZE]) -> Result<String> {
+    Ok(BASE64.encode(seal(plaintext.as_bytes(), key)?))
+}
+
+/// Decrypt ciphertext using AES-256-GCM.
+/// Expects base64(nonce || ciphertext || tag).
+pub fn decrypt(ciphertext: &str, key: &[u8; KEY_SIZE]) -> Result<String> {
+    let combined = BASE64
+        .decode(ciphertext)
+        .map_err(|e| anyhow!("Base64 decode failed: {}", e))?;
+
+    let plaintext = open(&combined, key)?;
+    String::from_utf8(plaintext).map_err(|e| anyhow!("UTF-8 decode failed: {}", e))
+}
+
+/// Derive a 256-bit key from a user passphrase and a random salt.
+pub fn derive_key_from_passphrase(passphrase: &str, salt: &[u8]) -> [u8; KEY_SIZE] {
+    let mut key = [0u8; KEY_SIZE];
+    pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, PBKDF2_ITERATIONS, &mut key);
+    key
+}
+
+/// Encrypt plaintext with a passphrase, for data at rest such as backups.
+/// Returns base64(salt || nonce || ciphertext || tag).
+pub fn encrypt_with_passphrase(plaintext: &str, passphrase: &str) -> Result<String> {
+    let mut salt = [0u8; PASSPHRASE_SALT_SIZE];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_key_from_passphrase(passphrase, &salt);
+
+    let mut combined = salt.to_vec();
+    combined.extend_from_slice(&seal(plaintext.as_bytes(), &key)?);
+    Ok(BASE64.encode(combined))
+}
+
+/// Decrypt ciphertext made by [`encrypt_with_passphrase`].
+/// Fails on a wrong passphrase, the GCM tag doesn't verify then.
+pub fn decrypt_with_passphrase(ciphertext: &str, passphrase: &str) -> Result<String> {
+    let combined = BASE64
+        .decode(ciphertext.trim())
+        .map_err(|e| anyhow!("Base64 decode failed: {}", e))?;
+
+    if combined.len() < PASSPHRASE_SALT_SIZE {
+        return Err(anyhow!("Ciphertext too short"));
+    }
+
+    let (salt, sealed) = combined.split_at(PASSPHRASE_SALT_SIZE);
+    let key = derive_key_from_passphrase(passphrase, salt);
+    let plaintext = open(sealed, &key)?;
+    String::from_utf8(plaintext).map_err(|e| anyhow!("UTF-8 decode failed: {}", e))
+}
+
+/// AES-256-GCM encrypt with a random nonce, giving nonce || ciphertext || tag.
+fn seal(plaintext: &[u8], key: &[u8; KEY_SIZE]) -> Result<Vec<u8>> {
     let cipher =
         Aes256Gcm::new_from_slice(key).map_err(|e| anyhow!("Failed to create cipher: {}", e))?;
 
@@ -123,24 +176,18 @@ pub fn encrypt(plaintext: &str, key: &[u8; KEY_SIZE]) -> Result<String> {
 
     // Encrypt
     let ciphertext = cipher
-        .encrypt(nonce, plaintext.as_bytes())
+        .encrypt(nonce, plaintext)
         .map_err(|e| anyhow!("Encryption failed: {}", e))?;
 
     // Combine nonce and ciphertext
     let mut combined = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
     combined.extend_from_slice(&nonce_bytes);
     combined.extend_from_slice(&ciphertext);
-
-    Ok(BASE64.encode(combined))
+    Ok(combined)
 }
 
-/// Decrypt ciphertext using AES-256-GCM.
-/// Expects base64(nonce || ciphertext || tag).
-pub fn decrypt(ciphertext: &str, key: &[u8; KEY_SIZE]) -> Result<String> {
-    let combined = BASE64
-        .decode(ciphertext)
-        .map_err(|e| anyhow!("Base64 decode failed: {}", e))?;
-
+/// Reverse of [`seal`].
+fn open(combined: &[u8], key: &[u8; KEY_SIZE]) -> Result<Vec<u8>> {
     if combined.len() < NONCE_SIZE {
         return Err(anyhow!("Ciphertext too short"));
     }
@@ -151,11 +198,9 @@ pub fn decrypt(ciphertext: &str, key: &[u8; KEY_SIZE]) -> Result<String> {
     let cipher =
         Aes256Gcm::new_from_slice(key).map_err(|e| anyhow!("Failed to create cipher: {}", e))?;
 
-    let plaintext = cipher
+    cipher
         .decrypt(nonce, ciphertext_bytes)
-        .map_err(|e| anyhow!("Decryption failed: {}", e))?;
-
-    String::from_utf8(plaintext).map_err(|e| anyhow!("UTF-8 decode failed: {}", e))
+        .map_err(|e| anyhow!("Decryption failed: {}", e))
 }
 
 /// Calculate SHA-256 checksum (first 8 hex characters).
@@ -203,6 +248,20 @@ mod tests {
         assert_ne!(plaintext, encrypted);
     }
 
+    #[test]
+    fn test_passphrase_round_trip() {
+        let encrypted = encrypt_with_passphrase("backup", "correct horse").unwrap();
+        assert_eq!(
+            decrypt_with_passphrase(&encrypted, "correct horse").unwrap(),
+            "backup"
+        );
+        assert!(decrypt_with_passphrase(&encrypted, "wrong horse").is_err());
+
+        // Random salt: the same input encrypts differently
+        let again = encrypt_with_passphrase("backup", "correct horse").unwrap();
+        assert_ne!(encrypted, again);
+    }
+
     #[test]
     fn test_checksum() {
         let key = derive_key("123456", "android-abc", "linux-xyz");
@@ -0,0 +1,89 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Speech2Prompt wire protocol.
+//!
+//! The pieces both ends of a Speech2Prompt connection need, independent of
+//! the BLE stack:
+//!
+//! - [`message`]: JSON messages and their payloads
+//! - [`crypto`]: key derivation (PIN or X25519 ECDH), AES-256-GCM payload
+//!   encryption and message checksums
+//! - [`packet`] and [`reassembler`]: splitting messages into MTU-sized
+//!   packets and putting them back together
+//! - [`ack`]: matching ACKs to the messages they acknowledge
+//! - [`rekey`]: rotating the session key of a long-lived connection
+//!
+//! See `PROTOCOL.md` next to this crate for the format description.
+//!
+//! # Example
+//!
+//! Pair, then send an encrypted message over a default-MTU link:
+//!
+//! ```
+//! use speech2prompt_protocol::crypto::ecdh::EcdhKeypair;
+//! use speech2prompt_protocol::packet::config::DEFAULT_MTU;
+//! use speech2prompt_protocol::{
+//!     chunk_message, CryptoContext, Message, MessageReassembler, MessageType,
+//! };
+//!
+//! // Both sides derive the same key from an X25519 exchange
+//! let phone_keys = EcdhKeypair::generate();
+//! let desktop_keys = EcdhKeypair::generate();
+//! let phone_public = phone_keys.public_key_bytes();
+//! let desktop_public = desktop_keys.public_key_bytes();
+//! let phone = CryptoContext::from_ecdh(
+//!     &phone_keys.compute_shared_secret(&desktop_public),
+//!     "android-id",
+//!     "linux-id",
+//! );
+//! let desktop = CryptoContext::from_ecdh(
+//!     &desktop_keys.compute_shared_secret(&phone_public),
+//!     "android-id",
+//!     "linux-id",
+//! );
+//!
+//! // Phone: encode, encrypt and chunk
+//! let mut message = Message::new(MessageType::Text, "Hello from the phone, typed on the desktop!");
+//! message.sign_and_encrypt(&phone)?;
+//! let packets = chunk_message(message.to_json()?.as_bytes(), DEFAULT_MTU);
+//! assert!(packets.len() > 1);
+//!
+//! // Desktop: reassemble and decrypt
+//! let mut reassembler = MessageReassembler::new();
+//! let data = packets
+//!     .iter()
+//!     .find_map(|packet| reassembler.process_packet(packet))
+//!     .expect("complete message");
+//! let mut received = Message::from_json(std::str::from_utf8(&data)?)?;
+//! received.verify_and_decrypt(&desktop)?;
+//! assert_eq!(received.payload, "Hello from the phone, typed on the desktop!");
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+pub mod ack;
+pub mod crypto;
+pub mod message;
+pub mod packet;
+pub mod reassembler;
+pub mod rekey;
+
+pub use ack::{AckTimeout, PendingAcks};
+pub use crypto::CryptoContext;
+pub use message::{
+    CommandCode, HeartbeatPayload, Message, MessageType, PairAckPayload, PairRequestPayload,
+    PairStatus, WordPayload, PROTOCOL_VERSION,
+};
+pub use reassembler::{chunk_message, MessageReassembler, ReassemblyError};
+pub use rekey::{RekeyPayload, RekeyPolicy, SessionRekey, REKEY_ANSWER_TIMEOUT};
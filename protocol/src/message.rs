@@ -40,6 +40,8 @@ pub enum MessageType {
     PairReq,
     #[serde(rename = "PAIR_ACK")]
     PairAck,
+    #[serde(rename = "REKEY")]
+    Rekey,
 }
 
 impl MessageType {
@@ -53,6 +55,7 @@ impl MessageType {
             Self::Ack => "ACK",
             Self::PairReq => "PAIR_REQ",
             Self::PairAck => "PAIR_ACK",
+            Self::Rekey => "REKEY",
         }
     }
 }
@@ -123,6 +126,7 @@ impl Message {
                 | MessageType::Command
                 | MessageType::PairReq
                 | MessageType::PairAck
+                | MessageType::Rekey
         ) {
             self.payload = ctx.encrypt(&self.payload)?;
         }
@@ -156,6 +160,7 @@ impl Message {
                 | MessageType::Command
                 | MessageType::PairReq
                 | MessageType::PairAck
+                | MessageType::Rekey
         ) {
             self.payload = ctx.decrypt(&self.payload)?;
         }
@@ -190,6 +195,9 @@ pub struct WordPayload {
     /// Optional timestamp for logging
     #[serde(default)]
     pub ts: Option<u64>,
+    /// Marks the end of an utterance. The word may be empty for a bare marker.
+    #[serde(default)]
+    pub eou: bool,
 }
 
 impl WordPayload {
@@ -198,6 +206,27 @@ impl WordPayload {
     }
 }
 
+/// Payload for HEARTBEAT messages.
+///
+/// Older phones send an empty payload, which reads like one without fields.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeartbeatPayload {
+    /// Battery level of the phone in percent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub battery: Option<u8>,
+}
+
+impl HeartbeatPayload {
+    pub fn from_json(json: &str) -> Result<Self> {
+        if json.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        let mut heartbeat: Self = serde_json::from_str(json)?;
+        heartbeat.battery = heartbeat.battery.filter(|level| *level <= 100);
+        Ok(heartbeat)
+    }
+}
+
 /// Pairing request payload.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PairRequestPayload {
@@ -205,6 +234,9 @@ pub struct PairRequestPayload {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub device_name: Option<String>,
     pub public_key: String,
+    /// Desktop public key scanned from its pairing QR code, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scanned_key: Option<String>,
 }
 
 impl PairRequestPayload {
@@ -263,11 +295,31 @@ impl PairAckPayload {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CommandCode {
     Enter,
+    NewParagraph,
     SelectAll,
+    ClearField,
+    SelectWord,
+    SelectLine,
     Copy,
     Paste,
     Cut,
+    Indent,
+    Dedent,
+    CapsLock,
+    DuplicateLine,
+    Click,
+    RightClick,
+    DoubleClick,
+    ScrollUp,
+    ScrollDown,
+    InsertDate,
+    InsertTime,
     Cancel,
+    CancelTyping,
+    Play,
+    Pause,
+    NextTrack,
+    PreviousTrack,
 }
 
 impl CommandCode {
@@ -275,11 +327,31 @@ impl CommandCode {
     pub fn parse(s: &str) -> Option<Self> {
         match s.trim().to_uppercase().as_str() {
             "ENTER" => Some(Self::Enter),
+            "NEW_PARAGRAPH" => Some(Self::NewParagraph),
             "SELECT_ALL" => Some(Self::SelectAll),
+            "CLEAR_FIELD" => Some(Self::ClearField),
+            "SELECT_WORD" => Some(Self::SelectWord),
+            "SELECT_LINE" => Some(Self::SelectLine),
             "COPY" => Some(Self::Copy),
             "PASTE" => Some(Self::Paste),
             "CUT" => Some(Self::Cut),
+            "INDENT" => Some(Self::Indent),
+            "DEDENT" => Some(Self::Dedent),
+            "CAPS_LOCK" => Some(Self::CapsLock),
+            "DUPLICATE_LINE" => Some(Self::DuplicateLine),
+            "CLICK" => Some(Self::Click),
+            "RIGHT_CLICK" => Some(Self::RightClick),
+            "DOUBLE_CLICK" => Some(Self::DoubleClick),
+            "SCROLL_UP" => Some(Self::ScrollUp),
+            "SCROLL_DOWN" => Some(Self::ScrollDown),
+            "INSERT_DATE" => Some(Self::InsertDate),
+            "INSERT_TIME" => Some(Self::InsertTime),
             "CANCEL" => Some(Self::Cancel),
+            "CANCEL_TYPING" => Some(Self::CancelTyping),
+            "PLAY" => Some(Self::Play),
+            "PAUSE" => Some(Self::Pause),
+            "NEXT_TRACK" => Some(Self::NextTrack),
+            "PREVIOUS_TRACK" => Some(Self::PreviousTrack),
             _ => None,
         }
     }
@@ -347,9 +419,71 @@ mod tests {
             CommandCode::parse("SELECT_ALL"),
             Some(CommandCode::SelectAll)
         );
+        assert_eq!(
+            CommandCode::parse("NEXT_TRACK"),
+            Some(CommandCode::NextTrack)
+        );
+        assert_eq!(
+            CommandCode::parse("double_click"),
+            Some(CommandCode::DoubleClick)
+        );
+        assert_eq!(
+            CommandCode::parse("SCROLL_DOWN"),
+            Some(CommandCode::ScrollDown)
+        );
+        assert_eq!(
+            CommandCode::parse("clear_field"),
+            Some(CommandCode::ClearField)
+        );
+        assert_eq!(
+            CommandCode::parse("DUPLICATE_LINE"),
+            Some(CommandCode::DuplicateLine)
+        );
+        assert_eq!(
+            CommandCode::parse("INSERT_DATE"),
+            Some(CommandCode::InsertDate)
+        );
         assert_eq!(CommandCode::parse("invalid"), None);
     }
 
+    #[test]
+    fn test_word_payload_end_of_utterance() {
+        let word = WordPayload::from_json(r#"{"word":"hello","session":"s1","seq":5}"#).unwrap();
+        assert!(!word.eou);
+
+        let marker = WordPayload::from_json(r#"{"word":"","session":"s1","eou":true}"#).unwrap();
+        assert!(marker.eou);
+        assert!(marker.word.is_empty());
+    }
+
+    #[test]
+    fn test_heartbeat_battery() {
+        let heartbeat = HeartbeatPayload::from_json(r#"{"battery":80}"#).unwrap();
+        assert_eq!(heartbeat.battery, Some(80));
+
+        assert_eq!(HeartbeatPayload::from_json("").unwrap().battery, None);
+        assert_eq!(HeartbeatPayload::from_json("{}").unwrap().battery, None);
+        // Not a percentage
+        let heartbeat = HeartbeatPayload::from_json(r#"{"battery":150}"#).unwrap();
+        assert_eq!(heartbeat.battery, None);
+        assert!(HeartbeatPayload::from_json(r#"{"battery":-1}"#).is_err());
+    }
+
+    #[test]
+    fn test_pair_request_scanned_key() {
+        let request = PairRequestPayload::from_json(
+            r#"{"device_id":"android-123","public_key":"android-public-key"}"#,
+        )
+        .unwrap();
+        assert_eq!(request.scanned_key, None);
+
+        let request = PairRequestPayload::from_json(
+            r#"{"device_id":"android-123","public_key":"android-public-key","scanned_key":"desktop-key"}"#,
+        )
+        .unwrap();
+        assert_eq!(request.scanned_key.as_deref(), Some("desktop-key"));
+    }
+
     #[test]
     fn test_pair_ack_payload() {
         let ack = PairAckPayload::success_with_key("linux-456", "linux-public-key");
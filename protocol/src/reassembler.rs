@@ -14,13 +14,42 @@
 
 //! BLE packet reassembly logic.
 
+use std::fmt;
 use tracing::{debug, warn};
 
-use super::ble_constants::flags;
+use crate::packet::flags;
+
+/// Why a partly received message was dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReassemblyError {
+    /// A continuation packet arrived out of order.
+    Sequence { expected: u8, got: u8 },
+    /// The last packet arrived but the size doesn't match the header.
+    Length { expected: usize, got: usize },
+}
+
+impl fmt::Display for ReassemblyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sequence { expected, got } => {
+                write!(f, "Sequence error: expected {}, got {}", expected, got)
+            }
+            Self::Length { expected, got } => {
+                write!(f, "Length mismatch: expected {}, got {}", expected, got)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReassemblyError {}
 
 /// Handles reassembly of BLE packets into complete messages.
+///
+/// Sequence numbers are one byte and count modulo 256, so messages longer
+/// than 256 packets wrap from 255 back to 0.
 pub struct MessageReassembler {
     buffer: Vec<u8>,
+    first_packet: Vec<u8>,
     expected_length: usize,
     expected_seq: u8,
     in_progress: bool,
@@ -31,6 +60,7 @@ impl MessageReassembler {
     pub fn new() -> Self {
         Self {
             buffer: Vec::with_capacity(4096),
+            first_packet: Vec::new(),
             expected_length: 0,
             expected_seq: 0,
             in_progress: false,
@@ -40,11 +70,26 @@ impl MessageReassembler {
     /// Process an incoming BLE packet.
     ///
     /// Returns `Some(complete_message)` when a full message is reassembled,
-    /// otherwise returns `None`.
+    /// otherwise returns `None`. Dropped messages are only logged, use
+    /// [`try_process_packet`](Self::try_process_packet) to handle them.
     pub fn process_packet(&mut self, packet: &[u8]) -> Option<Vec<u8>> {
+        self.try_process_packet(packet).unwrap_or_else(|e| {
+            warn!("{}", e);
+            None
+        })
+    }
+
+    /// Process an incoming BLE packet, reporting dropped messages.
+    ///
+    /// Like [`process_packet`](Self::process_packet), but returns an error
+    /// when the message in progress was corrupted and had to be dropped.
+    pub fn try_process_packet(
+        &mut self,
+        packet: &[u8],
+    ) -> Result<Option<Vec<u8>>, ReassemblyError> {
         if packet.len() < 2 {
             warn!("Packet too short: {} bytes", packet.len());
-            return None;
+            return Ok(None);
         }
 
         let flags = packet[0];
@@ -56,10 +101,26 @@ impl MessageReassembler {
             // Start of new message
             if packet.len() < 4 {
                 warn!("First packet too short: {} bytes", packet.len());
-                return None;
+                return Ok(None);
+            }
+
+            if self.in_progress {
+                // The phone resends the first packet when it thinks the
+                // write timed out
+                if packet == self.first_packet.as_slice() {
+                    debug!("Ignoring duplicate first packet");
+                    return Ok(None);
+                }
+                warn!(
+                    "New message started mid-message, discarding {} of {} bytes",
+                    self.buffer.len(),
+                    self.expected_length
+                );
             }
 
             self.buffer.clear();
+            self.first_packet.clear();
+            self.first_packet.extend_from_slice(packet);
             self.expected_length = u16::from_le_bytes([packet[2], packet[3]]) as usize;
             self.expected_seq = 0;
             self.in_progress = true;
@@ -74,12 +135,12 @@ impl MessageReassembler {
         } else if self.in_progress {
             // Continuation packet
             if seq != self.expected_seq {
-                warn!(
-                    "Sequence error: expected {}, got {}",
-                    self.expected_seq, seq
-                );
+                let error = ReassemblyError::Sequence {
+                    expected: self.expected_seq,
+                    got: seq,
+                };
                 self.reset();
-                return None;
+                return Err(error);
             }
 
             // Payload starts at byte 2 for continuation packets
@@ -87,9 +148,10 @@ impl MessageReassembler {
         } else {
             // Received continuation without start
             warn!("Received continuation packet without start");
-            return None;
+            return Ok(None);
         }
 
+        // 255 is followed by 0 (see chunk_message)
         self.expected_seq = self.expected_seq.wrapping_add(1);
 
         if is_last {
@@ -97,23 +159,24 @@ impl MessageReassembler {
 
             if self.buffer.len() == self.expected_length {
                 debug!("Message reassembly complete: {} bytes", self.buffer.len());
-                return Some(std::mem::take(&mut self.buffer));
+                return Ok(Some(std::mem::take(&mut self.buffer)));
             } else {
-                warn!(
-                    "Length mismatch: expected {}, got {}",
-                    self.expected_length,
-                    self.buffer.len()
-                );
+                let error = ReassemblyError::Length {
+                    expected: self.expected_length,
+                    got: self.buffer.len(),
+                };
                 self.reset();
+                return Err(error);
             }
         }
 
-        None
+        Ok(None)
     }
 
     /// Reset the reassembler state.
     pub fn reset(&mut self) {
         self.buffer.clear();
+        self.first_packet.clear();
         self.expected_length = 0;
         self.expected_seq = 0;
         self.in_progress = false;
@@ -140,7 +203,7 @@ impl Default for MessageReassembler {
 
 /// Helper function to chunk a message into BLE packets.
 pub fn chunk_message(data: &[u8], mtu: usize) -> Vec<Vec<u8>> {
-    use super::ble_constants::config;
+    use crate::packet::config;
 
     if data.is_empty() {
         return vec![];
@@ -169,7 +232,7 @@ pub fn chunk_message(data: &[u8], mtu: usize) -> Vec<Vec<u8>> {
         }
         packet.push(flags_byte);
 
-        // Sequence number
+        // Sequence number, modulo 256
         packet.push(seq);
         seq = seq.wrapping_add(1);
 
@@ -193,6 +256,7 @@ pub fn chunk_message(data: &[u8], mtu: usize) -> Vec<Vec<u8>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::packet::config;
 
     #[test]
     fn test_single_packet_message() {
@@ -255,6 +319,62 @@ mod tests {
         assert!(!reassembler.is_in_progress());
     }
 
+    #[test]
+    fn test_identical_duplicate_first_packet_is_ignored() {
+        let mut reassembler = MessageReassembler::new();
+
+        let packet1 = vec![0x08, 0x00, 0x0A, 0x00, b'h', b'e', b'l', b'l', b'o'];
+        assert!(reassembler.process_packet(&packet1).is_none());
+        // Retransmitted before the rest arrives
+        assert!(reassembler.process_packet(&packet1).is_none());
+        assert_eq!(reassembler.buffer_size(), 5);
+
+        let packet2 = vec![0x04, 0x01, b'w', b'o', b'r', b'l', b'd'];
+        assert_eq!(
+            reassembler.process_packet(&packet2),
+            Some(b"helloworld".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_different_first_packet_restarts() {
+        let mut reassembler = MessageReassembler::new();
+
+        let packet1 = vec![0x08, 0x00, 0x0A, 0x00, b'h', b'e', b'l', b'l', b'o'];
+        assert!(reassembler.process_packet(&packet1).is_none());
+
+        // A new message replaces the unfinished one
+        let packet2 = vec![0x0C, 0x00, 0x03, 0x00, b'n', b'e', b'w'];
+        assert_eq!(reassembler.process_packet(&packet2), Some(b"new".to_vec()));
+        assert!(!reassembler.is_in_progress());
+    }
+
+    #[test]
+    fn test_try_process_packet_reports_dropped_messages() {
+        let mut reassembler = MessageReassembler::new();
+
+        let packet1 = vec![0x08, 0x00, 0x0A, 0x00, b'h', b'e', b'l', b'l', b'o'];
+        assert_eq!(reassembler.try_process_packet(&packet1), Ok(None));
+        let packet2 = vec![0x04, 0x02, b'w', b'o', b'r', b'l', b'd'];
+        assert_eq!(
+            reassembler.try_process_packet(&packet2),
+            Err(ReassemblyError::Sequence {
+                expected: 1,
+                got: 2
+            })
+        );
+
+        assert_eq!(reassembler.try_process_packet(&packet1), Ok(None));
+        let packet2 = vec![0x04, 0x01, b'w', b'o', b'w'];
+        assert_eq!(
+            reassembler.try_process_packet(&packet2),
+            Err(ReassemblyError::Length {
+                expected: 10,
+                got: 8
+            })
+        );
+    }
+
     #[test]
     fn test_length_mismatch() {
         let mut reassembler = MessageReassembler::new();
@@ -323,4 +443,25 @@ mod tests {
 
         assert_eq!(result, Some(original_data.to_vec()));
     }
+
+    #[test]
+    fn test_roundtrip_more_than_256_packets() {
+        // Sequence numbers wrap from 255 back to 0 twice
+        let original_data: Vec<u8> = (0..10_000).map(|i| (i % 251) as u8).collect();
+        let mtu = config::DEFAULT_MTU;
+
+        let packets = chunk_message(&original_data, mtu);
+        assert!(packets.len() > 512);
+        assert_eq!(packets[256][1], 0);
+
+        let mut reassembler = MessageReassembler::new();
+        let mut result = None;
+        for packet in packets {
+            if let Some(msg) = reassembler.process_packet(&packet) {
+                result = Some(msg);
+            }
+        }
+
+        assert_eq!(result, Some(original_data));
+    }
 }
@@ -0,0 +1,298 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Session re-keying.
+//!
+//! A long-lived session can switch to a fresh key without re-pairing. The
+//! initiator sends a `REKEY` message with a new X25519 public key, the peer
+//! answers with its own, and both derive the next [`CryptoContext`] from the
+//! new shared secret. Both `REKEY` messages are signed and encrypted with the
+//! old key, so only the paired peer can start or answer one.
+//!
+//! Each side switches at a defined point: the responder right after sending
+//! its answer, the initiator when the answer arrives. Until then the old key
+//! stays in use, so a peer that doesn't know `REKEY` just keeps the old key.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::crypto::ecdh::EcdhKeypair;
+use crate::crypto::CryptoContext;
+use crate::message::{Message, MessageType};
+
+/// How long a `REKEY` we sent waits for its answer. After that it is
+/// abandoned, and a later answer is rejected.
+pub const REKEY_ANSWER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Payload of `REKEY` messages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RekeyPayload {
+    /// Sender's fresh X25519 public key (base64).
+    pub public_key: String,
+}
+
+/// When a session key is due for rotation. `None` disables a limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RekeyPolicy {
+    /// Rotate after the key has been in use this long.
+    pub interval: Option<Duration>,
+    /// Rotate after this many messages under the key.
+    pub max_messages: Option<u64>,
+}
+
+impl RekeyPolicy {
+    /// Whether any limit is set.
+    pub fn is_enabled(&self) -> bool {
+        self.interval.is_some() || self.max_messages.is_some()
+    }
+}
+
+/// Re-keying state of one side of a session.
+pub struct SessionRekey {
+    android_id: String,
+    linux_id: String,
+    /// Our keypair of a `REKEY` we sent and that isn't answered yet, and
+    /// when it was sent.
+    pending: Option<(EcdhKeypair, Instant)>,
+    /// Messages under the current key.
+    messages: u64,
+    /// When the current key came into use.
+    since: Instant,
+}
+
+impl SessionRekey {
+    /// Start tracking a session whose key came into use at `now`.
+    pub fn new(android_id: impl Into<String>, linux_id: impl Into<String>, now: Instant) -> Self {
+        Self {
+            android_id: android_id.into(),
+            linux_id: linux_id.into(),
+            pending: None,
+            messages: 0,
+            since: now,
+        }
+    }
+
+    /// Count a message sent or received under the current key.
+    pub fn count_message(&mut self) {
+        self.messages += 1;
+    }
+
+    /// Whether a `REKEY` we sent is waiting for its answer.
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Whether the current key is due for rotation under `policy`.
+    ///
+    /// Never while a rotation waits for its answer, unless that took longer
+    /// than [`REKEY_ANSWER_TIMEOUT`].
+    pub fn is_due(&self, policy: &RekeyPolicy, now: Instant) -> bool {
+        if let Some((_, sent)) = &self.pending {
+            if now.duration_since(*sent) < REKEY_ANSWER_TIMEOUT {
+                return false;
+            }
+        }
+        let expired = policy
+            .interval
+            .is_some_and(|interval| now.duration_since(self.since) >= interval);
+        let used_up = policy.max_messages.is_some_and(|max| self.messages >= max);
+        expired || used_up
+    }
+
+    /// Initiator: build a `REKEY` request sent at `now`, to be sent under
+    /// the current key.
+    pub fn start(&mut self, now: Instant) -> Result<Message> {
+        let keypair = EcdhKeypair::generate();
+        let payload = RekeyPayload {
+            public_key: keypair.public_key_base64(),
+        };
+        let message = Message::new(MessageType::Rekey, serde_json::to_string(&payload)?);
+        self.pending = Some((keypair, now));
+        Ok(message)
+    }
+
+    /// Initiator: derive the next key from the peer's answer.
+    ///
+    /// `answer` must already be verified and decrypted with the old key.
+    pub fn finish(&mut self, answer: &Message, now: Instant) -> Result<CryptoContext> {
+        let (keypair, sent) = self
+            .pending
+            .take()
+            .ok_or_else(|| anyhow!("No re-key in progress"))?;
+        if now.duration_since(sent) >= REKEY_ANSWER_TIMEOUT {
+            return Err(anyhow!("Re-key answer arrived too late"));
+        }
+        let payload: RekeyPayload = serde_json::from_str(&answer.payload)?;
+        let shared_secret = keypair.compute_shared_secret_base64(&payload.public_key)?;
+        Ok(self.switch(&shared_secret, now))
+    }
+
+    /// Responder: answer a `REKEY` request.
+    ///
+    /// `request` must already be verified and decrypted with the old key.
+    /// Returns the answer, to be sent under the old key, and the key to
+    /// switch to once it is sent.
+    pub fn respond(&mut self, request: &Message, now: Instant) -> Result<(Message, CryptoContext)> {
+        let payload: RekeyPayload = serde_json::from_str(&request.payload)?;
+        let keypair = EcdhKeypair::generate();
+        let answer = RekeyPayload {
+            public_key: keypair.public_key_base64(),
+        };
+        let shared_secret = keypair.compute_shared_secret_base64(&payload.public_key)?;
+
+        let message = Message::new(MessageType::Rekey, serde_json::to_string(&answer)?);
+        // Crossed requests: answering the peer's settles it, ours is void
+        self.pending = None;
+        Ok((message, self.switch(&shared_secret, now)))
+    }
+
+    /// Derive the key for `shared_secret` and restart the counters.
+    fn switch(&mut self, shared_secret: &[u8; 32], now: Instant) -> CryptoContext {
+        self.messages = 0;
+        self.since = now;
+        CryptoContext::from_ecdh(shared_secret, &self.android_id, &self.linux_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Both sides of a session paired with the same key.
+    fn paired() -> (CryptoContext, CryptoContext) {
+        let key = [7u8; 32];
+        (
+            CryptoContext::from_ecdh(&key, "android-1", "linux-1"),
+            CryptoContext::from_ecdh(&key, "android-1", "linux-1"),
+        )
+    }
+
+    fn encrypted(mut message: Message, ctx: &CryptoContext) -> Message {
+        message.sign_and_encrypt(ctx).unwrap();
+        message
+    }
+
+    fn encrypted_text(ctx: &CryptoContext, text: &str) -> Message {
+        encrypted(Message::new(MessageType::Text, text), ctx)
+    }
+
+    #[test]
+    fn test_policy() {
+        let now = Instant::now();
+        let mut rekey = SessionRekey::new("android-1", "linux-1", now);
+        let policy = RekeyPolicy {
+            interval: Some(Duration::from_secs(3600)),
+            max_messages: Some(2),
+        };
+        assert!(policy.is_enabled());
+        assert!(!RekeyPolicy::default().is_enabled());
+
+        assert!(!rekey.is_due(&policy, now));
+        rekey.count_message();
+        rekey.count_message();
+        assert!(rekey.is_due(&policy, now));
+
+        let rekey = SessionRekey::new("android-1", "linux-1", now);
+        assert!(rekey.is_due(&policy, now + Duration::from_secs(3600)));
+        assert!(!rekey.is_due(&RekeyPolicy::default(), now + Duration::from_secs(86400)));
+    }
+
+    #[test]
+    fn test_messages_use_old_key_before_and_new_key_after() {
+        let now = Instant::now();
+        let (desktop_old, phone_old) = paired();
+        let mut desktop = SessionRekey::new("android-1", "linux-1", now);
+        let mut phone = SessionRekey::new("android-1", "linux-1", now);
+
+        // Before: the old key works
+        let mut before = encrypted_text(&phone_old, "before");
+        before.verify_and_decrypt(&desktop_old).unwrap();
+        assert_eq!(before.payload, "before");
+
+        // Desktop starts, phone answers and switches
+        let mut request = encrypted(desktop.start(now).unwrap(), &desktop_old);
+        assert!(desktop.is_pending());
+        let always = RekeyPolicy {
+            interval: None,
+            max_messages: Some(0),
+        };
+        assert!(!desktop.is_due(&always, now));
+        request.verify_and_decrypt(&phone_old).unwrap();
+        let (answer, phone_new) = phone.respond(&request, now).unwrap();
+        let mut answer = encrypted(answer, &phone_old);
+
+        // The answer is still under the old key, then desktop switches
+        answer.verify_and_decrypt(&desktop_old).unwrap();
+        let desktop_new = desktop.finish(&answer, now).unwrap();
+        assert!(!desktop.is_pending());
+
+        // After: the new key works, the old one doesn't
+        let mut after = encrypted_text(&phone_new, "after");
+        let mut stale = after.clone();
+        after.verify_and_decrypt(&desktop_new).unwrap();
+        assert_eq!(after.payload, "after");
+        assert!(stale.verify_and_decrypt(&desktop_old).is_err());
+
+        // And the other way round
+        let mut reply = encrypted_text(&desktop_new, "reply");
+        reply.verify_and_decrypt(&phone_new).unwrap();
+        assert_eq!(reply.payload, "reply");
+    }
+
+    #[test]
+    fn test_rekey_request_needs_old_key() {
+        let now = Instant::now();
+        let (desktop_old, _) = paired();
+        let stranger = CryptoContext::from_ecdh(&[9u8; 32], "android-1", "linux-1");
+        let mut desktop = SessionRekey::new("android-1", "linux-1", now);
+
+        let mut request = encrypted(desktop.start(now).unwrap(), &desktop_old);
+        assert!(request.verify_and_decrypt(&stranger).is_err());
+    }
+
+    #[test]
+    fn test_unanswered_rekey_is_abandoned() {
+        let now = Instant::now();
+        let (desktop_old, phone_old) = paired();
+        let mut desktop = SessionRekey::new("android-1", "linux-1", now);
+        let mut phone = SessionRekey::new("android-1", "linux-1", now);
+        let always = RekeyPolicy {
+            interval: None,
+            max_messages: Some(0),
+        };
+
+        let mut request = encrypted(desktop.start(now).unwrap(), &desktop_old);
+        assert!(!desktop.is_due(&always, now + REKEY_ANSWER_TIMEOUT / 2));
+        // The answer got lost, so rotating is tried again
+        let later = now + REKEY_ANSWER_TIMEOUT;
+        assert!(desktop.is_due(&always, later));
+
+        // A late answer doesn't switch the key
+        request.verify_and_decrypt(&phone_old).unwrap();
+        let (answer, _) = phone.respond(&request, now).unwrap();
+        let mut answer = encrypted(answer, &phone_old);
+        answer.verify_and_decrypt(&desktop_old).unwrap();
+        assert!(desktop.finish(&answer, later).is_err());
+        assert!(!desktop.is_pending());
+    }
+
+    #[test]
+    fn test_finish_without_request_fails() {
+        let now = Instant::now();
+        let mut desktop = SessionRekey::new("android-1", "linux-1", now);
+        let answer = Message::new(MessageType::Rekey, r#"{"public_key":"x"}"#);
+        assert!(desktop.finish(&answer, now).is_err());
+    }
+}